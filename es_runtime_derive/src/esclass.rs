@@ -0,0 +1,418 @@
+//! the #[es_class] attribute macro, see es_runtime::esclass for the supported attributes
+
+use crate::{lit_str, parse_es_attrs};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, AttributeArgs, FnArg, ImplItem, ImplItemMethod, Item, ItemImpl, ItemStruct, Lit,
+    Meta, NestedMeta, ReturnType, Type,
+};
+
+pub(crate) fn expand_es_class(args: AttributeArgs, item: Item) -> syn::Result<TokenStream2> {
+    match item {
+        Item::Struct(item_struct) => expand_struct(args, item_struct),
+        Item::Impl(item_impl) => {
+            if let Some(arg) = args.first() {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "#[es_class] on an impl block does not take arguments, set them on the struct",
+                ));
+            }
+            expand_impl(item_impl)
+        }
+        other => Err(syn::Error::new_spanned(
+            other,
+            "#[es_class] can only be used on a struct and its impl block",
+        )),
+    }
+}
+
+fn expand_struct(args: AttributeArgs, item_struct: ItemStruct) -> syn::Result<TokenStream2> {
+    let mut namespace: Vec<String> = vec![];
+    let mut class_name = item_struct.ident.to_string();
+    for arg in &args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("namespace") => {
+                if let Lit::Str(s) = &nv.lit {
+                    namespace = s
+                        .value()
+                        .split('.')
+                        .filter(|part| !part.is_empty())
+                        .map(|part| part.to_string())
+                        .collect();
+                } else {
+                    return Err(syn::Error::new_spanned(&nv.lit, "expected a string"));
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                if let Lit::Str(s) = &nv.lit {
+                    class_name = s.value();
+                } else {
+                    return Err(syn::Error::new_spanned(&nv.lit, "expected a string"));
+                }
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "unknown es_class argument, expected namespace or name",
+                ))
+            }
+        }
+    }
+
+    let ident = &item_struct.ident;
+    let doc = doc_string(&item_struct.attrs).unwrap_or_default();
+    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
+    Ok(quote! {
+        #item_struct
+
+        impl #impl_generics ::es_runtime::esclass::EsClassMeta for #ident #ty_generics #where_clause {
+            const NAMESPACE: &'static [&'static str] = &[#(#namespace),*];
+            const CLASS_NAME: &'static str = #class_name;
+            const DOC: &'static str = #doc;
+        }
+    })
+}
+
+/// join the /// doc comments of an item, None if it has none
+fn doc_string(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(s) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(line.as_str()).to_string())
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+#[derive(Default)]
+struct MethodAttrs {
+    constructor: bool,
+    getter: Option<Option<String>>,
+    setter: Option<Option<String>>,
+    rename: Option<String>,
+    skip: bool,
+}
+
+/// parse and remove the #[es(...)] attributes of a method
+fn take_method_attrs(method: &mut ImplItemMethod) -> syn::Result<MethodAttrs> {
+    let mut ret = MethodAttrs::default();
+    for (ident, lit) in parse_es_attrs(&method.attrs)? {
+        if ident == "constructor" {
+            ret.constructor = true;
+        } else if ident == "getter" {
+            ret.getter = Some(match lit {
+                Some(lit) => Some(lit_str(&ident, Some(lit))?),
+                None => None,
+            });
+        } else if ident == "setter" {
+            ret.setter = Some(match lit {
+                Some(lit) => Some(lit_str(&ident, Some(lit))?),
+                None => None,
+            });
+        } else if ident == "rename" {
+            ret.rename = Some(lit_str(&ident, lit)?);
+        } else if ident == "skip" {
+            ret.skip = true;
+        } else {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!("unknown es attribute: {}", ident),
+            ));
+        }
+    }
+    method.attrs.retain(|attr| !attr.path.is_ident("es"));
+    Ok(ret)
+}
+
+fn returns_result(output: &ReturnType) -> bool {
+    if let ReturnType::Type(_, ty) = output {
+        if let Type::Path(type_path) = &**ty {
+            if let Some(segment) = type_path.path.segments.last() {
+                return segment.ident == "Result";
+            }
+        }
+    }
+    false
+}
+
+/// convert the result of a call to a Result<EsValueFacade, String>
+fn convert_result(output: &ReturnType, call: TokenStream2) -> TokenStream2 {
+    match output {
+        ReturnType::Default => quote! {
+            {
+                #call;
+                Ok(::es_runtime::esvaluefacade::EsValueFacade::undefined())
+            }
+        },
+        _ if returns_result(output) => quote! {
+            #call
+                .map(::es_runtime::esconversions::ToEsValue::to_es_value_facade)
+                .map_err(|e| e.to_string())
+        },
+        _ => quote! {
+            Ok(::es_runtime::esconversions::ToEsValue::to_es_value_facade(#call))
+        },
+    }
+}
+
+/// statements which convert a Vec<EsValueFacade> called args to the arguments of a method
+fn convert_args(es_name: &str, method: &ImplItemMethod) -> (TokenStream2, Vec<syn::Ident>) {
+    let types: Vec<&Type> = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => Some(&*pat_type.ty),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let arity = types.len();
+    let vars: Vec<syn::Ident> = (0..arity).map(|i| format_ident!("arg_{}", i)).collect();
+    let converts = types.iter().zip(vars.iter()).enumerate().map(|(index, (ty, var))| {
+        quote! {
+            let #var = ::es_runtime::esconversions::from_es_arg::<#ty>(args_iter.next(), #index, #arity, argc)
+                .map_err(|e| format!("{}: {}", #es_name, e))?;
+        }
+    });
+    let stmts = quote! {
+        let argc = args.len();
+        if argc > #arity {
+            return Err(format!("{}: expected {} arguments but got {}", #es_name, #arity, argc));
+        }
+        #[allow(unused_mut, unused_variables)]
+        let mut args_iter = args.into_iter();
+        #(#converts)*
+    };
+    (stmts, vars)
+}
+
+struct PropertyDef {
+    name: String,
+    is_static: bool,
+    getter: Option<TokenStream2>,
+    setter: Option<TokenStream2>,
+}
+
+fn property_def<'a>(
+    props: &'a mut Vec<PropertyDef>,
+    name: &str,
+    is_static: bool,
+) -> &'a mut PropertyDef {
+    let pos = props
+        .iter()
+        .position(|p| p.name == name && p.is_static == is_static);
+    let pos = match pos {
+        Some(pos) => pos,
+        None => {
+            props.push(PropertyDef {
+                name: name.to_string(),
+                is_static,
+                getter: None,
+                setter: None,
+            });
+            props.len() - 1
+        }
+    };
+    &mut props[pos]
+}
+
+fn expand_impl(mut item_impl: ItemImpl) -> syn::Result<TokenStream2> {
+    let self_ty = item_impl.self_ty.clone();
+
+    let mut constructor = None;
+    let mut methods = vec![];
+    let mut static_methods = vec![];
+    let mut properties: Vec<PropertyDef> = vec![];
+    let mut member_docs: Vec<(String, String)> = vec![];
+
+    for impl_item in item_impl.items.iter_mut() {
+        let method = match impl_item {
+            ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        let attrs = take_method_attrs(method)?;
+        if attrs.skip {
+            continue;
+        }
+        let ident = method.sig.ident.clone();
+        let fn_name = ident.to_string();
+        let doc = doc_string(&method.attrs);
+        let output = method.sig.output.clone();
+        let is_static = match method.sig.inputs.first() {
+            Some(FnArg::Receiver(receiver)) => {
+                if receiver.reference.is_none() {
+                    return Err(syn::Error::new_spanned(
+                        receiver,
+                        "es_class methods should take &self or &mut self",
+                    ));
+                }
+                false
+            }
+            _ => true,
+        };
+
+        if attrs.constructor {
+            if !is_static {
+                return Err(syn::Error::new_spanned(
+                    &method.sig,
+                    "a constructor should not take self",
+                ));
+            }
+            let (arg_stmts, vars) = convert_args("constructor", method);
+            let create = if returns_result(&output) {
+                quote! { Self::#ident(#(#vars),*).map_err(|e| e.to_string())? }
+            } else {
+                quote! { Self::#ident(#(#vars),*) }
+            };
+            constructor = Some(quote! {
+                builder.constructor(|args: Vec<::es_runtime::esvaluefacade::EsValueFacade>| {
+                    #arg_stmts
+                    let instance: Self = #create;
+                    Ok(instance)
+                });
+            });
+        } else if let Some(getter_name) = attrs.getter {
+            let name =
+                getter_name.unwrap_or_else(|| fn_name.trim_start_matches("get_").to_string());
+            let getter = if is_static {
+                let call = convert_result(&output, quote! { Self::#ident() });
+                quote! { || { #call } }
+            } else {
+                let call = convert_result(&output, quote! { instance.#ident() });
+                quote! { |instance: &Self| { #call } }
+            };
+            property_def(&mut properties, &name, is_static).getter = Some(getter);
+            // the doc of the getter wins over the doc of the setter
+            if let Some(doc) = doc {
+                member_docs.retain(|(member, _doc)| member != &name);
+                member_docs.push((name, doc));
+            }
+        } else if let Some(setter_name) = attrs.setter {
+            let name =
+                setter_name.unwrap_or_else(|| fn_name.trim_start_matches("set_").to_string());
+            let (arg_stmts, vars) = convert_args(&name, method);
+            let call_to_unit = |call: TokenStream2| {
+                if returns_result(&output) {
+                    quote! { #call.map(|_| ()).map_err(|e| e.to_string()) }
+                } else {
+                    quote! { { #call; Ok(()) } }
+                }
+            };
+            let setter = if is_static {
+                let call = call_to_unit(quote! { Self::#ident(#(#vars),*) });
+                quote! {
+                    |val: ::es_runtime::esvaluefacade::EsValueFacade| {
+                        let args = vec![val];
+                        #arg_stmts
+                        #call
+                    }
+                }
+            } else {
+                let call = call_to_unit(quote! { instance.#ident(#(#vars),*) });
+                quote! {
+                    |instance: &mut Self, val: ::es_runtime::esvaluefacade::EsValueFacade| {
+                        let args = vec![val];
+                        #arg_stmts
+                        #call
+                    }
+                }
+            };
+            property_def(&mut properties, &name, is_static).setter = Some(setter);
+            if let Some(doc) = doc {
+                if !member_docs.iter().any(|(member, _doc)| member == &name) {
+                    member_docs.push((name, doc));
+                }
+            }
+        } else {
+            let es_name = attrs.rename.unwrap_or(fn_name);
+            let (arg_stmts, vars) = convert_args(&es_name, method);
+            if let Some(doc) = doc {
+                member_docs.push((es_name.clone(), doc));
+            }
+            if is_static {
+                let call = convert_result(&output, quote! { Self::#ident(#(#vars),*) });
+                static_methods.push(quote! {
+                    builder.static_method(#es_name, |args: Vec<::es_runtime::esvaluefacade::EsValueFacade>| {
+                        #arg_stmts
+                        #call
+                    });
+                });
+            } else {
+                let call = convert_result(&output, quote! { instance.#ident(#(#vars),*) });
+                methods.push(quote! {
+                    builder.method(#es_name, |instance: &mut Self, args: Vec<::es_runtime::esvaluefacade::EsValueFacade>| {
+                        #arg_stmts
+                        #call
+                    });
+                });
+            }
+        }
+    }
+
+    let properties = properties.into_iter().map(|prop| {
+        let name = prop.name;
+        let default_attributes =
+            quote! { ::es_runtime::esreflection::PropertyAttributes::default() };
+        match (prop.is_static, prop.getter, prop.setter) {
+            (true, Some(getter), Some(setter)) => {
+                quote! { builder.static_property(#name, #getter, #setter); }
+            }
+            (true, Some(getter), None) => {
+                quote! { builder.static_readonly_property(#name, #getter); }
+            }
+            (true, None, setter) => {
+                quote! { builder.static_accessor(#name, None, Some(Box::new(#setter)), #default_attributes); }
+            }
+            (false, Some(getter), Some(setter)) => {
+                quote! { builder.property(#name, #getter, #setter); }
+            }
+            (false, Some(getter), None) => {
+                quote! { builder.readonly_property(#name, #getter); }
+            }
+            (false, None, setter) => {
+                quote! { builder.accessor(#name, None, Some(Box::new(#setter)), #default_attributes); }
+            }
+        }
+    });
+
+    let member_docs = member_docs
+        .into_iter()
+        .map(|(name, doc)| quote! { builder.member_doc(#name, #doc); });
+
+    let (impl_generics, _ty_generics, where_clause) = item_impl.generics.split_for_impl();
+
+    Ok(quote! {
+        #item_impl
+
+        impl #impl_generics ::es_runtime::esclass::EsClass for #self_ty #where_clause {
+            fn register(rt: &::es_runtime::esruntime::EsRuntime) -> ::std::result::Result<::es_runtime::esreflection::EsProxy, String> {
+                let mut builder = ::es_runtime::esreflection::EsTypedProxyBuilder::<Self>::new(
+                    <Self as ::es_runtime::esclass::EsClassMeta>::NAMESPACE.to_vec(),
+                    <Self as ::es_runtime::esclass::EsClassMeta>::CLASS_NAME,
+                );
+                #constructor
+                #(#methods)*
+                #(#properties)*
+                #(#static_methods)*
+                let doc = <Self as ::es_runtime::esclass::EsClassMeta>::DOC;
+                if !doc.is_empty() {
+                    builder.doc(doc);
+                }
+                #(#member_docs)*
+                builder.build(rt)
+            }
+        }
+    })
+}
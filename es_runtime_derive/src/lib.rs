@@ -0,0 +1,719 @@
+//! # es_runtime_derive
+//!
+//! derive macros for the es_runtime::esconversions::ToEsValue and FromEsValue traits and the
+//! #[es_class] attribute macro (see es_runtime::esclass)
+//!
+//! structs with named fields are converted to and from objects, tuple structs to and from arrays
+//! (or to the value of their single field for newtypes), enums with only unit variants to and from
+//! strings and other enums to and from objects with a tag property
+//!
+//! # Attributes
+//!
+//! * `#[es(rename_all = "camelCase")]` on a struct or enum renames all fields or variants,
+//!   supported are camelCase, snake_case, PascalCase, lowercase, UPPERCASE and SCREAMING_SNAKE_CASE
+//! * `#[es(tag = "kind")]` on an enum sets the name of the tag property (default is "type"), setting
+//!   this on an enum with only unit variants converts those to objects instead of strings
+//! * `#[es(content = "data")]` on an enum sets the name of the property which holds the value of tuple
+//!   variants (default is "value")
+//! * `#[es(rename = "name")]` on a field or variant renames that field or variant
+//! * `#[es(default)]` or `#[es(default = "path::to::fn")]` on a field uses Default::default() or the
+//!   given fn when the property is missing or undefined
+//! * `#[es(skip)]` on a field skips the field, it is initialized with Default::default() when converting
+//!   from an EsValueFacade
+//!
+//! default and skip are not supported on enum variants and result in a compile error there
+//!
+//! Option fields which are None are omitted from the object and missing properties are converted to None
+//!
+//! # Example
+//!
+//! ```no_run
+//! use es_runtime::esconversions::{FromEsValue, ToEsValue};
+//!
+//! #[derive(ToEsValue, FromEsValue)]
+//! #[es(rename_all = "camelCase")]
+//! struct User {
+//!     user_name: String,
+//!     #[es(default)]
+//!     login_count: i32,
+//!     email: Option<String>,
+//! }
+//!
+//! #[derive(ToEsValue, FromEsValue)]
+//! #[es(tag = "kind")]
+//! enum Shape {
+//!     Circle { radius: f64 },
+//!     Square(f64),
+//!     Empty,
+//! }
+//! ```
+
+extern crate proc_macro;
+
+mod esclass;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, AttributeArgs, Data, DeriveInput, Fields, GenericParam, Generics,
+    Ident, Item, Lit, Meta, NestedMeta, Type,
+};
+
+/// derive es_runtime::esconversions::ToEsValue
+#[proc_macro_derive(ToEsValue, attributes(es))]
+pub fn derive_to_es_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_to_es_value(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// derive es_runtime::esconversions::FromEsValue
+#[proc_macro_derive(FromEsValue, attributes(es))]
+pub fn derive_from_es_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_es_value(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// create an EsProxy class from a struct and its impl block, see es_runtime::esclass
+#[proc_macro_attribute]
+pub fn es_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    let item = parse_macro_input!(item as Item);
+    match esclass::expand_es_class(args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    rename_all: Option<String>,
+    tag: Option<String>,
+    content: Option<String>,
+}
+
+enum FieldDefault {
+    None,
+    DefaultTrait,
+    Path(syn::Path),
+}
+
+struct FieldAttrs {
+    rename: Option<String>,
+    default: FieldDefault,
+    skip: bool,
+}
+
+struct VariantAttrs {
+    rename: Option<String>,
+}
+
+/// get the name value pairs and flags of all #[es(...)] attributes
+fn parse_es_attrs(attrs: &[Attribute]) -> syn::Result<Vec<(Ident, Option<Lit>)>> {
+    let mut ret = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident("es") {
+            continue;
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(nv)) => {
+                            let ident = nv.path.get_ident().cloned().ok_or_else(|| {
+                                syn::Error::new_spanned(&nv.path, "expected an identifier")
+                            })?;
+                            ret.push((ident, Some(nv.lit)));
+                        }
+                        NestedMeta::Meta(Meta::Path(path)) => {
+                            let ident = path.get_ident().cloned().ok_or_else(|| {
+                                syn::Error::new_spanned(&path, "expected an identifier")
+                            })?;
+                            ret.push((ident, None));
+                        }
+                        other => {
+                            return Err(syn::Error::new_spanned(other, "unsupported es attribute"));
+                        }
+                    }
+                }
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "expected #[es(...)] with a list of options",
+                ));
+            }
+        }
+    }
+    Ok(ret)
+}
+
+fn lit_str(ident: &Ident, lit: Option<Lit>) -> syn::Result<String> {
+    match lit {
+        Some(Lit::Str(s)) => Ok(s.value()),
+        _ => Err(syn::Error::new_spanned(
+            ident,
+            format!("{} requires a string value", ident),
+        )),
+    }
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut ret = ContainerAttrs::default();
+    for (ident, lit) in parse_es_attrs(attrs)? {
+        if ident == "rename_all" {
+            let style = lit_str(&ident, lit)?;
+            if rename(&style, "test_name").is_none() {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    format!("unsupported rename_all style: {}", style),
+                ));
+            }
+            ret.rename_all = Some(style);
+        } else if ident == "tag" {
+            ret.tag = Some(lit_str(&ident, lit)?);
+        } else if ident == "content" {
+            ret.content = Some(lit_str(&ident, lit)?);
+        } else {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!("unknown es attribute: {}", ident),
+            ));
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut ret = FieldAttrs {
+        rename: None,
+        default: FieldDefault::None,
+        skip: false,
+    };
+    for (ident, lit) in parse_es_attrs(attrs)? {
+        if ident == "rename" {
+            ret.rename = Some(lit_str(&ident, lit)?);
+        } else if ident == "default" {
+            ret.default = match lit {
+                None => FieldDefault::DefaultTrait,
+                Some(lit) => FieldDefault::Path(syn::parse_str(&lit_str(&ident, Some(lit))?)?),
+            };
+        } else if ident == "skip" {
+            ret.skip = true;
+        } else {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!("unknown es attribute: {}", ident),
+            ));
+        }
+    }
+    Ok(ret)
+}
+
+/// variants only support rename, other attributes (e.g. skip or default) are an error instead of being ignored
+fn parse_variant_attrs(attrs: &[Attribute]) -> syn::Result<VariantAttrs> {
+    let mut ret = VariantAttrs { rename: None };
+    for (ident, lit) in parse_es_attrs(attrs)? {
+        if ident == "rename" {
+            ret.rename = Some(lit_str(&ident, lit)?);
+        } else {
+            return Err(syn::Error::new_spanned(
+                &ident,
+                format!("unsupported es attribute on an enum variant: {}", ident),
+            ));
+        }
+    }
+    Ok(ret)
+}
+
+/// split a snake_case or PascalCase name into lowercase words
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    for c in name.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(current);
+                current = String::new();
+            }
+        } else if c.is_uppercase() {
+            if !current.is_empty() {
+                words.push(current);
+            }
+            current = c.to_lowercase().collect();
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// rename a field or variant name according to a rename_all style
+fn rename(style: &str, name: &str) -> Option<String> {
+    let words = split_words(name);
+    match style {
+        "camelCase" => Some(
+            words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+                .collect(),
+        ),
+        "PascalCase" => Some(words.iter().map(|w| capitalize(w)).collect()),
+        "snake_case" => Some(words.join("_")),
+        "SCREAMING_SNAKE_CASE" => Some(words.join("_").to_uppercase()),
+        "lowercase" => Some(words.concat()),
+        "UPPERCASE" => Some(words.concat().to_uppercase()),
+        _ => None,
+    }
+}
+
+fn es_name(ident: &Ident, rename_attr: &Option<String>, container: &ContainerAttrs) -> String {
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    if let Some(renamed) = rename_attr {
+        renamed.clone()
+    } else if let Some(style) = container.rename_all.as_ref() {
+        rename(style, name).unwrap()
+    } else {
+        name.to_string()
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "Option";
+        }
+    }
+    false
+}
+
+fn add_trait_bounds(mut generics: Generics, bound: TokenStream2) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(type_param) = param {
+            type_param.bounds.push(syn::parse2(bound.clone()).unwrap());
+        }
+    }
+    generics
+}
+
+struct NamedField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    es_name: String,
+    attrs: FieldAttrs,
+}
+
+fn named_fields<'a>(
+    fields: &'a syn::FieldsNamed,
+    container: &ContainerAttrs,
+) -> syn::Result<Vec<NamedField<'a>>> {
+    let mut ret = vec![];
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let attrs = parse_field_attrs(&field.attrs)?;
+        ret.push(NamedField {
+            ident,
+            ty: &field.ty,
+            es_name: es_name(ident, &attrs.rename, container),
+            attrs,
+        });
+    }
+    Ok(ret)
+}
+
+/// statements which push the props for named fields to a Vec called props, the fields are expected
+/// to be bound to local variables with the same name
+fn named_fields_to_props(fields: &[NamedField]) -> TokenStream2 {
+    let stmts = fields.iter().filter(|f| !f.attrs.skip).map(|f| {
+        let ident = f.ident;
+        let es_name = &f.es_name;
+        if is_option(f.ty) {
+            quote! {
+                if let ::std::option::Option::Some(val) = #ident {
+                    props.push((
+                        #es_name.to_string(),
+                        ::es_runtime::esconversions::ToEsValue::to_es_value_facade(val),
+                    ));
+                }
+            }
+        } else {
+            quote! {
+                props.push((
+                    #es_name.to_string(),
+                    ::es_runtime::esconversions::ToEsValue::to_es_value_facade(#ident),
+                ));
+            }
+        }
+    });
+    quote! { #(#stmts)* }
+}
+
+/// field initializers for named fields, read from an IndexMap called props
+fn named_fields_from_props(fields: &[NamedField]) -> TokenStream2 {
+    let inits = fields.iter().map(|f| {
+        let ident = f.ident;
+        let ty = f.ty;
+        let es_name = &f.es_name;
+        if f.attrs.skip {
+            return quote! { #ident: ::std::default::Default::default() };
+        }
+        let missing = match &f.attrs.default {
+            FieldDefault::None => quote! {
+                <#ty as ::es_runtime::esconversions::FromEsValue>::from_es_value_facade(
+                    ::es_runtime::esvaluefacade::EsValueFacade::undefined(),
+                )
+                .map_err(|_e| format!("missing property {}", #es_name))?
+            },
+            FieldDefault::DefaultTrait => quote! { ::std::default::Default::default() },
+            FieldDefault::Path(path) => quote! { #path() },
+        };
+        quote! {
+            #ident: match props.shift_remove(#es_name) {
+                ::std::option::Option::Some(val) if !val.is_undefined() => {
+                    <#ty as ::es_runtime::esconversions::FromEsValue>::from_es_value_facade(val)
+                        .map_err(|e| format!("at property {}: {}", #es_name, e))?
+                }
+                _ => #missing,
+            }
+        }
+    });
+    quote! { #(#inits),* }
+}
+
+fn expand_to_es_value(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let container = parse_container_attrs(&input.attrs)?;
+    let generics = add_trait_bounds(
+        input.generics.clone(),
+        quote! { ::es_runtime::esconversions::ToEsValue },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let fields = named_fields(fields, &container)?;
+                let idents: Vec<&Ident> = fields.iter().map(|f| f.ident).collect();
+                let push_props = named_fields_to_props(&fields);
+                quote! {
+                    #[allow(unused_variables)]
+                    let #name { #(#idents),* } = self;
+                    let mut props = vec![];
+                    #push_props
+                    ::es_runtime::esvaluefacade::EsValueFacade::new_obj(props)
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                ::es_runtime::esconversions::ToEsValue::to_es_value_facade(self.0)
+            },
+            Fields::Unnamed(fields) => {
+                let vals = (0..fields.unnamed.len()).map(syn::Index::from);
+                quote! {
+                    ::es_runtime::esvaluefacade::EsValueFacade::new_array(vec![
+                        #(::es_runtime::esconversions::ToEsValue::to_es_value_facade(self.#vals)),*
+                    ])
+                }
+            }
+            Fields::Unit => quote! { ::es_runtime::esvaluefacade::EsValueFacade::undefined() },
+        },
+        Data::Enum(data) => {
+            let as_strings = container.tag.is_none()
+                && data
+                    .variants
+                    .iter()
+                    .all(|v| matches!(v.fields, Fields::Unit));
+            let tag = container.tag.clone().unwrap_or_else(|| "type".to_string());
+            let content = container
+                .content
+                .clone()
+                .unwrap_or_else(|| "value".to_string());
+
+            let mut arms = vec![];
+            for variant in &data.variants {
+                let v_ident = &variant.ident;
+                let v_attrs = parse_variant_attrs(&variant.attrs)?;
+                let v_name = es_name(v_ident, &v_attrs.rename, &container);
+                let tag_prop = quote! {
+                    (
+                        #tag.to_string(),
+                        ::es_runtime::esvaluefacade::EsValueFacade::new_str(#v_name.to_string()),
+                    )
+                };
+                arms.push(match &variant.fields {
+                    Fields::Unit if as_strings => quote! {
+                        #name::#v_ident => ::es_runtime::esvaluefacade::EsValueFacade::new_str(#v_name.to_string())
+                    },
+                    Fields::Unit => quote! {
+                        #name::#v_ident => ::es_runtime::esvaluefacade::EsValueFacade::new_obj(vec![#tag_prop])
+                    },
+                    Fields::Named(fields) => {
+                        let fields = named_fields(fields, &container)?;
+                        let idents: Vec<&Ident> = fields.iter().map(|f| f.ident).collect();
+                        let push_props = named_fields_to_props(&fields);
+                        quote! {
+                            #[allow(unused_variables)]
+                            #name::#v_ident { #(#idents),* } => {
+                                let mut props = vec![#tag_prop];
+                                #push_props
+                                ::es_runtime::esvaluefacade::EsValueFacade::new_obj(props)
+                            }
+                        }
+                    }
+                    Fields::Unnamed(fields) => {
+                        let vars: Vec<Ident> = (0..fields.unnamed.len())
+                            .map(|i| format_ident!("val_{}", i))
+                            .collect();
+                        let content_val = if vars.len() == 1 {
+                            let var = &vars[0];
+                            quote! { ::es_runtime::esconversions::ToEsValue::to_es_value_facade(#var) }
+                        } else {
+                            quote! {
+                                ::es_runtime::esvaluefacade::EsValueFacade::new_array(vec![
+                                    #(::es_runtime::esconversions::ToEsValue::to_es_value_facade(#vars)),*
+                                ])
+                            }
+                        };
+                        quote! {
+                            #name::#v_ident(#(#vars),*) => ::es_runtime::esvaluefacade::EsValueFacade::new_obj(vec![
+                                #tag_prop,
+                                (#content.to_string(), #content_val),
+                            ])
+                        }
+                    }
+                });
+            }
+            quote! {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "ToEsValue can not be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::es_runtime::esconversions::ToEsValue for #name #ty_generics #where_clause {
+            fn to_es_value_facade(self) -> ::es_runtime::esvaluefacade::EsValueFacade {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_from_es_value(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let container = parse_container_attrs(&input.attrs)?;
+    let generics = add_trait_bounds(
+        input.generics.clone(),
+        quote! { ::es_runtime::esconversions::FromEsValue },
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let expect_object = quote! {
+        if !esvf.is_object() {
+            return Err(format!(
+                "expected an object for {} but got {}",
+                #name_str,
+                ::es_runtime::esconversions::describe_type(&esvf)
+            ));
+        }
+        #[allow(unused_mut)]
+        let mut props = esvf.into_object();
+    };
+
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let fields = named_fields(fields, &container)?;
+                let inits = named_fields_from_props(&fields);
+                quote! {
+                    #expect_object
+                    Ok(#name { #inits })
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                quote! {
+                    Ok(#name(<#ty as ::es_runtime::esconversions::FromEsValue>::from_es_value_facade(esvf)?))
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let converted = from_array_items(&fields.unnamed, &name_str);
+                let vars: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| format_ident!("val_{}", i))
+                    .collect();
+                quote! {
+                    let (#(#vars),*) = #converted;
+                    Ok(#name(#(#vars),*))
+                }
+            }
+            Fields::Unit => quote! {
+                let _ = esvf;
+                Ok(#name)
+            },
+        },
+        Data::Enum(data) => {
+            let as_strings = container.tag.is_none()
+                && data
+                    .variants
+                    .iter()
+                    .all(|v| matches!(v.fields, Fields::Unit));
+            let tag = container.tag.clone().unwrap_or_else(|| "type".to_string());
+            let content = container
+                .content
+                .clone()
+                .unwrap_or_else(|| "value".to_string());
+
+            let mut arms = vec![];
+            for variant in &data.variants {
+                let v_ident = &variant.ident;
+                let v_attrs = parse_variant_attrs(&variant.attrs)?;
+                let v_name = es_name(v_ident, &v_attrs.rename, &container);
+                arms.push(match &variant.fields {
+                    Fields::Unit => quote! { #v_name => Ok(#name::#v_ident) },
+                    Fields::Named(fields) => {
+                        let fields = named_fields(fields, &container)?;
+                        let inits = named_fields_from_props(&fields);
+                        quote! { #v_name => Ok(#name::#v_ident { #inits }) }
+                    }
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        let ty = &fields.unnamed[0].ty;
+                        quote! {
+                            #v_name => {
+                                let esvf = props
+                                    .shift_remove(#content)
+                                    .unwrap_or_else(::es_runtime::esvaluefacade::EsValueFacade::undefined);
+                                Ok(#name::#v_ident(
+                                    <#ty as ::es_runtime::esconversions::FromEsValue>::from_es_value_facade(esvf)
+                                        .map_err(|e| format!("at property {}: {}", #content, e))?,
+                                ))
+                            }
+                        }
+                    }
+                    Fields::Unnamed(fields) => {
+                        let converted = from_array_items(&fields.unnamed, &v_name);
+                        let vars: Vec<Ident> = (0..fields.unnamed.len())
+                            .map(|i| format_ident!("val_{}", i))
+                            .collect();
+                        quote! {
+                            #v_name => {
+                                let esvf = props
+                                    .shift_remove(#content)
+                                    .unwrap_or_else(::es_runtime::esvaluefacade::EsValueFacade::undefined);
+                                let (#(#vars),*) = #converted;
+                                Ok(#name::#v_ident(#(#vars),*))
+                            }
+                        }
+                    }
+                });
+            }
+
+            let unknown = quote! {
+                other => Err(format!("unknown variant {} for {}", other, #name_str))
+            };
+
+            if as_strings {
+                quote! {
+                    if !esvf.is_string() {
+                        return Err(format!(
+                            "expected a string for {} but got {}",
+                            #name_str,
+                            ::es_runtime::esconversions::describe_type(&esvf)
+                        ));
+                    }
+                    match esvf.get_string().as_str() {
+                        #(#arms,)*
+                        #unknown,
+                    }
+                }
+            } else {
+                quote! {
+                    #expect_object
+                    let variant = match props.shift_remove(#tag) {
+                        ::std::option::Option::Some(tag_val) if tag_val.is_string() => {
+                            tag_val.get_string().clone()
+                        }
+                        _ => return Err(format!("missing string property {} for {}", #tag, #name_str)),
+                    };
+                    match variant.as_str() {
+                        #(#arms,)*
+                        #unknown,
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "FromEsValue can not be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::es_runtime::esconversions::FromEsValue for #name #ty_generics #where_clause {
+            fn from_es_value_facade(
+                esvf: ::es_runtime::esvaluefacade::EsValueFacade,
+            ) -> ::std::result::Result<Self, String> {
+                #body
+            }
+        }
+    })
+}
+
+/// convert an EsValueFacade called esvf which should be an array to the comma separated values of a tuple
+fn from_array_items(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    name: &str,
+) -> TokenStream2 {
+    let len = fields.len();
+    let vars: Vec<Ident> = (0..len).map(|i| format_ident!("item_{}", i)).collect();
+    let converts = fields.iter().zip(vars.iter()).map(|(field, var)| {
+        let ty = &field.ty;
+        quote! {
+            let #var = <#ty as ::es_runtime::esconversions::FromEsValue>::from_es_value_facade(
+                items.next().unwrap(),
+            )?;
+        }
+    });
+    quote! {
+        {
+            if !esvf.is_array() || esvf.get_array().len() != #len {
+                return Err(format!(
+                    "expected an array of {} items for {} but got {}",
+                    #len,
+                    #name,
+                    ::es_runtime::esconversions::describe_type(&esvf)
+                ));
+            }
+            let mut items = esvf.into_array().into_iter();
+            #(#converts)*
+            (#(#vars),*)
+        }
+    }
+}
@@ -6,6 +6,11 @@ pub(crate) fn init_es(rt: &EsRuntime) {
         "es_sys_scripts/es_01_core.es",
         include_str!("es_sys_scripts/es_01_core.es"),
     );
+    init_file(
+        rt,
+        "es_sys_scripts/es_02_error.es",
+        include_str!("es_sys_scripts/es_02_error.es"),
+    );
 }
 
 fn init_file(runtime: &EsRuntime, file_name: &str, es_code: &str) {
@@ -6,30 +6,10 @@ this.esses = new (class Esses {
 
         this._next_id = 0;
         this._cleanup_jobs = [];
-        this._registered_promises = new Map();
         this._runtime_id = Math.floor(Math.random() * 10000);
 
     }
 
-    /**
-    * generate a new id and resolve values with that id later
-    */
-    registerPromiseForResolutionInRust(prom) {
-        if (typeof prom === 'object' && prom instanceof Promise) {
-            let id = this.next_id();
-            // then and catch are registered async to prevent direct resolution without id being registered in rust
-            // we also store the promise in a Map so it is not garbage collected
-            this._registered_promises.set(id, prom);
-
-            setImmediate(function() {
-                esses.register_waitfor_promise(prom, id);
-            });
-            return id;
-        } else {
-            throw Error("value pass to registerPromiseForResolutionInRust was not a Promise [" + typeof prom + "]");
-        }
-    }
-
     next_id() {
         return this._next_id++;
     }
@@ -77,34 +57,6 @@ this.esses = new (class Esses {
 
     }
 
-    register_waitfor_promise(val, man_obj_id) {
-
-        console.log("register_waitfor_promise: val = %s" + typeof val);
-
-        if (val instanceof Promise) {
-            val.then((result) => {
-                console.trace('resolving esvf from es to {}', result);
-                esses.invoke_rust_op_sync('resolve_waiting_esvf_future', man_obj_id, result);
-            });
-            val.catch((ex) => {
-                console.trace('rejecting esvf from es to {}', ex);
-                esses.invoke_rust_op_sync('reject_waiting_esvf_future', man_obj_id, ex);
-            });
-            val.finally(() => {
-                console.trace('finalize promise (remove from map) id: %s', man_obj_id);
-                esses._registered_promises.remove(man_obj_id);
-            });
-        } else {
-            let t = "" + val;
-            if (val && val.constructor) {
-                t = val.constructor.name;
-            } else if (val){
-                t = JSON.stringify(val);
-            }
-            throw Error("_register_waitfor_promise_ managed obj was not a promise: " + t);
-        }
-    }
-
     /**
     * add a job todo when cleanup is called from rust
     */
@@ -125,6 +77,15 @@ this.esses = new (class Esses {
         }
     }
 
+    /**
+    * describe the methods, properties and events of a proxy class
+    * @param target the canonical name of the class (e.g. "my.biz.MyClass"), its constructor, a singleton or an instance
+    * @returns {Object} the description or null if the target is not a proxy class
+    */
+    describe(target) {
+        return __describe_proxy(target);
+    }
+
 
 })();
 
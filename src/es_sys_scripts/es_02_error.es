@@ -0,0 +1,76 @@
+
+/**
+* uncaught errors from async contexts (e.g. setImmediate callbacks or event listeners) are dispatched here from rust
+* like in a browser you can handle them by setting onerror or by adding a listener for the 'error' event
+*/
+
+this.onerror = null;
+
+((global) => {
+
+    let error_listeners = [];
+
+    /**
+    * only the error event is dispatched on the global scope, listeners for other types are ignored
+    */
+    global.addEventListener = function(type, listener) {
+        if (type !== 'error') {
+            return;
+        }
+        if (!(listener instanceof Function)) {
+            throw Error("listener was not a function");
+        }
+        if (!error_listeners.includes(listener)) {
+            error_listeners.push(listener);
+        }
+    };
+
+    global.removeEventListener = function(type, listener) {
+        if (type !== 'error') {
+            return;
+        }
+        let idx = error_listeners.indexOf(listener);
+        if (idx >= 0) {
+            error_listeners.splice(idx, 1);
+        }
+    };
+
+    /**
+    * called from rust when an uncaught error occurred
+    * this is a non writable internal binding so scripts can not replace it
+    */
+    let dispatch_error_event = function(error_info) {
+        let evt = {
+            type: 'error',
+            message: error_info.message,
+            filename: error_info.filename,
+            lineno: error_info.lineno,
+            colno: error_info.colno
+        };
+
+        if (global.onerror instanceof Function) {
+            try {
+                global.onerror(evt.message, evt.filename, evt.lineno, evt.colno);
+            } catch(ex) {
+                console.error("onerror failed with %s", "" + ex);
+            }
+        }
+
+        for (let listener of error_listeners.slice()) {
+            try {
+                listener(evt);
+            } catch(ex) {
+                console.error("error listener failed with %s", "" + ex);
+            }
+        }
+    };
+
+    Object.defineProperty(global, '__dispatch_error_event', {
+        value: dispatch_error_event,
+        writable: false,
+        configurable: false,
+        enumerable: false
+    });
+
+})(this);
+
@@ -0,0 +1,194 @@
+//! # EsClass
+//!
+//! the #[es_class] attribute macro creates an EsProxy class from a rust struct and its impl block
+//!
+//! on the struct you can set the namespace and the name of the class, on the impl block you can mark
+//! methods with #[es(...)] attributes
+//!
+//! * `#[es(constructor)]` marks the fn which creates a new instance, it should return Self or Result<Self, String>
+//! * `#[es(getter)]` or `#[es(getter = "name")]` marks a getter, the default name is the fn name without the get_ prefix
+//! * `#[es(setter)]` or `#[es(setter = "name")]` marks a setter, the default name is the fn name without the set_ prefix
+//!   a getter without a setter makes a read only property, a setter without a getter a write only property
+//! * `#[es(rename = "name")]` sets the name of a method in script
+//! * `#[es(skip)]` hides a fn from script
+//!
+//! the /// doc comments of the struct and of the fns are added to the class as its documentation, see
+//! esreflection::EsProxy::describe and EsProxy::generate_typescript_declarations
+//!
+//! all other fns with a self receiver become methods and all other fns without one become static methods,
+//! arguments and return values are converted with the esconversions::FromEsValue and ToEsValue traits
+//! the class is built with an esreflection::EsTypedProxyBuilder so the instances are owned by the
+//! proxy and dropped when they are garbage collected in script
+//!
+//! # Example
+//!
+//! ```no_run
+//! use es_runtime::esclass::{es_class, EsClass};
+//! use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+//!
+//! #[es_class(namespace = "my.biz", name = "Counter")]
+//! struct Counter {
+//!     count: i32,
+//! }
+//!
+//! #[es_class]
+//! impl Counter {
+//!     #[es(constructor)]
+//!     fn new(start: Option<i32>) -> Self {
+//!         Counter { count: start.unwrap_or(0) }
+//!     }
+//!
+//!     fn increment(&mut self, by: i32) -> i32 {
+//!         self.count += by;
+//!         self.count
+//!     }
+//!
+//!     #[es(getter)]
+//!     fn count(&self) -> i32 {
+//!         self.count
+//!     }
+//!
+//!     fn describe() -> String {
+//!         "a counter".to_string()
+//!     }
+//! }
+//!
+//! let rt = EsRuntimeBuilder::new().build();
+//! Counter::register(&rt).ok().expect("could not register Counter");
+//! let esvf = rt.eval_sync("let c = new my.biz.Counter(1); c.increment(2); c.count;", "test_es_class.es")
+//!     .ok().expect("script failed");
+//! assert_eq!(esvf.get_i32(), &3);
+//! ```
+
+use crate::esreflection::EsProxy;
+use crate::esruntime::EsRuntime;
+pub use es_runtime_derive::es_class;
+
+/// the namespace and name of a class, this is implemented by #[es_class] on a struct
+pub trait EsClassMeta {
+    const NAMESPACE: &'static [&'static str];
+    const CLASS_NAME: &'static str;
+    /// the doc comment of the struct, empty if it has none
+    const DOC: &'static str = "";
+}
+
+/// a class which can be registered in a runtime, this is implemented by #[es_class] on an impl block
+pub trait EsClass: EsClassMeta + Sized + 'static {
+    /// create the proxy class for this type in a runtime
+    fn register(rt: &EsRuntime) -> Result<EsProxy, String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esclass::{es_class, EsClass};
+    use crate::esruntime::tests::TEST_RT;
+
+    /// a bank account
+    #[es_class(namespace = "test.esclass", name = "Account")]
+    struct TestAccount {
+        owner: String,
+        balance: f64,
+    }
+
+    #[es_class]
+    impl TestAccount {
+        #[es(constructor)]
+        fn new(owner: String, balance: Option<f64>) -> Result<Self, String> {
+            if owner.is_empty() {
+                return Err("owner may not be empty".to_string());
+            }
+            Ok(TestAccount {
+                owner,
+                balance: balance.unwrap_or(0.0),
+            })
+        }
+
+        /// deposit an amount, returns the new balance
+        #[es(rename = "depositAmount")]
+        fn deposit(&mut self, amount: f64) -> Result<f64, String> {
+            if amount <= 0.0 {
+                return Err("amount should be positive".to_string());
+            }
+            self.balance += amount;
+            Ok(self.balance)
+        }
+
+        #[es(getter)]
+        fn get_owner(&self) -> String {
+            self.owner.clone()
+        }
+
+        #[es(setter)]
+        fn set_owner(&mut self, owner: String) {
+            self.owner = owner;
+        }
+
+        #[es(getter)]
+        fn balance(&self) -> f64 {
+            self.balance
+        }
+
+        fn currency() -> &'static str {
+            "EUR"
+        }
+
+        #[es(skip)]
+        #[allow(dead_code)]
+        fn internal(&self) -> i32 {
+            1
+        }
+    }
+
+    #[test]
+    fn test_es_class() {
+        log::info!("test: test_es_class");
+        let rt = TEST_RT.clone();
+        let proxy = TestAccount::register(&rt)
+            .ok()
+            .expect("could not register TestAccount");
+        let esvf = rt
+            .eval_sync(
+                "{let acc = new test.esclass.Account('Helena', 10); \
+                 acc.depositAmount(5); \
+                 acc.owner = 'Mike'; \
+                 ({owner: acc.owner, balance: String(acc.balance), currency: test.esclass.Account.currency(), \
+                 internal: typeof acc.internal});}",
+                "test_es_class.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(res["owner"].get_string(), "Mike");
+        assert_eq!(res["balance"].get_string(), "15");
+        assert_eq!(res["currency"].get_string(), "EUR");
+        // methods marked with #[es(skip)] are not exposed
+        assert_eq!(res["internal"].get_string(), "undefined");
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = {}; \
+                 try {new test.esclass.Account(''); res.ctor = 'constructed';} catch(ex) {res.ctor = 'failed';} \
+                 try {new test.esclass.Account('a').depositAmount('1'); res.type = 'deposited';} catch(ex) {res.type = 'failed';} \
+                 try {new test.esclass.Account('a').depositAmount(-1); res.deposit = 'deposited';} catch(ex) {res.deposit = 'failed';} \
+                 res;}",
+                "test_es_class2.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        // the constructor returned an Err
+        assert_eq!(res["ctor"].get_string(), "failed");
+        // the argument could not be converted to the type of the rust method
+        assert_eq!(res["type"].get_string(), "failed");
+        // the method returned an Err
+        assert_eq!(res["deposit"].get_string(), "failed");
+
+        let desc = proxy.describe(&rt).expect("class was not registered");
+        assert_eq!(desc.doc.as_deref(), Some("a bank account"));
+        assert_eq!(desc.methods[0].name, "depositAmount");
+        assert_eq!(
+            desc.methods[0].doc.as_deref(),
+            Some("deposit an amount, returns the new balance")
+        );
+    }
+}
@@ -0,0 +1,596 @@
+//! conversion traits between rust types and EsValueFacade
+//!
+//! these are used by EsRuntime::add_global_typed_function to convert the arguments of a function call
+//! and its return value so you don't have to unpack a Vec<EsValueFacade> by hand
+//!
+//! for your own structs and enums you can derive ToEsValue and FromEsValue, see the es_runtime_derive
+//! crate for the supported attributes
+//!
+//! # Example
+//!
+//! ```no_run
+//! use es_runtime::esconversions::{FromEsValue, ToEsValue};
+//! use es_runtime::esvaluefacade::EsValueFacade;
+//!
+//! let esvf = vec![1, 2, 3].to_es_value_facade();
+//! assert!(esvf.is_array());
+//! let nums: Vec<i32> = Vec::from_es_value_facade(esvf).ok().expect("not a Vec<i32>");
+//! assert_eq!(nums, vec![1, 2, 3]);
+//! let res: Result<String, String> = String::from_es_value_facade(EsValueFacade::new_i32(1));
+//! assert!(res.is_err());
+//! ```
+
+use crate::esvaluefacade::EsValueFacade;
+pub use es_runtime_derive::{FromEsValue, ToEsValue};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+
+/// convert a rust value to an EsValueFacade
+pub trait ToEsValue {
+    fn to_es_value_facade(self) -> EsValueFacade;
+}
+
+/// convert an EsValueFacade to a rust value, this fails with a description of the mismatch if
+/// the EsValueFacade does not represent the expected type
+pub trait FromEsValue: Sized {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String>;
+}
+
+/// get a description of the type an EsValueFacade represents, used in error messages
+pub fn describe_type(esvf: &EsValueFacade) -> &'static str {
+    if esvf.is_undefined() {
+        "undefined"
+    } else if esvf.is_string() {
+        "a string"
+    } else if esvf.is_i32() || esvf.is_f64() {
+        "a number"
+    } else if esvf.is_boolean() {
+        "a boolean"
+    } else if esvf.is_array() {
+        "an array"
+    } else if esvf.is_error() {
+        "an Error"
+    } else if esvf.is_promise() || esvf.is_prepped_promise() {
+        "a Promise"
+    } else if esvf.is_function() || esvf.is_rust_function() {
+        "a function"
+    } else if esvf.is_proxy_instance() {
+        "a proxy instance"
+    } else {
+        "an object"
+    }
+}
+
+fn type_mismatch(expected: &str, esvf: &EsValueFacade) -> String {
+    format!("expected {} but got {}", expected, describe_type(esvf))
+}
+
+fn get_integral_f64(esvf: &EsValueFacade) -> Option<f64> {
+    if esvf.is_i32() {
+        Some(*esvf.get_i32() as f64)
+    } else if esvf.is_f64() && esvf.get_f64().fract() == 0.0 {
+        Some(*esvf.get_f64())
+    } else {
+        None
+    }
+}
+
+impl ToEsValue for EsValueFacade {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        self
+    }
+}
+
+impl FromEsValue for EsValueFacade {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        Ok(esvf)
+    }
+}
+
+impl ToEsValue for () {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::undefined()
+    }
+}
+
+impl ToEsValue for bool {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_bool(self)
+    }
+}
+
+impl FromEsValue for bool {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        if esvf.is_boolean() {
+            Ok(esvf.get_boolean())
+        } else {
+            Err(type_mismatch("a boolean", &esvf))
+        }
+    }
+}
+
+impl ToEsValue for String {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_str(self)
+    }
+}
+
+impl ToEsValue for &str {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_str(self.to_string())
+    }
+}
+
+impl FromEsValue for String {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        if esvf.is_string() {
+            Ok(esvf.get_string().clone())
+        } else {
+            Err(type_mismatch("a string", &esvf))
+        }
+    }
+}
+
+impl ToEsValue for f64 {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_f64(self)
+    }
+}
+
+impl FromEsValue for f64 {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        if esvf.is_f64() {
+            Ok(*esvf.get_f64())
+        } else if esvf.is_i32() {
+            Ok(*esvf.get_i32() as f64)
+        } else {
+            Err(type_mismatch("a number", &esvf))
+        }
+    }
+}
+
+impl ToEsValue for f32 {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_f64(self as f64)
+    }
+}
+
+impl FromEsValue for f32 {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        f64::from_es_value_facade(esvf).map(|f| f as f32)
+    }
+}
+
+// integers which always fit in an i32
+macro_rules! impl_small_int {
+    ($($t:ty),*) => {
+        $(
+            impl ToEsValue for $t {
+                fn to_es_value_facade(self) -> EsValueFacade {
+                    EsValueFacade::new_i32(self as i32)
+                }
+            }
+
+            impl FromEsValue for $t {
+                fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+                    let expected = concat!("an integer (", stringify!($t), ")");
+                    match get_integral_f64(&esvf) {
+                        Some(f) if f >= <$t>::MIN as f64 && f <= <$t>::MAX as f64 => Ok(f as $t),
+                        _ => Err(type_mismatch(expected, &esvf)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_small_int!(i8, i16, i32, u8, u16);
+
+// integers which may not fit in an i32, these are passed as a float when they don't
+macro_rules! impl_large_int {
+    ($($t:ty),*) => {
+        $(
+            impl ToEsValue for $t {
+                fn to_es_value_facade(self) -> EsValueFacade {
+                    match i32::try_from(self) {
+                        Ok(i) => EsValueFacade::new_i32(i),
+                        Err(_) => EsValueFacade::new_f64(self as f64),
+                    }
+                }
+            }
+
+            impl FromEsValue for $t {
+                fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+                    let expected = concat!("an integer (", stringify!($t), ")");
+                    // MAX + 1 is a power of two so it is exact as f64, MAX itself is rounded up to
+                    // MAX + 1 for the 64 bit types so it can not be used as an inclusive bound
+                    let upper_bound = <$t>::MAX as f64 + 1.0;
+                    match get_integral_f64(&esvf) {
+                        Some(f) if f >= <$t>::MIN as f64 && f < upper_bound => Ok(f as $t),
+                        _ => Err(type_mismatch(expected, &esvf)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_large_int!(u32, i64, u64, usize);
+
+impl<T: ToEsValue> ToEsValue for Option<T> {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        match self {
+            Some(val) => val.to_es_value_facade(),
+            None => EsValueFacade::undefined(),
+        }
+    }
+}
+
+impl<T: FromEsValue> FromEsValue for Option<T> {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        if esvf.is_undefined() {
+            Ok(None)
+        } else {
+            T::from_es_value_facade(esvf).map(Some)
+        }
+    }
+}
+
+impl<T: ToEsValue> ToEsValue for Vec<T> {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_array(self.into_iter().map(T::to_es_value_facade).collect())
+    }
+}
+
+impl<T: FromEsValue> FromEsValue for Vec<T> {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        if !esvf.is_array() {
+            return Err(type_mismatch("an array", &esvf));
+        }
+        let mut ret = vec![];
+        for (index, item) in esvf.into_array().into_iter().enumerate() {
+            ret.push(
+                T::from_es_value_facade(item).map_err(|e| format!("at index {}: {}", index, e))?,
+            );
+        }
+        Ok(ret)
+    }
+}
+
+impl<T: ToEsValue, S: BuildHasher> ToEsValue for HashMap<String, T, S> {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_obj(self.into_iter().map(|(k, v)| (k, v.to_es_value_facade())))
+    }
+}
+
+impl<T: FromEsValue, S: BuildHasher + Default> FromEsValue for HashMap<String, T, S> {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        let mut ret = HashMap::default();
+        for (key, val) in into_object_props(esvf)? {
+            let converted =
+                T::from_es_value_facade(val).map_err(|e| format!("at property {}: {}", key, e))?;
+            ret.insert(key, converted);
+        }
+        Ok(ret)
+    }
+}
+
+impl<T: ToEsValue> ToEsValue for IndexMap<String, T> {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_obj(self.into_iter().map(|(k, v)| (k, v.to_es_value_facade())))
+    }
+}
+
+impl<T: FromEsValue> FromEsValue for IndexMap<String, T> {
+    fn from_es_value_facade(esvf: EsValueFacade) -> Result<Self, String> {
+        let mut ret = IndexMap::new();
+        for (key, val) in into_object_props(esvf)? {
+            let converted =
+                T::from_es_value_facade(val).map_err(|e| format!("at property {}: {}", key, e))?;
+            ret.insert(key, converted);
+        }
+        Ok(ret)
+    }
+}
+
+fn into_object_props(esvf: EsValueFacade) -> Result<IndexMap<String, EsValueFacade>, String> {
+    if esvf.is_object() {
+        Ok(esvf.into_object())
+    } else {
+        Err(type_mismatch("an object", &esvf))
+    }
+}
+
+/// the error of a typed function invocation
+pub enum TypedFunctionError {
+    /// the arguments did not match the signature of the function, this is thrown as a TypeError in script
+    TypeError(String),
+    /// the function itself returned an Err
+    Failed(String),
+}
+
+/// a rust function with typed arguments and a typed result, this is implemented for all
+/// `Fn(A1, A2, ..) -> Result<R, String>` (up to 8 arguments) where the arguments implement FromEsValue
+/// and R implements ToEsValue
+pub trait TypedFunction<Args>: Send + 'static {
+    /// the number of arguments the function expects
+    fn arity(&self) -> usize;
+    /// convert the arguments, invoke the function and convert its result
+    fn invoke(&self, args: Vec<EsValueFacade>) -> Result<EsValueFacade, TypedFunctionError>;
+}
+
+/// convert an argument (or undefined if it's missing) to the type a function expects
+/// this is used by the code generated by #[es_class]
+pub fn from_es_arg<T: FromEsValue>(
+    arg: Option<EsValueFacade>,
+    index: usize,
+    arity: usize,
+    argc: usize,
+) -> Result<T, String> {
+    match arg {
+        Some(esvf) => {
+            T::from_es_value_facade(esvf).map_err(|e| format!("argument {}: {}", index, e))
+        }
+        None => T::from_es_value_facade(EsValueFacade::undefined())
+            .map_err(|_e| format!("expected {} arguments but got {}", arity, argc)),
+    }
+}
+
+fn convert_arg<T: FromEsValue>(
+    arg: Option<EsValueFacade>,
+    index: usize,
+    arity: usize,
+    argc: usize,
+) -> Result<T, TypedFunctionError> {
+    from_es_arg(arg, index, arity, argc).map_err(TypedFunctionError::TypeError)
+}
+
+macro_rules! impl_typed_function {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg,)*> TypedFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> Result<R, String> + Send + 'static,
+            R: ToEsValue,
+            $($arg: FromEsValue,)*
+        {
+            fn arity(&self) -> usize {
+                let arg_names: &[&str] = &[$(stringify!($arg)),*];
+                arg_names.len()
+            }
+
+            #[allow(non_snake_case, unused_mut, unused_variables, unused_assignments)]
+            fn invoke(&self, args: Vec<EsValueFacade>) -> Result<EsValueFacade, TypedFunctionError> {
+                let arity = self.arity();
+                let argc = args.len();
+                if argc > arity {
+                    return Err(TypedFunctionError::TypeError(format!(
+                        "expected {} arguments but got {}",
+                        arity, argc
+                    )));
+                }
+                let mut args_iter = args.into_iter();
+                let mut index = 0;
+                $(
+                    let $arg: $arg = convert_arg(args_iter.next(), index, arity, argc)?;
+                    index += 1;
+                )*
+                self($($arg),*)
+                    .map(ToEsValue::to_es_value_facade)
+                    .map_err(TypedFunctionError::Failed)
+            }
+        }
+    };
+}
+
+impl_typed_function!();
+impl_typed_function!(A1);
+impl_typed_function!(A1, A2);
+impl_typed_function!(A1, A2, A3);
+impl_typed_function!(A1, A2, A3, A4);
+impl_typed_function!(A1, A2, A3, A4, A5);
+impl_typed_function!(A1, A2, A3, A4, A5, A6);
+impl_typed_function!(A1, A2, A3, A4, A5, A6, A7);
+impl_typed_function!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+#[cfg(test)]
+mod tests {
+    use crate::esconversions::{FromEsValue, ToEsValue, TypedFunction, TypedFunctionError};
+    use crate::esvaluefacade::EsValueFacade;
+    use std::collections::HashMap;
+
+    #[derive(ToEsValue, FromEsValue, Debug, PartialEq)]
+    #[es(rename_all = "camelCase")]
+    struct TestUser {
+        user_name: String,
+        #[es(default)]
+        login_count: i32,
+        email: Option<String>,
+        #[es(rename = "roles")]
+        user_roles: Vec<TestRole>,
+        #[es(skip)]
+        cached: bool,
+    }
+
+    #[derive(ToEsValue, FromEsValue, Debug, PartialEq)]
+    enum TestRole {
+        Admin,
+        Guest,
+    }
+
+    #[derive(ToEsValue, FromEsValue, Debug, PartialEq)]
+    #[es(tag = "kind")]
+    enum TestShape {
+        Circle { radius: f64 },
+        Square(f64),
+        Rect(f64, f64),
+        Empty,
+    }
+
+    #[test]
+    fn test_conversions() {
+        log::info!("test: test_conversions");
+        assert_eq!(
+            i32::from_es_value_facade(EsValueFacade::new_f64(3.0)).ok(),
+            Some(3)
+        );
+        assert!(i32::from_es_value_facade(EsValueFacade::new_f64(3.5)).is_err());
+        assert!(u8::from_es_value_facade(EsValueFacade::new_i32(256)).is_err());
+        assert!(u64::from_es_value_facade(EsValueFacade::new_f64(18446744073709551616.0)).is_err());
+        assert!(i64::from_es_value_facade(EsValueFacade::new_f64(9223372036854775808.0)).is_err());
+        assert_eq!(
+            i64::from_es_value_facade(EsValueFacade::new_f64(-9223372036854775808.0)).ok(),
+            Some(i64::MIN)
+        );
+        assert_eq!(
+            u32::from_es_value_facade(EsValueFacade::new_f64(4294967295.0)).ok(),
+            Some(u32::MAX)
+        );
+        assert_eq!(
+            f64::from_es_value_facade(EsValueFacade::new_i32(2)).ok(),
+            Some(2.0)
+        );
+        assert_eq!(
+            Option::<String>::from_es_value_facade(EsValueFacade::undefined()).ok(),
+            Some(None)
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), vec![Some(1), None]);
+        let esvf = map.to_es_value_facade();
+        assert!(esvf.is_object());
+        let map2: HashMap<String, Vec<Option<i32>>> =
+            HashMap::from_es_value_facade(esvf).ok().unwrap();
+        assert_eq!(map2.get("a").unwrap(), &vec![Some(1), None]);
+
+        let err = Vec::<String>::from_es_value_facade(EsValueFacade::new_array(vec![
+            EsValueFacade::new_str("a".to_string()),
+            EsValueFacade::new_bool(true),
+        ]))
+        .err()
+        .unwrap();
+        assert_eq!(err, "at index 1: expected a string but got a boolean");
+    }
+
+    #[test]
+    fn test_typed_function() {
+        log::info!("test: test_typed_function");
+        let func =
+            |a: i32, b: Option<f64>| -> Result<f64, String> { Ok(a as f64 + b.unwrap_or(0.5)) };
+        assert_eq!(TypedFunction::<(i32, Option<f64>)>::arity(&func), 2);
+
+        let res = func.invoke(vec![EsValueFacade::new_i32(1)]).ok().unwrap();
+        assert_eq!(res.get_f64(), &1.5);
+
+        match func.invoke(vec![EsValueFacade::new_str("1".to_string())]) {
+            Err(TypedFunctionError::TypeError(msg)) => {
+                assert_eq!(
+                    msg,
+                    "argument 0: expected an integer (i32) but got a string"
+                )
+            }
+            _ => panic!("expected a TypeError"),
+        }
+
+        match func.invoke(vec![
+            EsValueFacade::new_i32(1),
+            EsValueFacade::new_i32(2),
+            EsValueFacade::new_i32(3),
+        ]) {
+            Err(TypedFunctionError::TypeError(msg)) => {
+                assert_eq!(msg, "expected 2 arguments but got 3")
+            }
+            _ => panic!("expected a TypeError"),
+        }
+    }
+
+    #[test]
+    fn test_derive_struct() {
+        log::info!("test: test_derive_struct");
+        let user = TestUser {
+            user_name: "Helena".to_string(),
+            login_count: 3,
+            email: None,
+            user_roles: vec![TestRole::Admin, TestRole::Guest],
+            cached: true,
+        };
+        let esvf = user.to_es_value_facade();
+        {
+            let props = esvf.get_object();
+            let keys: Vec<&String> = props.keys().collect();
+            assert_eq!(keys, vec!["userName", "loginCount", "roles"]);
+            assert_eq!(
+                props.get("roles").unwrap().get_array()[0].get_string(),
+                "Admin"
+            );
+        }
+
+        let user2 = TestUser::from_es_value_facade(esvf).ok().unwrap();
+        assert_eq!(
+            user2,
+            TestUser {
+                user_name: "Helena".to_string(),
+                login_count: 3,
+                email: None,
+                user_roles: vec![TestRole::Admin, TestRole::Guest],
+                cached: false,
+            }
+        );
+
+        // loginCount has a default, userName is required
+        let esvf = EsValueFacade::new_obj(vec![(
+            "roles".to_string(),
+            EsValueFacade::new_array(vec![]),
+        )]);
+        let err = TestUser::from_es_value_facade(esvf).err().unwrap();
+        assert_eq!(err, "missing property userName");
+
+        let esvf = EsValueFacade::new_obj(vec![
+            (
+                "userName".to_string(),
+                EsValueFacade::new_str("a".to_string()),
+            ),
+            (
+                "roles".to_string(),
+                EsValueFacade::new_array(vec![EsValueFacade::new_str("Root".to_string())]),
+            ),
+        ]);
+        let err = TestUser::from_es_value_facade(esvf).err().unwrap();
+        assert_eq!(
+            err,
+            "at property roles: at index 0: unknown variant Root for TestRole"
+        );
+    }
+
+    #[test]
+    fn test_derive_tagged_enum() {
+        log::info!("test: test_derive_tagged_enum");
+        let shapes = vec![
+            TestShape::Circle { radius: 1.5 },
+            TestShape::Square(2.0),
+            TestShape::Rect(1.0, 2.5),
+            TestShape::Empty,
+        ];
+        let esvf = shapes.to_es_value_facade();
+        {
+            let arr = esvf.get_array();
+            assert_eq!(
+                arr[0].get_object().get("kind").unwrap().get_string(),
+                "Circle"
+            );
+            assert_eq!(arr[1].get_object().get("value").unwrap().get_f64(), &2.0);
+            assert!(arr[2].get_object().get("value").unwrap().is_array());
+        }
+        let shapes2: Vec<TestShape> = Vec::from_es_value_facade(esvf).ok().unwrap();
+        assert_eq!(
+            shapes2,
+            vec![
+                TestShape::Circle { radius: 1.5 },
+                TestShape::Square(2.0),
+                TestShape::Rect(1.0, 2.5),
+                TestShape::Empty,
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! the EsFuture is a std::future::Future which is completed from the worker thread of an EsRuntime
+//!
+//! it does not depend on any executor, the waker of the last poll is woken when the result is set
+//! so it can be awaited from any async runtime (or waited for blocking with get_result_blocking)
+//!
+//! an EsFuture has a single consumer, it can not be cloned and awaiting it or waiting for it
+//! consumes it, if the runtime drops the job which would have completed the future (e.g. because the
+//! runtime was dropped) the future is completed with an error instead of staying pending forever
+//!
+//! # Example
+//!
+//! ```no_run
+//! use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+//! use futures::executor::block_on;
+//!
+//! let rt = EsRuntimeBuilder::new().build();
+//! let esvf = block_on(rt.eval_async("(1 + 2);", "test_esfuture.es")).ok().expect("script failed");
+//! assert_eq!(esvf.get_i32(), &3);
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+type EsFutureCallback<R> = Box<dyn FnOnce(R) + Send + 'static>;
+
+struct EsFutureState<R> {
+    result: Option<R>,
+    waker: Option<Waker>,
+    callback: Option<EsFutureCallback<R>>,
+}
+
+type EsFutureStateHolder<R> = Arc<(Mutex<EsFutureState<R>>, Condvar)>;
+
+/// a Future which is completed by an EsFutureResolver
+pub struct EsFuture<R> {
+    state: EsFutureStateHolder<R>,
+}
+
+/// the resolver for an EsFuture, this is used to set the result from the worker thread of the EsRuntime
+/// if the resolver is dropped without being used the EsFuture is completed with the dropped_result
+pub(crate) struct EsFutureResolver<R> {
+    state: Option<EsFutureStateHolder<R>>,
+    dropped_result: fn() -> R,
+}
+
+fn new_state_holder<R>(result: Option<R>) -> EsFutureStateHolder<R> {
+    Arc::new((
+        Mutex::new(EsFutureState {
+            result,
+            waker: None,
+            callback: None,
+        }),
+        Condvar::new(),
+    ))
+}
+
+/// create a new EsFuture and the EsFutureResolver which is used to complete it
+/// dropped_result creates the result of the EsFuture for when the resolver is dropped without being used
+pub(crate) fn new_es_future<R>(dropped_result: fn() -> R) -> (EsFuture<R>, EsFutureResolver<R>) {
+    let state = new_state_holder(None);
+    (
+        EsFuture {
+            state: state.clone(),
+        },
+        EsFutureResolver {
+            state: Some(state),
+            dropped_result,
+        },
+    )
+}
+
+impl<R> EsFuture<R> {
+    /// create a new EsFuture which is already completed
+    pub(crate) fn new_resolved(result: R) -> Self {
+        EsFuture {
+            state: new_state_holder(Some(result)),
+        }
+    }
+
+    /// wait for the result in the current thread
+    /// on timeout the EsFuture is returned as the Err so it can be waited for (or awaited) again
+    pub fn get_result_blocking(self, timeout: Duration) -> Result<R, Self> {
+        let deadline = Instant::now() + timeout;
+        {
+            let (mtx, cvar) = &*self.state;
+            let mut state = mtx.lock().unwrap();
+            loop {
+                if let Some(result) = state.result.take() {
+                    return Ok(result);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                state = cvar.wait_timeout(state, deadline - now).unwrap().0;
+            }
+        }
+        Err(self)
+    }
+}
+
+impl<R: 'static> EsFuture<R> {
+    /// consume the result with a callback instead of awaiting it
+    /// if the result is already there the callback is invoked immediately, else it is invoked by
+    /// the thread which sets the result
+    /// this consumes the EsFuture so the callback and awaiting the EsFuture are mutually exclusive
+    pub fn on_resolved<C>(self, callback: C)
+    where
+        C: FnOnce(R) + Send + 'static,
+    {
+        let (mtx, _cvar) = &*self.state;
+        let result_opt = {
+            let state = &mut *mtx.lock().unwrap();
+            let result_opt = state.result.take();
+            if result_opt.is_none() {
+                state.callback = Some(Box::new(callback));
+                return;
+            }
+            result_opt
+        };
+        callback(result_opt.unwrap());
+    }
+}
+
+impl<R> Future for EsFuture<R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (mtx, _cvar) = &*self.state;
+        let state = &mut *mtx.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            Poll::Ready(result)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<R> EsFutureResolver<R> {
+    /// complete the EsFuture and wake whoever is waiting for it
+    pub(crate) fn resolve(mut self, result: R) {
+        if let Some(state) = self.state.take() {
+            complete(&state, result);
+        }
+    }
+}
+
+impl<R> Drop for EsFutureResolver<R> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            complete(&state, (self.dropped_result)());
+        }
+    }
+}
+
+fn complete<R>(state_holder: &EsFutureStateHolder<R>, result: R) {
+    let (mtx, cvar) = &**state_holder;
+    let (waker_opt, callback_and_result) = {
+        let state = &mut *mtx.lock().unwrap();
+        if let Some(callback) = state.callback.take() {
+            (None, Some((callback, result)))
+        } else {
+            state.result = Some(result);
+            (state.waker.take(), None)
+        }
+    };
+    if let Some((callback, result)) = callback_and_result {
+        // run the callback outside of the lock
+        callback(result);
+        return;
+    }
+    cvar.notify_all();
+    if let Some(waker) = waker_opt {
+        waker.wake();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esfuture::new_es_future;
+    use std::time::Duration;
+
+    #[test]
+    fn test_es_future() {
+        log::info!("test: test_es_future");
+        let (fut, resolver) = new_es_future(|| -1);
+        let fut = match fut.get_result_blocking(Duration::from_millis(10)) {
+            Ok(_) => panic!("future should not be completed yet"),
+            Err(fut) => fut,
+        };
+        std::thread::spawn(move || {
+            resolver.resolve(123);
+        });
+        assert_eq!(futures::executor::block_on(fut), 123);
+    }
+
+    #[test]
+    fn test_es_future_dropped_resolver() {
+        log::info!("test: test_es_future_dropped_resolver");
+        let (fut, resolver) = new_es_future(|| -1);
+        std::thread::spawn(move || {
+            drop(resolver);
+        });
+        assert_eq!(futures::executor::block_on(fut), -1);
+    }
+}
@@ -0,0 +1,275 @@
+//! the EsObjectRef is a live handle to an object in the script engine
+//!
+//! where an EsValueFacade is always a copy of the object in the script engine, an EsObjectRef keeps
+//! the original object alive (rooted) and reads from or writes to that object when you call its methods
+//! all methods are run in the worker thread of the EsRuntime so an EsObjectRef can be used from any thread
+//!
+//! # Example
+//!
+//! ```no_run
+//! use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+//! use es_runtime::esvaluefacade::EsValueFacade;
+//!
+//! let rt = EsRuntimeBuilder::new().build();
+//! let obj_ref = rt.eval_object_ref_sync("this.my_obj = {a: 1}; my_obj;", "test_esobjectref.es")
+//!     .ok().expect("script failed");
+//! obj_ref.set_prop("b", EsValueFacade::new_i32(2)).ok().expect("set_prop failed");
+//! let esvf = rt.eval_sync("my_obj.b;", "test_esobjectref2.es").ok().expect("script failed");
+//! assert_eq!(esvf.get_i32(), &2);
+//! ```
+
+use crate::esruntimeinner::EsRuntimeInner;
+use crate::esvaluefacade::EsValueFacade;
+use crate::jsapi_utils::rooting::EsPersistentRooted;
+use crate::jsapi_utils::{functions, objects, EsErrorInfo};
+use crate::spidermonkeyruntimewrapper;
+use crate::spidermonkeyruntimewrapper::SmRuntime;
+use log::trace;
+use mozjs::jsapi::JSContext;
+use mozjs::jsapi::JSObject;
+use mozjs::jsval::{JSVal, UndefinedValue};
+use mozjs::rust::{HandleObject, Runtime};
+use std::sync::Arc;
+
+/// a live reference to an object in the script engine
+/// the object is rooted for as long as the EsObjectRef lives and is unrooted when it is dropped
+pub struct EsObjectRef {
+    cached_id: usize,
+    rt_inner: Arc<EsRuntimeInner>,
+}
+
+impl EsObjectRef {
+    /// create a new EsObjectRef for an object, this may only be called from the worker thread of an EsRuntime
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn new(context: *mut JSContext, obj: *mut JSObject) -> Self {
+        let rt_inner = SmRuntime::clone_current_esrt_inner_arc();
+        let cached_id = spidermonkeyruntimewrapper::register_cached_object(context, obj);
+        trace!("EsObjectRef::new id={}", cached_id);
+        EsObjectRef {
+            cached_id,
+            rt_inner,
+        }
+    }
+
+    /// get the referenced object, this may only be called from the worker thread of the EsRuntime
+    /// the returned object should be rooted before doing anything that may trigger the garbage collector
+    pub fn get_obj(&self) -> *mut JSObject {
+        spidermonkeyruntimewrapper::do_with_cached_object(
+            self.cached_id,
+            |epr: &EsPersistentRooted| epr.get(),
+        )
+    }
+
+    fn do_with_obj<R, C>(&self, consumer: C) -> R
+    where
+        C: FnOnce(&Runtime, *mut JSContext, HandleObject, HandleObject) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let cached_id = self.cached_id;
+        self.rt_inner
+            .do_in_es_runtime_thread_sync(move |sm_rt: &SmRuntime| {
+                sm_rt.do_with_jsapi(|rt, cx, global| {
+                    let obj = spidermonkeyruntimewrapper::do_with_cached_object(
+                        cached_id,
+                        |epr: &EsPersistentRooted| epr.get(),
+                    );
+                    rooted!(in (cx) let obj_root = obj);
+                    consumer(rt, cx, global, obj_root.handle())
+                })
+            })
+    }
+
+    /// get the value of a property of the object
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let obj_ref = rt.eval_object_ref_sync("({a: 1});", "test_get_prop.es").ok().expect("script failed");
+    /// let esvf = obj_ref.get_prop("a").ok().expect("get_prop failed");
+    /// assert_eq!(esvf.get_i32(), &1);
+    /// ```
+    pub fn get_prop(&self, prop_name: &str) -> Result<EsValueFacade, EsErrorInfo> {
+        let prop_name = prop_name.to_string();
+        self.do_with_obj(move |rt, cx, global, obj| {
+            rooted!(in (cx) let mut rval = UndefinedValue());
+            objects::get_es_obj_prop_val(cx, obj, prop_name.as_str(), rval.handle_mut())?;
+            Ok(EsValueFacade::new_v(rt, cx, global, rval.handle()))
+        })
+    }
+
+    /// get a property of the object as a new EsObjectRef, this fails if the prop is not an object
+    /// this can be used to walk an object graph without copying it
+    pub fn get_prop_ref(&self, prop_name: &str) -> Result<EsObjectRef, EsErrorInfo> {
+        let prop_name = prop_name.to_string();
+        self.do_with_obj(move |_rt, cx, _global, obj| {
+            rooted!(in (cx) let mut rval = UndefinedValue());
+            objects::get_es_obj_prop_val(cx, obj, prop_name.as_str(), rval.handle_mut())?;
+            let val: JSVal = *rval;
+            if val.is_object() {
+                Ok(EsObjectRef::new(cx, val.to_object()))
+            } else {
+                Err(EsErrorInfo {
+                    message: format!("{} was not an object.", prop_name),
+                    filename: "".to_string(),
+                    lineno: 0,
+                    column: 0,
+                })
+            }
+        })
+    }
+
+    /// set the value of a property of the object, this works like obj[prop_name] = val; in script
+    /// so setters are invoked
+    pub fn set_prop(&self, prop_name: &str, val: EsValueFacade) -> Result<(), EsErrorInfo> {
+        let prop_name = prop_name.to_string();
+        self.do_with_obj(move |_rt, cx, _global, obj| {
+            rooted!(in (cx) let val_root = val.to_es_value(cx));
+            objects::assign_es_obj_prop_val(cx, obj, prop_name.as_str(), val_root.handle())
+        })
+    }
+
+    /// delete a property of the object
+    /// returns Ok(false) if the prop could not be deleted
+    pub fn delete_prop(&self, prop_name: &str) -> Result<bool, EsErrorInfo> {
+        let prop_name = prop_name.to_string();
+        self.do_with_obj(move |_rt, cx, _global, obj| {
+            objects::delete_es_obj_prop(cx, obj, prop_name.as_str())
+        })
+    }
+
+    /// get the names of the own enumerable properties of the object, like Object.keys(obj) in script
+    pub fn keys(&self) -> Vec<String> {
+        self.do_with_obj(|_rt, cx, _global, obj| objects::get_js_obj_prop_names(cx, obj))
+    }
+
+    /// call a method of the object with the object as this
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let obj_ref = rt.eval_object_ref_sync("({a: 1, add: function(b){return this.a + b;}});", "test_call_method.es")
+    ///     .ok().expect("script failed");
+    /// let esvf = obj_ref.call_method("add", vec![EsValueFacade::new_i32(2)]).ok().expect("call_method failed");
+    /// assert_eq!(esvf.get_i32(), &3);
+    /// ```
+    pub fn call_method(
+        &self,
+        method_name: &str,
+        args: Vec<EsValueFacade>,
+    ) -> Result<EsValueFacade, EsErrorInfo> {
+        let method_name = method_name.to_string();
+        self.do_with_obj(move |rt, cx, global, obj| {
+            spidermonkeyruntimewrapper::do_with_rooted_esvf_vec(cx, args, |hva| {
+                rooted!(in (cx) let mut rval = UndefinedValue());
+                functions::call_method_name2(
+                    cx,
+                    obj,
+                    method_name.as_str(),
+                    hva,
+                    rval.handle_mut(),
+                )?;
+                Ok(EsValueFacade::new_v(rt, cx, global, rval.handle()))
+            })
+        })
+    }
+
+    /// create a copy of the current state of the object as an EsValueFacade
+    pub fn snapshot(&self) -> EsValueFacade {
+        self.do_with_obj(|rt, cx, global, obj| {
+            rooted!(in (cx) let obj_val = mozjs::jsval::ObjectValue(*obj));
+            EsValueFacade::new_v(rt, cx, global, obj_val.handle())
+        })
+    }
+}
+
+impl Drop for EsObjectRef {
+    fn drop(&mut self) {
+        let cached_id = self.cached_id;
+        trace!("EsObjectRef::drop id={}", cached_id);
+        self.rt_inner.do_in_es_runtime_thread(move |_sm_rt| {
+            spidermonkeyruntimewrapper::consume_cached_object(cached_id);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esvaluefacade::EsValueFacade;
+
+    #[test]
+    fn test_object_ref() {
+        log::info!("test: test_object_ref");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let obj_ref = rt
+            .eval_object_ref_sync(
+                "this.test_object_ref_obj = {a: 1, b: 2, sum: function(c){return this.a + this.b + c;}}; test_object_ref_obj;",
+                "test_object_ref.es",
+            )
+            .ok()
+            .expect("script failed");
+
+        assert_eq!(obj_ref.get_prop("a").ok().unwrap().get_i32(), &1);
+        assert_eq!(obj_ref.keys(), vec!["a", "b", "sum"]);
+
+        obj_ref
+            .set_prop("a", EsValueFacade::new_i32(5))
+            .ok()
+            .expect("set_prop failed");
+        // the change should be visible in script
+        let esvf = rt
+            .eval_sync("test_object_ref_obj.a;", "test_object_ref2.es")
+            .ok()
+            .unwrap();
+        assert_eq!(esvf.get_i32(), &5);
+
+        let sum_esvf = obj_ref
+            .call_method("sum", vec![EsValueFacade::new_i32(3)])
+            .ok()
+            .expect("call_method failed");
+        assert_eq!(sum_esvf.get_i32(), &10);
+
+        assert!(obj_ref.delete_prop("b").ok().unwrap());
+        assert_eq!(obj_ref.keys(), vec!["a", "sum"]);
+
+        let snapshot = obj_ref.snapshot();
+        assert!(snapshot.is_object());
+        assert_eq!(snapshot.get_object().get("a").unwrap().get_i32(), &5);
+
+        // passing the ref back to script should pass the original object
+        rt.eval_sync(
+            "this.test_object_ref_is = function(o){return o === test_object_ref_obj;};",
+            "test_object_ref3.es",
+        )
+        .ok()
+        .unwrap();
+        let is_esvf = rt
+            .call_sync(
+                vec![],
+                "test_object_ref_is",
+                vec![EsValueFacade::new_object_ref(obj_ref)],
+            )
+            .ok()
+            .unwrap();
+        assert!(is_esvf.get_boolean());
+    }
+
+    #[test]
+    fn test_object_ref_gc() {
+        log::info!("test: test_object_ref_gc");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let obj_ref = rt
+            .eval_object_ref_sync("({a: {b: 'hello'}});", "test_object_ref_gc.es")
+            .ok()
+            .expect("script failed");
+        let sub_ref = obj_ref.get_prop_ref("a").ok().expect("get_prop_ref failed");
+        drop(obj_ref);
+
+        rt.cleanup_sync();
+
+        assert_eq!(sub_ref.get_prop("b").ok().unwrap().get_string(), "hello");
+        assert!(sub_ref.get_prop_ref("b").is_err());
+    }
+}
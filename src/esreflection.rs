@@ -32,7 +32,7 @@
 //!     })
 //!     .event("EventA")
 //!     .event("EventB")
-//!     .build(&rt);
+//!     .build(&rt).ok().expect("could not build proxy");
 //!
 //!     rt.eval_sync("let my_instance = new com.my.biz.MyClass(1, 2, 3); my_instance.do_something(); my_instance.my_prop = 541; let a = my_instance.my_prop; my_instance = null;", "es_proxy_example.es").ok().expect("script failed");
 //!
@@ -43,7 +43,7 @@
 //!          Ok(EsValueFacade::new_bool(true))
 //!      })
 //!     .static_event("epiphany")
-//!     .build(&rt);
+//!     .build(&rt).ok().expect("could not build proxy");
 //!
 //!     rt.eval_sync("com.my.biz.MyApp.addEventListener('epiphany', (evt) => {console.log('Rust had an epiphany about %s', evt.subject);});com.my.biz.MyApp.inform(1, 2, 3);", "es_proxy_example2.es").ok().unwrap();
 //!
@@ -54,12 +54,34 @@
 //! ```
 //!
 //!
+use crate::esconversions::ToEsValue;
+use crate::esobjectref::EsObjectRef;
 use crate::esruntime::EsRuntime;
 use crate::esvaluefacade::EsValueFacade;
-use crate::jsapi_utils::reflection::{get_proxy, ProxyBuilder};
-use mozjs::jsval::JSVal;
+pub use crate::jsapi_utils::reflection::EventListenerOptions;
+pub use crate::jsapi_utils::reflection::PropertyAttributes;
+use crate::jsapi_utils::reflection::{
+    describe_proxies, generate_typescript_declarations, get_obj_id_for, get_proxy, get_proxy_for,
+    unregister_proxy, Getter, IteratorNext, Proxy, ProxyBuilder, Setter, StaticGetter,
+    StaticSetter,
+};
+pub use crate::jsapi_utils::reflection::{
+    MemberDescription, PropertyDescription, ProxyDescription,
+};
+use crate::jsapi_utils::EsErrorInfo;
+use crate::spidermonkeyruntimewrapper::{do_with_rooted_esvf_vec, SmRuntime};
+use crate::utils::AutoIdMap;
+use mozjs::jsapi::JSContext;
+use mozjs::jsapi::JSObject;
+use mozjs::jsval::{JSVal, ObjectValue};
+use mozjs::rust::{HandleValue, MutableHandleValue};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
 use std::ptr::replace;
+use std::rc::Rc;
+use std::sync::Arc;
 
 pub type EsProxyConstructor = dyn Fn(Vec<EsValueFacade>) -> Result<i32, String> + Send;
 pub type EsProxyMethod = dyn Fn(&i32, Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send;
@@ -69,6 +91,27 @@ pub type EsProxySetter = dyn Fn(&i32, EsValueFacade) -> Result<(), String> + Sen
 pub type EsProxyStaticMethod = dyn Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send;
 pub type EsProxyStaticGetter = dyn Fn() -> Result<EsValueFacade, String> + Send;
 pub type EsProxyStaticSetter = dyn Fn(EsValueFacade) -> Result<(), String> + Send;
+pub type EsProxyIterator =
+    dyn Fn(&i32) -> Result<Box<dyn Iterator<Item = EsValueFacade>>, String> + Send;
+pub type EsProxyIndexLength = dyn Fn(&i32) -> Result<u32, String> + Send;
+pub type EsProxyIndexGetter = dyn Fn(&i32, u32) -> Result<EsValueFacade, String> + Send;
+pub type EsProxyIndexSetter = dyn Fn(&i32, u32, EsValueFacade) -> Result<(), String> + Send;
+pub type EsProxyDynamicGetter = dyn Fn(&i32, &str) -> Result<EsValueFacade, String> + Send;
+pub type EsProxyDynamicSetter = dyn Fn(&i32, &str, EsValueFacade) -> Result<(), String> + Send;
+pub type EsProxyDynamicHas = dyn Fn(&i32, &str) -> Result<bool, String> + Send;
+pub type EsProxyDynamicDelete = dyn Fn(&i32, &str) -> Result<bool, String> + Send;
+pub type EsProxyDynamicKeys = dyn Fn(&i32) -> Result<Vec<String>, String> + Send;
+pub type EsTypedProxyFinalizer<T> = dyn Fn(T) -> () + Send;
+pub type EsTypedProxyGetter<T> = dyn Fn(&T) -> Result<EsValueFacade, String> + Send;
+pub type EsTypedProxySetter<T> = dyn Fn(&mut T, EsValueFacade) -> Result<(), String> + Send;
+
+/// the result of dispatching an event with EsProxy::dispatch_event_sync
+pub struct EsEventDispatchResult {
+    /// the return values of the listeners in the order in which they were called
+    pub results: Vec<EsValueFacade>,
+    /// true if a listener called preventDefault() on the event object or returned false
+    pub default_prevented: bool,
+}
 
 pub struct EsProxy {
     namespace: Vec<&'static str>,
@@ -78,18 +121,51 @@ pub struct EsProxy {
 pub struct EsProxyBuilder {
     pub namespace: Vec<&'static str>,
     pub class_name: &'static str,
+    extends: Option<String>,
 
     constructor: Option<Box<EsProxyConstructor>>,
     finalizer: Option<Box<EsProxyFinalizer>>,
 
     methods: HashMap<&'static str, Box<EsProxyMethod>>,
-    properties: HashMap<&'static str, (Box<EsProxyGetter>, Box<EsProxySetter>)>,
+    properties: HashMap<
+        &'static str,
+        (
+            Option<Box<EsProxyGetter>>,
+            Option<Box<EsProxySetter>>,
+            PropertyAttributes,
+        ),
+    >,
+    constants: HashMap<&'static str, EsValueFacade>,
 
     events: HashSet<&'static str>,
 
-    static_properties: HashMap<&'static str, (Box<EsProxyStaticGetter>, Box<EsProxyStaticSetter>)>,
+    iterator: Option<Box<EsProxyIterator>>,
+    indexed: Option<(Box<EsProxyIndexLength>, Box<EsProxyIndexGetter>)>,
+    index_setter: Option<Box<EsProxyIndexSetter>>,
+    dynamic_properties: Option<EsDynamicProperties>,
+
+    static_properties: HashMap<
+        &'static str,
+        (
+            Option<Box<EsProxyStaticGetter>>,
+            Option<Box<EsProxyStaticSetter>>,
+            PropertyAttributes,
+        ),
+    >,
     static_methods: HashMap<&'static str, Box<EsProxyStaticMethod>>,
     static_events: HashSet<&'static str>,
+    static_constants: HashMap<&'static str, EsValueFacade>,
+
+    doc: Option<String>,
+    member_docs: HashMap<String, String>,
+}
+
+struct EsDynamicProperties {
+    getter: Box<EsProxyDynamicGetter>,
+    setter: Box<EsProxyDynamicSetter>,
+    has: Box<EsProxyDynamicHas>,
+    delete: Box<EsProxyDynamicDelete>,
+    keys: Box<EsProxyDynamicKeys>,
 }
 
 impl EsProxy {
@@ -97,7 +173,6 @@ impl EsProxy {
     pub fn builder(namespace: Vec<&'static str>, class_name: &'static str) -> EsProxyBuilder {
         EsProxyBuilder::new(namespace, class_name)
     }
-    // todo do we want sync variants which may return a veto boolean or an altered EsValueFacade?
     /// dispatch an event for an instance of the class
     ///
     /// you can pass an EsValueFacade as event obj
@@ -114,7 +189,7 @@ impl EsProxy {
     ///.constructor(|args| {
     ///    Ok(1)
     ///})
-    ///.event("some_event").build(&rt);
+    ///.event("some_event").build(&rt).ok().expect("could not build proxy");
     ///rt.eval_sync("let i = new my.biz.MyClass(); \
     ///              i.addEventListener('some_event', (evtObj) => {\
     ///                  console.log('it happened!');\
@@ -131,10 +206,11 @@ impl EsProxy {
         let p_name = self.get_canonical_name();
         rt.do_in_es_runtime_thread(move |sm_rt| {
             sm_rt.do_with_jsapi(move |_rt, cx, _global| {
-                let proxy = get_proxy(p_name.as_str()).unwrap();
-                let event_obj_value: JSVal = event_obj.to_es_value(cx);
-                rooted!(in (cx) let event_obj_root = event_obj_value);
-                proxy.dispatch_event(obj_id, event_name, cx, event_obj_root.handle().into());
+                if let Some(proxy) = get_proxy(p_name.as_str()) {
+                    let event_obj_value: JSVal = event_obj.to_es_value(cx);
+                    rooted!(in (cx) let event_obj_root = event_obj_value);
+                    proxy.dispatch_event(obj_id, event_name, cx, event_obj_root.handle().into());
+                }
             });
         });
     }
@@ -155,7 +231,7 @@ impl EsProxy {
     ///.constructor(|args| {
     ///    Ok(1)
     ///})
-    ///.event("some_event").build(&rt);
+    ///.event("some_event").build(&rt).ok().expect("could not build proxy");
     ///rt.eval_sync("let i = new my.biz.MyClass(); \
     ///              i.addEventListener('some_event', (evtObj) => {\
     ///                  console.log('it happened!');\
@@ -171,14 +247,379 @@ impl EsProxy {
         let p_name = self.get_canonical_name();
         rt.do_in_es_runtime_thread(move |sm_rt| {
             sm_rt.do_with_jsapi(move |_rt, cx, _global| {
-                let proxy = get_proxy(p_name.as_str()).unwrap();
-                let event_obj_value: JSVal = event_obj.to_es_value(cx);
-                rooted!(in (cx) let event_obj_root = event_obj_value);
-                proxy.dispatch_static_event(event_name, cx, event_obj_root.handle().into());
+                if let Some(proxy) = get_proxy(p_name.as_str()) {
+                    let event_obj_value: JSVal = event_obj.to_es_value(cx);
+                    rooted!(in (cx) let event_obj_root = event_obj_value);
+                    proxy.dispatch_static_event(event_name, cx, event_obj_root.handle().into());
+                }
             });
         });
     }
 
+    /// create a new instance of the class from rust, the constructor of the class is called with args
+    ///
+    /// the instance is returned as an EsValueFacade which refers to the live object (see
+    /// EsValueFacade::get_proxy_instance) so it can be passed to script as an argument or returned
+    /// from a rust function
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "Connection")
+    ///.constructor(|_args| {
+    ///    Ok(1)
+    ///})
+    ///.method("query", |_obj_id, _args| {
+    ///    Ok(EsValueFacade::new_i32(42))
+    ///})
+    ///.build(&rt).ok().expect("could not build proxy");
+    ///let conn = es_proxy.new_instance(&rt, vec![EsValueFacade::new_str("db://localhost".to_string())])
+    ///    .ok().expect("could not create instance");
+    ///rt.eval_sync("this.useConnection = function(conn){return conn.query();};", "test_new_instance.es")
+    ///    .ok().expect("script failed");
+    ///let esvf = rt.call_sync(vec![], "useConnection", vec![conn]).ok().expect("call failed");
+    ///assert_eq!(esvf.get_i32(), &42);
+    /// ```
+    pub fn new_instance(
+        &self,
+        rt: &EsRuntime,
+        args: Vec<EsValueFacade>,
+    ) -> Result<EsValueFacade, EsErrorInfo> {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt.do_with_jsapi(move |rt, cx, global| {
+                let proxy = get_proxy(p_name.as_str()).ok_or_else(|| EsErrorInfo {
+                    message: format!("class {} is not registered", p_name),
+                    filename: "".to_string(),
+                    lineno: 0,
+                    column: 0,
+                })?;
+                rooted!(in (cx) let mut instance_root = std::ptr::null_mut::<JSObject>());
+                do_with_rooted_esvf_vec(cx, args, |hva| {
+                    proxy.new_instance(cx, global, hva, instance_root.handle_mut())
+                })?;
+                rooted!(in (cx) let instance_val_root = ObjectValue(*instance_root));
+                Ok(EsValueFacade::new_v(
+                    rt,
+                    cx,
+                    global,
+                    instance_val_root.handle(),
+                ))
+            })
+        })
+    }
+
+    /// dispatch an event for an instance of the class and wait for the listeners to be called
+    ///
+    /// the result contains the return values of the listeners and whether a listener vetoed the
+    /// event by calling preventDefault() on the event object or by returning false
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///.constructor(|args| {
+    ///    Ok(1)
+    ///})
+    ///.event("closing").build(&rt).ok().expect("could not build proxy");
+    ///rt.eval_sync("let i = new my.biz.MyClass(); \
+    ///              i.addEventListener('closing', (evt) => {\
+    ///                  evt.preventDefault();\
+    ///              });", "test_dispatch_event_sync.es");
+    ///let res = es_proxy.dispatch_event_sync(&rt, 1, "closing", EsValueFacade::new_obj(vec![]));
+    ///assert!(res.default_prevented);
+    /// ```
+    pub fn dispatch_event_sync(
+        &self,
+        rt: &EsRuntime,
+        obj_id: i32,
+        event_name: &'static str,
+        event_obj: EsValueFacade,
+    ) -> EsEventDispatchResult {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt.do_with_jsapi(move |rt, cx, global| {
+                let mut results = vec![];
+                let proxy = match get_proxy(p_name.as_str()) {
+                    Some(proxy) => proxy,
+                    None => {
+                        return EsEventDispatchResult {
+                            results,
+                            default_prevented: false,
+                        }
+                    }
+                };
+                let event_obj_value: JSVal = event_obj.to_es_value(cx);
+                rooted!(in (cx) let event_obj_root = event_obj_value);
+                let default_prevented = proxy.dispatch_event_sync(
+                    obj_id,
+                    event_name,
+                    cx,
+                    event_obj_root.handle().into(),
+                    &mut |cx, res| results.push(EsValueFacade::new_v(rt, cx, global, res)),
+                );
+                EsEventDispatchResult {
+                    results,
+                    default_prevented,
+                }
+            })
+        })
+    }
+
+    /// dispatch a static event for the class and wait for the listeners to be called
+    /// see dispatch_event_sync
+    pub fn dispatch_static_event_sync(
+        &self,
+        rt: &EsRuntime,
+        event_name: &'static str,
+        event_obj: EsValueFacade,
+    ) -> EsEventDispatchResult {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt.do_with_jsapi(move |rt, cx, global| {
+                let mut results = vec![];
+                let proxy = match get_proxy(p_name.as_str()) {
+                    Some(proxy) => proxy,
+                    None => {
+                        return EsEventDispatchResult {
+                            results,
+                            default_prevented: false,
+                        }
+                    }
+                };
+                let event_obj_value: JSVal = event_obj.to_es_value(cx);
+                rooted!(in (cx) let event_obj_root = event_obj_value);
+                let default_prevented = proxy.dispatch_static_event_sync(
+                    event_name,
+                    cx,
+                    event_obj_root.handle().into(),
+                    &mut |cx, res| results.push(EsValueFacade::new_v(rt, cx, global, res)),
+                );
+                EsEventDispatchResult {
+                    results,
+                    default_prevented,
+                }
+            })
+        })
+    }
+
+    /// add a rust listener for an event of an instance of the class, the listener is also called
+    /// for events which are dispatched from script
+    ///
+    /// the listener gets a copy of the event object, its return value is added to the results of
+    /// dispatch_event_sync and returning false vetoes the event
+    /// returns an id which can be used to remove the listener
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::{EsProxyBuilder, EventListenerOptions};
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///.constructor(|args| {
+    ///    Ok(1)
+    ///})
+    ///.event("saved").build(&rt).ok().expect("could not build proxy");
+    ///es_proxy.add_event_listener(&rt, 1, "saved", EventListenerOptions { once: true }, |evt| {
+    ///    println!("saved {}", evt.get_object().get("name").unwrap().get_string());
+    ///    Ok(EsValueFacade::undefined())
+    ///}).ok().expect("could not add listener");
+    ///rt.eval_sync("let i = new my.biz.MyClass(); i.dispatchEvent('saved', {name: 'a'});", "test_add_event_listener.es");
+    /// ```
+    pub fn add_event_listener<L>(
+        &self,
+        rt: &EsRuntime,
+        obj_id: i32,
+        event_name: &'static str,
+        options: EventListenerOptions,
+        listener: L,
+    ) -> Result<i32, String>
+    where
+        L: Fn(EsValueFacade) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            let proxy = get_registered_proxy(p_name.as_str())?;
+            proxy.add_event_listener(
+                obj_id,
+                event_name,
+                options,
+                Box::new(move |cx, evt_obj, mut rval| {
+                    let res = listener(EsValueFacade::new_v_in_current_rt(evt_obj))?;
+                    rval.set(res.to_es_value(cx));
+                    Ok(())
+                }),
+            )
+        })
+    }
+
+    /// remove a listener which was added with add_event_listener
+    pub fn remove_event_listener(
+        &self,
+        rt: &EsRuntime,
+        obj_id: i32,
+        event_name: &'static str,
+        listener_id: i32,
+    ) {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            if let Some(proxy) = get_proxy(p_name.as_str()) {
+                proxy.remove_event_listener(obj_id, event_name, listener_id);
+            }
+        })
+    }
+
+    /// add a rust listener for a static event of the class, see add_event_listener
+    pub fn add_static_event_listener<L>(
+        &self,
+        rt: &EsRuntime,
+        event_name: &'static str,
+        options: EventListenerOptions,
+        listener: L,
+    ) -> Result<i32, String>
+    where
+        L: Fn(EsValueFacade) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            let proxy = get_registered_proxy(p_name.as_str())?;
+            proxy.add_static_event_listener(
+                event_name,
+                options,
+                Box::new(move |cx, evt_obj, mut rval| {
+                    let res = listener(EsValueFacade::new_v_in_current_rt(evt_obj))?;
+                    rval.set(res.to_es_value(cx));
+                    Ok(())
+                }),
+            )
+        })
+    }
+
+    /// remove a listener which was added with add_static_event_listener
+    pub fn remove_static_event_listener(
+        &self,
+        rt: &EsRuntime,
+        event_name: &'static str,
+        listener_id: i32,
+    ) {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            if let Some(proxy) = get_proxy(p_name.as_str()) {
+                proxy.remove_static_event_listener(event_name, listener_id);
+            }
+        })
+    }
+
+    /// unregister the class, e.g. when the plugin which provided it is unloaded
+    ///
+    /// the class is removed from its namespace and can no longer be constructed, existing
+    /// instances keep working and are still finalized when they are garbage collected
+    /// a class can not be unregistered while other classes extend it
+    ///
+    /// building a class with the same canonical name replaces the class in the same way (so that
+    /// also fails while other classes extend it), an EsProxy always refers to the class which is
+    /// currently registered under its canonical name
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyPlugin")
+    ///    .constructor(|_args| Ok(1))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    ///es_proxy.unregister(&rt).ok().expect("could not unregister");
+    ///let esvf = rt.eval_sync("typeof my.biz.MyPlugin;", "test_unregister.es").ok().expect("script failed");
+    ///assert_eq!(esvf.get_string(), "undefined");
+    /// ```
+    pub fn unregister(&self, rt: &EsRuntime) -> Result<(), String> {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt
+                .do_with_jsapi(move |_rt, cx, global| unregister_proxy(cx, global, p_name.as_str()))
+        })
+    }
+
+    /// describe the methods, properties, constants and events of the class
+    /// returns None if the class is no longer registered
+    ///
+    /// the same description is available in script by calling esses.describe() with the
+    /// canonical name of the class, its constructor, the singleton or an instance
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|_args| Ok(1))
+    ///    .method("do_something", |_obj_id, _args| Ok(EsValueFacade::undefined()))
+    ///    .event("saved")
+    ///    .build(&rt).ok().expect("could not build proxy");
+    ///let desc = es_proxy.describe(&rt).expect("class was not registered");
+    ///assert!(desc.constructable);
+    ///assert_eq!(desc.methods[0].name, "do_something");
+    ///assert_eq!(desc.events[0].name, "saved");
+    ///let esvf = rt.eval_sync("esses.describe(my.biz.MyClass).methods[0].name;", "test_describe.es").ok().expect("script failed");
+    ///assert_eq!(esvf.get_string(), "do_something");
+    /// ```
+    pub fn describe(&self, rt: &EsRuntime) -> Option<ProxyDescription> {
+        let p_name = self.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            get_proxy(p_name.as_str()).map(|proxy| proxy.describe())
+        })
+    }
+
+    /// describe all proxy classes registered in a runtime, sorted by their canonical name
+    pub fn describe_all(rt: &EsRuntime) -> Vec<ProxyDescription> {
+        rt.do_in_es_runtime_thread_sync(|_sm_rt| describe_proxies())
+    }
+
+    /// generate a TypeScript declaration file (.d.ts) for all proxy classes registered in a runtime
+    /// and the global functions added with SmRuntime::add_global_function
+    /// internal functions (starting with __) are not declared
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::{EsProxy, EsProxyBuilder};
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|_args| Ok(1))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    ///let dts = EsProxy::generate_typescript_declarations(&rt);
+    ///std::fs::write("my_biz.d.ts", dts).expect("could not write declarations");
+    /// ```
+    pub fn generate_typescript_declarations(rt: &EsRuntime) -> String {
+        rt.do_in_es_runtime_thread_sync(|_sm_rt| {
+            let global_functions = SmRuntime::get_global_function_names()
+                .into_iter()
+                .filter(|name| !name.starts_with("__"))
+                .collect();
+            generate_typescript_declarations(global_functions)
+        })
+    }
+
     /// get the canonical name of the Proxy Class, this includes the namespace
     /// e.g. "my.biz.MyApp"
     /// # Example
@@ -189,7 +630,7 @@ impl EsProxy {
     /// use es_runtime::esvaluefacade::EsValueFacade;
     ///
     ///let rt = EsRuntimeBuilder::default().build();
-    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass").build(&rt);
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass").build(&rt).ok().expect("could not build proxy");
     ///assert_eq!(es_proxy.get_canonical_name().as_str(), "my.biz.MyClass");
     /// ```
     pub fn get_canonical_name(&self) -> String {
@@ -210,23 +651,63 @@ impl EsProxyBuilder {
     /// use es_runtime::esvaluefacade::EsValueFacade;
     ///
     ///let rt = EsRuntimeBuilder::default().build();
-    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass").build(&rt);
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass").build(&rt).ok().expect("could not build proxy");
     /// ```
     pub fn new(namespace: Vec<&'static str>, class_name: &'static str) -> Self {
         EsProxyBuilder {
             namespace,
             class_name,
+            extends: None,
             constructor: None,
             finalizer: None,
             methods: Default::default(),
             properties: Default::default(),
+            constants: Default::default(),
             events: Default::default(),
+            iterator: None,
+            indexed: None,
+            index_setter: None,
+            dynamic_properties: None,
             static_properties: Default::default(),
             static_methods: Default::default(),
             static_events: Default::default(),
+            static_constants: Default::default(),
+            doc: None,
+            member_docs: Default::default(),
         }
     }
 
+    /// set the documentation of the class, the documentation is included in EsProxy::describe()
+    /// and in the TypeScript declarations generated by EsProxy::generate_typescript_declarations()
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .doc("a class which does something")
+    ///    .method("do_something", |_obj_id, _args| Ok(EsValueFacade::undefined()))
+    ///    .member_doc("do_something", "do something in rust")
+    ///    .build(&rt).ok().expect("could not build proxy");
+    ///let desc = es_proxy.describe(&rt).expect("class was not registered");
+    ///assert_eq!(desc.methods[0].doc.as_deref(), Some("do something in rust"));
+    /// ```
+    pub fn doc(&mut self, doc: &str) -> &mut Self {
+        self.doc = Some(doc.to_string());
+        self
+    }
+
+    /// set the documentation of a method, property, constant or event of the class
+    /// static members and instance members with the same name share their documentation
+    pub fn member_doc(&mut self, name: &str, doc: &str) -> &mut Self {
+        self.member_docs.insert(name.to_string(), doc.to_string());
+        self
+    }
+
     /// the constrcutor is called when the script runtime instantiates an instance of you class
     /// if you do not define a constrcutor for you proxy your proxy will not be constructable
     ///
@@ -244,7 +725,7 @@ impl EsProxyBuilder {
     ///         // return an id which you can use to identify your rust objects
     ///         Ok(123)
     ///    })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("let mc = new my.biz.MyClass();", "test_constructor.es").ok().unwrap();
     /// // call the gc
@@ -275,7 +756,7 @@ impl EsProxyBuilder {
     ///    .finalizer(|obj_id| {
     ///         println!("do cleanup for objId {}", obj_id);
     ///    })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("let mc = new my.biz.MyClass(); mc = null;", "test_finalizer.es")
     ///     .ok().expect("script failed");
@@ -309,7 +790,7 @@ impl EsProxyBuilder {
     ///         println!("doing something for objId {}", obj_id);
     ///         Ok(EsValueFacade::undefined())
     ///    })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("let mc = new my.biz.MyClass(); mc.doSomething();", "test_method.es")
     ///     .ok().expect("script failed");
@@ -323,6 +804,45 @@ impl EsProxyBuilder {
         self
     }
 
+    /// add an async method to the proxy class, the method is run in the helper thread pool and
+    /// returns a Promise to script which is resolved with the result of the method
+    /// use this for slow methods so they don't block the runtime
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|args| {
+    ///         Ok(1)
+    ///    })
+    ///    .async_method("load", |obj_id, args| {
+    ///         // read a file or do a request here
+    ///         Ok(EsValueFacade::new_str(format!("loaded for objId {}", obj_id)))
+    ///    })
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let mc = new my.biz.MyClass(); mc.load().then((res) => {console.log(res);});", "test_async_method.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn async_method<M>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(&i32, Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static,
+    {
+        let method_arc = Arc::new(method);
+        self.method(name, move |obj_id, args| {
+            let obj_id = *obj_id;
+            let method_arc = method_arc.clone();
+            Ok(EsValueFacade::new_promise(move || {
+                method_arc(&obj_id, args)
+            }))
+        })
+    }
+
     /// add a property to the proxy class, the getter and setter can be called on an instance
     /// of the class
     ///
@@ -345,7 +865,7 @@ impl EsProxyBuilder {
     ///         println!("setting some_prop to {} for objId {}", arg.get_i32(), obj_id);             
     ///         Ok(())
     ///     })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("let mc = new my.biz.MyClass(); \
     /// mc.someProp = 4321; \
@@ -358,8 +878,103 @@ impl EsProxyBuilder {
         G: Fn(&i32) -> Result<EsValueFacade, String> + Send + 'static,
         S: Fn(&i32, EsValueFacade) -> Result<(), String> + Send + 'static,
     {
-        self.properties
-            .insert(name, (Box::new(getter), Box::new(setter)));
+        self.accessor(
+            name,
+            Some(Box::new(getter)),
+            Some(Box::new(setter)),
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a read only property to the proxy class, assigning the property from script is
+    /// ignored or fails in strict mode
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|_args| Ok(1))
+    ///    .readonly_property("id", |obj_id| Ok(EsValueFacade::new_i32(*obj_id)))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("'use strict'; let mc = new my.biz.MyClass(); \
+    /// try {mc.id = 2;} catch(ex) {console.log('id is read only');}", "test_readonly_property.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn(&i32) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.accessor(
+            name,
+            Some(Box::new(getter)),
+            None,
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a property with an optional getter and an optional setter to the proxy class
+    /// a property without a getter is write only and a property without a setter is read only
+    /// the attributes determine if the property is enumerable (e.g. by Object.keys() or JSON.stringify())
+    /// and if it may be deleted or redefined from script
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::{EsProxyBuilder, PropertyAttributes};
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|_args| Ok(1))
+    ///    .accessor("password", None, Some(Box::new(|_obj_id: &i32, val: EsValueFacade| {
+    ///         println!("setting password to {}", val.get_string());
+    ///         Ok(())
+    ///    })), PropertyAttributes{enumerable: false, configurable: false})
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let mc = new my.biz.MyClass(); mc.password = 'secret'; \
+    /// console.log(JSON.stringify(mc));", "test_accessor.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<Box<EsProxyGetter>>,
+        setter: Option<Box<EsProxySetter>>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
+        self.properties.insert(name, (getter, setter, attributes));
+        self
+    }
+
+    /// add a constant to the proxy class, the constant is defined on the prototype of the class
+    /// so it can be read from every instance, it is read only and not enumerable
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .constructor(|_args| Ok(1))
+    ///    .constant("kind", EsValueFacade::new_str("my_class".to_string()))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let mc = new my.biz.MyClass(); console.log(mc.kind);", "test_constant.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn constant(&mut self, name: &'static str, value: EsValueFacade) -> &mut Self {
+        self.constants.insert(name, value);
         self
     }
 
@@ -380,7 +995,7 @@ impl EsProxyBuilder {
     ///          Ok(1)
     ///    })
     ///    .event("itHappened")
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     ///
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("let mc = new my.biz.MyClass(); \
@@ -410,7 +1025,7 @@ impl EsProxyBuilder {
     ///let rt = EsRuntimeBuilder::default().build();
     ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
     ///    .static_event("itHappened")
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     ///
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("my.biz.MyClass.addEventListener('itHappened', (evtObj) => {console.log('Jup, it happened with %s', evtObj);})", "test_static_event.es").ok().expect("script failed");
@@ -443,7 +1058,7 @@ impl EsProxyBuilder {
     ///         println!("setting some_prop to {}", arg.get_i32());             
     ///         Ok(())
     ///     })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static getter and setter
     /// rt.eval_sync("my.biz.MyClass.someProp = 4321; \
     /// console.log('someprop = %s', my.biz.MyClass.someProp);", "test_static_property.es")
@@ -455,8 +1070,45 @@ impl EsProxyBuilder {
         G: Fn() -> Result<EsValueFacade, String> + Send + 'static,
         S: Fn(EsValueFacade) -> Result<(), String> + Send + 'static,
     {
+        self.static_accessor(
+            name,
+            Some(Box::new(getter)),
+            Some(Box::new(setter)),
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a read only static property to the proxy class, see readonly_property
+    pub fn static_readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn() -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.static_accessor(
+            name,
+            Some(Box::new(getter)),
+            None,
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a static property with an optional getter and an optional setter to the proxy class,
+    /// see accessor
+    pub fn static_accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<Box<EsProxyStaticGetter>>,
+        setter: Option<Box<EsProxyStaticSetter>>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
         self.static_properties
-            .insert(name, (Box::new(getter), Box::new(setter)));
+            .insert(name, (getter, setter, attributes));
+        self
+    }
+
+    /// add a static constant to the proxy class e.g. my.biz.MyClass.MAX_SIZE
+    /// the constant is read only and not enumerable
+    pub fn static_constant(&mut self, name: &'static str, value: EsValueFacade) -> &mut Self {
+        self.static_constants.insert(name, value);
         self
     }
 
@@ -476,7 +1128,7 @@ impl EsProxyBuilder {
     ///        println!("did something static");
     ///        Ok(EsValueFacade::undefined())
     ///    })
-    ///    .build(&rt);
+    ///    .build(&rt).ok().expect("could not build proxy");
     /// // we can then eval script which uses the static method
     /// rt.eval_sync("my.biz.MyClass.doSomethingStatic();", "test_static_method.es")
     /// .ok().expect("script failed");
@@ -490,10 +1142,268 @@ impl EsProxyBuilder {
         self
     }
 
+    /// add a static async method to the proxy class, the method is run in the helper thread pool
+    /// and returns a Promise to script which is resolved with the result of the method
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+    ///    .static_async_method("fetchConfig", |_args| {
+    ///        Ok(EsValueFacade::new_str("config".to_string()))
+    ///    })
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("my.biz.MyClass.fetchConfig().then((res) => {console.log(res);});", "test_static_async_method.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn static_async_method<M>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static,
+    {
+        let method_arc = Arc::new(method);
+        self.static_method(name, move |args| {
+            let method_arc = method_arc.clone();
+            Ok(EsValueFacade::new_promise(move || method_arc(args)))
+        })
+    }
+
+    /// make instances of the proxy class iterable with for..of, spread or Array.from()
+    ///
+    /// the closure is called with the obj_id of the instance every time script starts iterating
+    /// and returns a rust Iterator which produces the values
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "Range")
+    ///    .constructor(|_args| Ok(3))
+    ///    .iterator(|obj_id| Ok((0..*obj_id).map(EsValueFacade::new_i32)))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("for (let i of new my.biz.Range()) {console.log(i);}", "test_iterator.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn iterator<F, I>(&mut self, iterator: F) -> &mut Self
+    where
+        F: Fn(&i32) -> Result<I, String> + Send + 'static,
+        I: Iterator<Item = EsValueFacade> + 'static,
+    {
+        self.iterator = Some(Box::new(move |obj_id: &i32| {
+            let iter: Box<dyn Iterator<Item = EsValueFacade>> = Box::new(iterator(obj_id)?);
+            Ok(iter)
+        }));
+        self
+    }
+
+    /// make instances of the proxy class indexable like an array, e.g. list[0]
+    ///
+    /// this also adds a read only length property, indices lower than the length can be read
+    /// with the getter and are listed by Object.keys()
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "Squares")
+    ///    .constructor(|_args| Ok(1))
+    ///    .indexed(|_obj_id| Ok(10), |_obj_id, index| Ok(EsValueFacade::new_i32((index * index) as i32)))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let sq = new my.biz.Squares(); console.log('%s %s', sq.length, sq[3]);", "test_indexed.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn indexed<L, G>(&mut self, length: L, getter: G) -> &mut Self
+    where
+        L: Fn(&i32) -> Result<u32, String> + Send + 'static,
+        G: Fn(&i32, u32) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.indexed = Some((Box::new(length), Box::new(getter)));
+        self
+    }
+
+    /// make the indices of an indexed proxy class writable, e.g. list[0] = 1;
+    /// only indices lower than the length can be set
+    pub fn indexed_setter<S>(&mut self, setter: S) -> &mut Self
+    where
+        S: Fn(&i32, u32, EsValueFacade) -> Result<(), String> + Send + 'static,
+    {
+        self.index_setter = Some(Box::new(setter));
+        self
+    }
+
+    /// give the proxy class an open ended set of properties, e.g. for a config tree or a row object
+    ///
+    /// properties which are not declared with property() or method() are passed to these closures
+    /// with their name
+    /// * has is called when a property is read or checked with the in operator for the first time,
+    ///   after that the property is defined on the instance and has is not called again for it
+    /// * the getter and setter are called when a property is read or assigned, also for new properties
+    /// * delete is called for the delete operator, return Ok(false) if the property may not be deleted
+    /// * keys lists the properties for Object.keys(), for..in and JSON.stringify(), properties which
+    ///   were defined on the instance but are no longer listed are removed from it then
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let es_proxy = EsProxyBuilder::new(vec!["my", "biz"], "Env")
+    ///    .constructor(|_args| Ok(1))
+    ///    .dynamic_properties(
+    ///        |_obj_id, name| Ok(EsValueFacade::new_str(std::env::var(name).unwrap_or_default())),
+    ///        |_obj_id, name, val| {
+    ///            std::env::set_var(name, val.get_string());
+    ///            Ok(())
+    ///        },
+    ///        |_obj_id, name| Ok(std::env::var(name).is_ok()),
+    ///        |_obj_id, name| {
+    ///            std::env::remove_var(name);
+    ///            Ok(true)
+    ///        },
+    ///        |_obj_id| Ok(std::env::vars().map(|(key, _val)| key).collect()),
+    ///    )
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let env = new my.biz.Env(); env.GREETING = 'hello'; \
+    /// console.log('%s %s', env.GREETING, Object.keys(env).length); delete env.GREETING;", "test_dynamic_properties.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn dynamic_properties<G, S, H, D, K>(
+        &mut self,
+        getter: G,
+        setter: S,
+        has: H,
+        delete: D,
+        keys: K,
+    ) -> &mut Self
+    where
+        G: Fn(&i32, &str) -> Result<EsValueFacade, String> + Send + 'static,
+        S: Fn(&i32, &str, EsValueFacade) -> Result<(), String> + Send + 'static,
+        H: Fn(&i32, &str) -> Result<bool, String> + Send + 'static,
+        D: Fn(&i32, &str) -> Result<bool, String> + Send + 'static,
+        K: Fn(&i32) -> Result<Vec<String>, String> + Send + 'static,
+    {
+        self.dynamic_properties = Some(EsDynamicProperties {
+            getter: Box::new(getter),
+            setter: Box::new(setter),
+            has: Box::new(has),
+            delete: Box::new(delete),
+            keys: Box::new(keys),
+        });
+        self
+    }
+
+    /// make the proxy class extend another proxy class, the parent class should be built first
+    ///
+    /// instances of the class inherit the methods, properties and events of the parent class and
+    /// the class inherits its static members, if the class has no constructor the constructor of
+    /// the parent class is used
+    /// please note that the methods of the parent class are called with the obj_id of the instance of the subclass
+    ///
+    /// proxy classes can also be extended from script like any other class
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let _animal = EsProxyBuilder::new(vec!["my", "biz"], "Animal")
+    ///    .constructor(|_args| Ok(1))
+    ///    .method("eat", |_obj_id, _args| Ok(EsValueFacade::undefined()))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    ///let _dog = EsProxyBuilder::new(vec!["my", "biz"], "Dog")
+    ///    .extends("my.biz.Animal")
+    ///    .method("bark", |_obj_id, _args| Ok(EsValueFacade::undefined()))
+    ///    .build(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("let dog = new my.biz.Dog(); dog.eat(); dog.bark(); \
+    ///               class Puppy extends my.biz.Dog {play(){}}; \
+    ///               let puppy = new Puppy(); puppy.play(); puppy.eat(); \
+    ///               puppy instanceof my.biz.Animal;", "test_extends.es")
+    /// .ok().expect("script failed");
+    /// ```
+    ///
+    pub fn extends(&mut self, canonical_name: &str) -> &mut Self {
+        self.extends = Some(canonical_name.to_string());
+        self
+    }
+
     /// build the EsProxy this adds the proxy class to the runtime and return an EsProxy object
-    pub fn build(&mut self, rt: &EsRuntime) -> EsProxy {
+    /// this fails if the class extends a class which has not been built
+    pub fn build(&mut self, rt: &EsRuntime) -> Result<EsProxy, String> {
+        self.build_proxy(rt, false)
+    }
+
+    /// build a singleton object instead of a class, e.g. for an API object like my.biz.MyApp
+    ///
+    /// the object is a plain object in the namespace which can not be constructed, its members
+    /// are the static methods, properties, constants and events of the builder
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esreflection::EsProxyBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    ///let rt = EsRuntimeBuilder::default().build();
+    ///let my_app = EsProxyBuilder::new(vec!["my", "biz"], "MyApp")
+    ///    .static_readonly_property("version", || Ok(EsValueFacade::new_str("1.0".to_string())))
+    ///    .static_method("quit", |_args| {
+    ///        println!("quitting");
+    ///        Ok(EsValueFacade::undefined())
+    ///    })
+    ///    .static_event("started")
+    ///    .build_singleton(&rt).ok().expect("could not build proxy");
+    /// rt.eval_sync("console.log('%s %s', my.biz.MyApp, my.biz.MyApp.version); \
+    ///     my.biz.MyApp.addEventListener('started', () => {my.biz.MyApp.quit();});", "test_singleton.es")
+    /// .ok().expect("script failed");
+    /// my_app.dispatch_static_event(&rt, "started", EsValueFacade::undefined());
+    /// ```
+    ///
+    pub fn build_singleton(&mut self, rt: &EsRuntime) -> Result<EsProxy, String> {
+        if !(self.constructor.is_none()
+            && self.extends.is_none()
+            && self.methods.is_empty()
+            && self.properties.is_empty()
+            && self.events.is_empty()
+            && self.constants.is_empty()
+            && self.iterator.is_none()
+            && self.indexed.is_none()
+            && self.dynamic_properties.is_none())
+        {
+            return Err(format!(
+                "singleton {} can only have static members",
+                self.get_canonical_name()
+            ));
+        }
+        self.build_proxy(rt, true)
+    }
+
+    fn build_proxy(&mut self, rt: &EsRuntime, singleton: bool) -> Result<EsProxy, String> {
         let cn = self.class_name;
         let ns = self.namespace.clone();
+        let extends_opt = self.extends.clone();
         let constructor_opt = unsafe { replace(&mut self.constructor, None) };
         let finalizer_opt = unsafe { replace(&mut self.finalizer, None) };
         let mut methods = HashMap::new();
@@ -510,6 +1420,11 @@ impl EsProxyBuilder {
         });
 
         let events = self.events.clone();
+        let constants: Vec<(&'static str, EsValueFacade)> = self.constants.drain().collect();
+        let iterator_opt = self.iterator.take();
+        let indexed_opt = self.indexed.take();
+        let index_setter_opt = self.index_setter.take();
+        let dynamic_properties_opt = self.dynamic_properties.take();
 
         // static
         let mut static_methods = HashMap::new();
@@ -526,12 +1441,27 @@ impl EsProxyBuilder {
         });
 
         let static_events = self.static_events.clone();
+        let static_constants: Vec<(&'static str, EsValueFacade)> =
+            self.static_constants.drain().collect();
         // / static
 
-        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+        let doc_opt = self.doc.take();
+        let member_docs: Vec<(String, String)> = self.member_docs.drain().collect();
+
+        let build_res = rt.do_in_es_runtime_thread_sync(move |sm_rt| {
             sm_rt.do_with_jsapi(move |_rt, cx, global| {
                 let mut builder = ProxyBuilder::new(ns, cn);
 
+                if let Some(parent_name) = extends_opt {
+                    builder.extends(parent_name.as_str());
+                }
+                if let Some(doc) = doc_opt {
+                    builder.doc(doc.as_str());
+                }
+                for (name, doc) in member_docs {
+                    builder.member_doc(name.as_str(), doc.as_str());
+                }
+
                 if let Some(c) = constructor_opt {
                     builder.constructor(move |_cx: *mut mozjs::jsapi::JSContext, args| {
                         crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
@@ -582,39 +1512,133 @@ impl EsProxyBuilder {
                 properties.drain().all(|method_entry| {
                     let es_prop_name = method_entry.0;
 
-                    let (es_getter, es_setter) = method_entry.1;
-                    builder.property(
-                        es_prop_name,
-                        move |_cx, obj_id, mut rval| {
+                    let (es_getter_opt, es_setter_opt, attributes) = method_entry.1;
+                    let getter_opt = es_getter_opt.map(|es_getter| -> Getter {
+                        Box::new(
+                            move |_cx: *mut JSContext,
+                                  obj_id: i32,
+                                  mut rval: MutableHandleValue| {
+                                crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                                    let sm_rt = &*sm_rt_rc.borrow();
+                                    sm_rt.do_with_jsapi(|_rt, cx, _global| {
+                                        let res = es_getter(&obj_id);
+                                        match res {
+                                            Ok(esvf) => {
+                                                rval.set(esvf.to_es_value(cx));
+                                                Ok(())
+                                            }
+                                            Err(err_str) => Err(err_str),
+                                        }
+                                    })
+                                })
+                            },
+                        )
+                    });
+                    let setter_opt = es_setter_opt.map(|es_setter| -> Setter {
+                        Box::new(move |_cx: *mut JSContext, obj_id: i32, val: HandleValue| {
                             crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
                                 let sm_rt = &*sm_rt_rc.borrow();
-                                sm_rt.do_with_jsapi(|_rt, cx, _global| {
-                                    let res = es_getter(&obj_id);
-                                    match res {
-                                        Ok(esvf) => {
+                                sm_rt.do_with_jsapi(|rt, cx, global| {
+                                    let es_val = EsValueFacade::new_v(rt, cx, global, val);
+                                    es_setter(&obj_id, es_val)
+                                })
+                            })
+                        })
+                    });
+                    builder.accessor(es_prop_name, getter_opt, setter_opt, attributes);
+                    true
+                });
+
+                for (name, value) in constants {
+                    builder.constant(name, move |cx, mut rval| {
+                        rval.set(value.to_es_value(cx));
+                    });
+                }
+
+                for evt in events {
+                    builder.event(evt);
+                }
+
+                if let Some(es_iterator) = iterator_opt {
+                    builder.iterator(move |_cx, obj_id| {
+                        let mut iter = es_iterator(&obj_id)?;
+                        let next: IteratorNext =
+                            Box::new(move |_cx: *mut JSContext, mut rval: MutableHandleValue| {
+                                crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                                    let sm_rt = &*sm_rt_rc.borrow();
+                                    sm_rt.do_with_jsapi(|_rt, cx, _global| match iter.next() {
+                                        Some(esvf) => {
                                             rval.set(esvf.to_es_value(cx));
-                                            Ok(())
+                                            Ok(true)
                                         }
-                                        Err(err_str) => Err(err_str),
-                                    }
+                                        None => Ok(false),
+                                    })
+                                })
+                            });
+                        Ok(next)
+                    });
+                }
+
+                if let Some((es_length, es_getter)) = indexed_opt {
+                    builder.indexed(
+                        move |_cx, obj_id| es_length(&obj_id),
+                        move |_cx, obj_id, index, mut rval| {
+                            crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                                let sm_rt = &*sm_rt_rc.borrow();
+                                sm_rt.do_with_jsapi(|_rt, cx, _global| {
+                                    let esvf = es_getter(&obj_id, index)?;
+                                    rval.set(esvf.to_es_value(cx));
+                                    Ok(())
+                                })
+                            })
+                        },
+                    );
+                }
+
+                if let Some(es_dynamic_properties) = dynamic_properties_opt {
+                    let EsDynamicProperties {
+                        getter: es_getter,
+                        setter: es_setter,
+                        has: es_has,
+                        delete: es_delete,
+                        keys: es_keys,
+                    } = es_dynamic_properties;
+                    builder.dynamic_properties(
+                        move |_cx, obj_id, name, mut rval| {
+                            crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                                let sm_rt = &*sm_rt_rc.borrow();
+                                sm_rt.do_with_jsapi(|_rt, cx, _global| {
+                                    let esvf = es_getter(&obj_id, name)?;
+                                    rval.set(esvf.to_es_value(cx));
+                                    Ok(())
                                 })
                             })
                         },
-                        move |_cx, obj_id, val| {
+                        move |_cx, obj_id, name, val| {
                             crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
                                 let sm_rt = &*sm_rt_rc.borrow();
                                 sm_rt.do_with_jsapi(|rt, cx, global| {
                                     let es_val = EsValueFacade::new_v(rt, cx, global, val);
-                                    es_setter(&obj_id, es_val)
+                                    es_setter(&obj_id, name, es_val)
                                 })
                             })
                         },
+                        move |_cx, obj_id, name| es_has(&obj_id, name),
+                        move |_cx, obj_id, name| es_delete(&obj_id, name),
+                        move |_cx, obj_id| es_keys(&obj_id),
                     );
-                    true
-                });
+                }
 
-                for evt in events {
-                    builder.event(evt);
+                if let Some(es_setter) = index_setter_opt {
+                    builder.indexed_setter(move |_cx, obj_id, index, val| {
+                        crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                            let sm_rt = &*sm_rt_rc.borrow();
+                            sm_rt.do_with_jsapi(|rt, cx, global| {
+                                let es_val = EsValueFacade::new_v(rt, cx, global, val);
+                                es_setter(&obj_id, index, es_val)
+                            })
+                        })
+                    });
                 }
 
                 static_methods.drain().all(|method_entry| {
@@ -648,10 +1672,9 @@ impl EsProxyBuilder {
                 static_properties.drain().all(|method_entry| {
                     let es_prop_name = method_entry.0;
 
-                    let (es_getter, es_setter) = method_entry.1;
-                    builder.static_property(
-                        es_prop_name,
-                        move |_cx, mut rval| {
+                    let (es_getter_opt, es_setter_opt, attributes) = method_entry.1;
+                    let getter_opt = es_getter_opt.map(|es_getter| -> StaticGetter {
+                        Box::new(move |_cx: *mut JSContext, mut rval: MutableHandleValue| {
                             crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
                                 let sm_rt = &*sm_rt_rc.borrow();
                                 sm_rt.do_with_jsapi(|_rt, cx, _global| {
@@ -665,8 +1688,10 @@ impl EsProxyBuilder {
                                     }
                                 })
                             })
-                        },
-                        move |_cx, val| {
+                        })
+                    });
+                    let setter_opt = es_setter_opt.map(|es_setter| -> StaticSetter {
+                        Box::new(move |_cx: *mut JSContext, val: HandleValue| {
                             crate::spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
                                 let sm_rt = &*sm_rt_rc.borrow();
                                 sm_rt.do_with_jsapi(|rt, cx, global| {
@@ -674,22 +1699,34 @@ impl EsProxyBuilder {
                                     es_setter(es_val)
                                 })
                             })
-                        },
-                    );
+                        })
+                    });
+                    builder.static_accessor(es_prop_name, getter_opt, setter_opt, attributes);
                     true
                 });
 
+                for (name, value) in static_constants {
+                    builder.static_constant(name, move |cx, mut rval| {
+                        rval.set(value.to_es_value(cx));
+                    });
+                }
+
                 for evt in static_events {
                     builder.static_event(evt);
                 }
 
-                let _proxy = builder.build(cx, global);
-            });
+                let proxy_res = if singleton {
+                    builder.build_singleton(cx, global)
+                } else {
+                    builder.build(cx, global)
+                };
+                proxy_res.map(|_proxy| ())
+            })
         });
-        EsProxy {
+        build_res.map(|_| EsProxy {
             namespace: self.namespace.clone(),
             class_name: self.class_name,
-        }
+        })
     }
 
     /// get the canonical name of the proxy class, this includes the namespace
@@ -698,3 +1735,1430 @@ impl EsProxyBuilder {
         format!("{}.{}", self.namespace.join("."), self.class_name)
     }
 }
+
+fn get_registered_proxy(canonical_name: &str) -> Result<Arc<Proxy>, String> {
+    get_proxy(canonical_name).ok_or_else(|| format!("class {} is not registered", canonical_name))
+}
+
+type InstanceMap<T> = AutoIdMap<Rc<RefCell<T>>>;
+
+thread_local! {
+    // the state of the instances of typed proxies by TypeId, the state is only used from the
+    // worker thread of the runtime it was created in so it need not be Send
+    static INSTANCES: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+    // the type of the state of the typed proxies by canonical name
+    static TYPED_PROXIES: RefCell<HashMap<String, TypeId>> = RefCell::new(HashMap::new());
+}
+
+fn with_instance_map<T: 'static, R, F: FnOnce(&mut InstanceMap<T>) -> R>(consumer: F) -> R {
+    INSTANCES.with(|instances_rc| {
+        let instances = &mut *instances_rc.borrow_mut();
+        let map = instances
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(InstanceMap::<T>::new()));
+        consumer(map.downcast_mut::<InstanceMap<T>>().unwrap())
+    })
+}
+
+fn insert_instance<T: 'static>(instance: T) -> i32 {
+    with_instance_map(|map: &mut InstanceMap<T>| map.insert(Rc::new(RefCell::new(instance))) as i32)
+}
+
+/// use the state of an instance, this fails if the instance is already in use further up the stack
+/// (e.g. when a method calls a script function which calls a method of the same instance)
+fn with_instance<T: 'static, R, F: FnOnce(&mut T) -> R>(
+    obj_id: i32,
+    consumer: F,
+) -> Result<R, String> {
+    // clone the Rc so the map is not borrowed while the consumer runs
+    let instance_rc =
+        with_instance_map(|map: &mut InstanceMap<T>| map.get(&(obj_id as usize)).cloned())
+            .ok_or_else(|| format!("no instance found with id {}", obj_id))?;
+    let mut instance = instance_rc
+        .try_borrow_mut()
+        .map_err(|_e| format!("instance {} is already in use", obj_id))?;
+    Ok(consumer(&mut *instance))
+}
+
+fn remove_instance<T: 'static>(obj_id: i32) -> Option<T> {
+    let id = obj_id as usize;
+    let instance_rc = with_instance_map(|map: &mut InstanceMap<T>| {
+        if map.contains_key(&id) {
+            Some(map.remove(&id))
+        } else {
+            None
+        }
+    })?;
+    Rc::try_unwrap(instance_rc)
+        .ok()
+        .map(|instance_cell| instance_cell.into_inner())
+}
+
+/// the EsTypedProxyBuilder creates a proxy class of which every instance owns a rust object of type T
+///
+/// the constructor creates the object, methods, getters and setters get a reference to it and it
+/// is dropped (or passed to the finalizer) when the instance is garbage collected
+///
+/// # Example
+///
+/// ```no_run
+/// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+/// use es_runtime::esreflection::EsTypedProxyBuilder;
+/// use es_runtime::esvaluefacade::EsValueFacade;
+///
+/// struct Counter {
+///     count: i32,
+/// }
+///
+/// let rt = EsRuntimeBuilder::default().build();
+/// let es_proxy = EsTypedProxyBuilder::new(vec!["my", "biz"], "Counter")
+///     .constructor(|_args| Ok(Counter { count: 0 }))
+///     .method("increment", |counter: &mut Counter, _args| {
+///         counter.count += 1;
+///         Ok(EsValueFacade::new_i32(counter.count))
+///     })
+///     .property("count", |counter| Ok(EsValueFacade::new_i32(counter.count)), |counter, val| {
+///         counter.count = *val.get_i32();
+///         Ok(())
+///     })
+///     .finalizer(|counter| {
+///         println!("counter was garbage collected at {}", counter.count);
+///     })
+///     .build(&rt).ok().expect("could not build proxy");
+/// rt.eval_sync("let c = new my.biz.Counter(); c.increment(); c.count = 5; c.increment();", "test_typed_proxy.es")
+///     .ok().expect("script failed");
+/// ```
+pub struct EsTypedProxyBuilder<T: 'static> {
+    builder: EsProxyBuilder,
+    finalizer: Option<Box<EsTypedProxyFinalizer<T>>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> EsTypedProxyBuilder<T> {
+    /// create a new EsTypedProxyBuilder
+    /// you can pass a namespace as a Vec and a classname as str
+    pub fn new(namespace: Vec<&'static str>, class_name: &'static str) -> Self {
+        EsTypedProxyBuilder {
+            builder: EsProxyBuilder::new(namespace, class_name),
+            finalizer: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// the constructor creates the rust object for a new instance of the class
+    /// if you do not define a constructor your proxy will not be constructable
+    pub fn constructor<C>(&mut self, constructor: C) -> &mut Self
+    where
+        C: Fn(Vec<EsValueFacade>) -> Result<T, String> + Send + 'static,
+    {
+        self.builder
+            .constructor(move |args| constructor(args).map(insert_instance));
+        self
+    }
+
+    /// the finalizer receives the rust object of an instance when it is garbage collected
+    /// if you don't set a finalizer the object is just dropped
+    pub fn finalizer<F>(&mut self, finalizer: F) -> &mut Self
+    where
+        F: Fn(T) + Send + 'static,
+    {
+        self.finalizer = Some(Box::new(finalizer));
+        self
+    }
+
+    /// add a method to the proxy class, the method gets the rust object of the instance it was called on
+    pub fn method<M>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(&mut T, Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.method(name, move |obj_id, args| {
+            with_instance(*obj_id, |instance: &mut T| method(instance, args))?
+        });
+        self
+    }
+
+    /// add an async method to the proxy class which returns a Promise to script
+    ///
+    /// the method gets the rust object of the instance and returns a job, the job is run in the
+    /// helper thread pool and the Promise is resolved with its result
+    /// the rust object itself is not Send so copy what the job needs from it
+    pub fn async_method<M, J>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(&mut T, Vec<EsValueFacade>) -> Result<J, String> + Send + 'static,
+        J: FnOnce() -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.method(name, move |obj_id, args| {
+            let job = with_instance(*obj_id, |instance: &mut T| method(instance, args))??;
+            Ok(EsValueFacade::new_promise(job))
+        });
+        self
+    }
+
+    /// add a property to the proxy class, the getter and setter get the rust object of the instance
+    pub fn property<G, S>(&mut self, name: &'static str, getter: G, setter: S) -> &mut Self
+    where
+        G: Fn(&T) -> Result<EsValueFacade, String> + Send + 'static,
+        S: Fn(&mut T, EsValueFacade) -> Result<(), String> + Send + 'static,
+    {
+        self.builder.property(
+            name,
+            move |obj_id| with_instance(*obj_id, |instance: &mut T| getter(instance))?,
+            move |obj_id, val| with_instance(*obj_id, |instance: &mut T| setter(instance, val))?,
+        );
+        self
+    }
+
+    /// add a read only property to the proxy class, see EsProxyBuilder::readonly_property
+    pub fn readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn(&T) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.readonly_property(name, move |obj_id| {
+            with_instance(*obj_id, |instance: &mut T| getter(instance))?
+        });
+        self
+    }
+
+    /// add a property with an optional getter and an optional setter, see EsProxyBuilder::accessor
+    pub fn accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<Box<EsTypedProxyGetter<T>>>,
+        setter: Option<Box<EsTypedProxySetter<T>>>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
+        let getter_opt = getter.map(|getter| -> Box<EsProxyGetter> {
+            Box::new(move |obj_id: &i32| {
+                with_instance(*obj_id, |instance: &mut T| getter(instance))?
+            })
+        });
+        let setter_opt = setter.map(|setter| -> Box<EsProxySetter> {
+            Box::new(move |obj_id: &i32, val: EsValueFacade| {
+                with_instance(*obj_id, |instance: &mut T| setter(instance, val))?
+            })
+        });
+        self.builder
+            .accessor(name, getter_opt, setter_opt, attributes);
+        self
+    }
+
+    /// add a constant to the proxy class, see EsProxyBuilder::constant
+    pub fn constant(&mut self, name: &'static str, value: EsValueFacade) -> &mut Self {
+        self.builder.constant(name, value);
+        self
+    }
+
+    /// make instances of the proxy class iterable, see EsProxyBuilder::iterator
+    /// the iterator can not borrow the rust object so it should own or copy the values it produces
+    pub fn iterator<F, I>(&mut self, iterator: F) -> &mut Self
+    where
+        F: Fn(&T) -> Result<I, String> + Send + 'static,
+        I: Iterator<Item = EsValueFacade> + 'static,
+    {
+        self.builder
+            .iterator(move |obj_id| with_instance(*obj_id, |instance: &mut T| iterator(instance))?);
+        self
+    }
+
+    /// make instances of the proxy class indexable, see EsProxyBuilder::indexed
+    pub fn indexed<L, G>(&mut self, length: L, getter: G) -> &mut Self
+    where
+        L: Fn(&T) -> Result<u32, String> + Send + 'static,
+        G: Fn(&T, u32) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.indexed(
+            move |obj_id| with_instance(*obj_id, |instance: &mut T| length(instance))?,
+            move |obj_id, index| {
+                with_instance(*obj_id, |instance: &mut T| getter(instance, index))?
+            },
+        );
+        self
+    }
+
+    /// make the indices of an indexed proxy class writable, see EsProxyBuilder::indexed_setter
+    pub fn indexed_setter<S>(&mut self, setter: S) -> &mut Self
+    where
+        S: Fn(&mut T, u32, EsValueFacade) -> Result<(), String> + Send + 'static,
+    {
+        self.builder.indexed_setter(move |obj_id, index, val| {
+            with_instance(*obj_id, |instance: &mut T| setter(instance, index, val))?
+        });
+        self
+    }
+
+    /// give the proxy class an open ended set of properties, see EsProxyBuilder::dynamic_properties
+    pub fn dynamic_properties<G, S, H, D, K>(
+        &mut self,
+        getter: G,
+        setter: S,
+        has: H,
+        delete: D,
+        keys: K,
+    ) -> &mut Self
+    where
+        G: Fn(&T, &str) -> Result<EsValueFacade, String> + Send + 'static,
+        S: Fn(&mut T, &str, EsValueFacade) -> Result<(), String> + Send + 'static,
+        H: Fn(&T, &str) -> Result<bool, String> + Send + 'static,
+        D: Fn(&mut T, &str) -> Result<bool, String> + Send + 'static,
+        K: Fn(&T) -> Result<Vec<String>, String> + Send + 'static,
+    {
+        self.builder.dynamic_properties(
+            move |obj_id, name| with_instance(*obj_id, |instance: &mut T| getter(instance, name))?,
+            move |obj_id, name, val| {
+                with_instance(*obj_id, |instance: &mut T| setter(instance, name, val))?
+            },
+            move |obj_id, name| with_instance(*obj_id, |instance: &mut T| has(instance, name))?,
+            move |obj_id, name| with_instance(*obj_id, |instance: &mut T| delete(instance, name))?,
+            move |obj_id| with_instance(*obj_id, |instance: &mut T| keys(instance))?,
+        );
+        self
+    }
+
+    /// define an event type to the proxy class, see EsProxyBuilder::event
+    pub fn event(&mut self, event_type: &'static str) -> &mut Self {
+        self.builder.event(event_type);
+        self
+    }
+
+    /// define a static event type to the proxy class, see EsProxyBuilder::static_event
+    pub fn static_event(&mut self, event_type: &'static str) -> &mut Self {
+        self.builder.static_event(event_type);
+        self
+    }
+
+    /// set the documentation of the proxy class, see EsProxyBuilder::doc
+    pub fn doc(&mut self, doc: &str) -> &mut Self {
+        self.builder.doc(doc);
+        self
+    }
+
+    /// set the documentation of a member of the proxy class, see EsProxyBuilder::member_doc
+    pub fn member_doc(&mut self, name: &str, doc: &str) -> &mut Self {
+        self.builder.member_doc(name, doc);
+        self
+    }
+
+    /// add a static property to the proxy class, see EsProxyBuilder::static_property
+    pub fn static_property<G, S>(&mut self, name: &'static str, getter: G, setter: S) -> &mut Self
+    where
+        G: Fn() -> Result<EsValueFacade, String> + Send + 'static,
+        S: Fn(EsValueFacade) -> Result<(), String> + Send + 'static,
+    {
+        self.builder.static_property(name, getter, setter);
+        self
+    }
+
+    /// add a read only static property to the proxy class, see EsProxyBuilder::static_readonly_property
+    pub fn static_readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn() -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.static_readonly_property(name, getter);
+        self
+    }
+
+    /// add a static property with an optional getter and an optional setter, see EsProxyBuilder::static_accessor
+    pub fn static_accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<Box<EsProxyStaticGetter>>,
+        setter: Option<Box<EsProxyStaticSetter>>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
+        self.builder
+            .static_accessor(name, getter, setter, attributes);
+        self
+    }
+
+    /// add a static constant to the proxy class, see EsProxyBuilder::static_constant
+    pub fn static_constant(&mut self, name: &'static str, value: EsValueFacade) -> &mut Self {
+        self.builder.static_constant(name, value);
+        self
+    }
+
+    /// add a static method to the proxy class, see EsProxyBuilder::static_method
+    pub fn static_method<M>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        self.builder.static_method(name, method);
+        self
+    }
+
+    /// add a static async method to the proxy class, see EsProxyBuilder::static_async_method
+    pub fn static_async_method<M>(&mut self, name: &'static str, method: M) -> &mut Self
+    where
+        M: Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static,
+    {
+        self.builder.static_async_method(name, method);
+        self
+    }
+
+    /// build the EsProxy this adds the proxy class to the runtime and return an EsProxy object
+    /// this fails if the class extends a class which has not been built
+    pub fn build(&mut self, rt: &EsRuntime) -> Result<EsProxy, String> {
+        let finalizer_opt = self.finalizer.take();
+        self.builder.finalizer(move |obj_id| {
+            if let Some(instance) = remove_instance::<T>(obj_id) {
+                if let Some(finalizer) = finalizer_opt.as_ref() {
+                    finalizer(instance);
+                }
+            }
+        });
+        let es_proxy = self.builder.build(rt)?;
+        let canonical_name = es_proxy.get_canonical_name();
+        rt.do_in_es_runtime_thread_sync(move |_sm_rt| {
+            TYPED_PROXIES.with(|typed_proxies_rc| {
+                let typed_proxies = &mut *typed_proxies_rc.borrow_mut();
+                typed_proxies.insert(canonical_name, TypeId::of::<T>());
+            });
+        });
+        Ok(es_proxy)
+    }
+}
+
+/// a reference to an instance of a proxy class which was passed from script to rust
+///
+/// the instance is kept alive for as long as the reference lives and when it is passed back to
+/// script as part of an EsValueFacade the original instance is used
+///
+/// # Example
+///
+/// ```no_run
+/// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+/// use es_runtime::esreflection::EsProxyBuilder;
+/// use es_runtime::esvaluefacade::EsValueFacade;
+///
+/// let rt = EsRuntimeBuilder::default().build();
+/// EsProxyBuilder::new(vec!["my", "biz"], "MyClass")
+///     .constructor(|_args| Ok(12))
+///     .build(&rt).ok().expect("could not build proxy");
+/// rt.add_global_sync_function("inspect", |args| {
+///     let instance = args[0].get_proxy_instance();
+///     Ok(EsValueFacade::new_str(format!("{}#{}", instance.get_canonical_name(), instance.get_obj_id())))
+/// });
+/// let esvf = rt.eval_sync("inspect(new my.biz.MyClass());", "test_proxy_instance.es").ok().expect("script failed");
+/// assert_eq!(esvf.get_string(), "my.biz.MyClass#12");
+/// ```
+pub struct EsProxyInstanceRef {
+    canonical_name: String,
+    obj_id: i32,
+    obj_ref: EsObjectRef,
+}
+
+impl EsProxyInstanceRef {
+    /// create a reference to a proxy instance, this may only be called from the worker thread of an EsRuntime
+    pub(crate) fn new(cx: *mut JSContext, obj: *mut JSObject) -> Self {
+        let canonical_name = get_proxy_for(cx, obj)
+            .map(|proxy| proxy.get_canonical_name())
+            .unwrap_or_default();
+        let obj_id = get_obj_id_for(cx, obj);
+        EsProxyInstanceRef {
+            canonical_name,
+            obj_id,
+            obj_ref: EsObjectRef::new(cx, obj),
+        }
+    }
+
+    /// get the canonical name of the class of the instance, e.g. "my.biz.MyClass"
+    pub fn get_canonical_name(&self) -> &str {
+        self.canonical_name.as_str()
+    }
+
+    /// get the id of the instance, this is the id which was returned by the constructor of the class
+    pub fn get_obj_id(&self) -> i32 {
+        self.obj_id
+    }
+
+    /// get a live reference to the instance object
+    pub fn get_object_ref(&self) -> &EsObjectRef {
+        &self.obj_ref
+    }
+
+    /// use the state of an instance of a class created with an EsTypedProxyBuilder
+    /// this may only be called from the worker thread of the EsRuntime, e.g. in a rust function
+    /// which was called from script, and fails if the class does not have state of type T
+    pub fn with_state<T: 'static, R, F: FnOnce(&mut T) -> R>(
+        &self,
+        consumer: F,
+    ) -> Result<R, String> {
+        let type_id = TYPED_PROXIES.with(|typed_proxies_rc| {
+            let typed_proxies = &*typed_proxies_rc.borrow();
+            typed_proxies.get(self.canonical_name.as_str()).cloned()
+        });
+        if type_id != Some(TypeId::of::<T>()) {
+            return Err(format!(
+                "instance of {} does not have state of the requested type",
+                self.canonical_name
+            ));
+        }
+        with_instance(self.obj_id, consumer)
+    }
+}
+
+impl ToEsValue for MemberDescription {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_obj(vec![
+            ("name".to_string(), self.name.to_es_value_facade()),
+            ("doc".to_string(), self.doc.to_es_value_facade()),
+        ])
+    }
+}
+
+impl ToEsValue for PropertyDescription {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        EsValueFacade::new_obj(vec![
+            ("name".to_string(), self.name.to_es_value_facade()),
+            ("doc".to_string(), self.doc.to_es_value_facade()),
+            ("readable".to_string(), self.readable.to_es_value_facade()),
+            ("writable".to_string(), self.writable.to_es_value_facade()),
+            (
+                "enumerable".to_string(),
+                self.enumerable.to_es_value_facade(),
+            ),
+        ])
+    }
+}
+
+impl ToEsValue for ProxyDescription {
+    fn to_es_value_facade(self) -> EsValueFacade {
+        let canonical_name = self.get_canonical_name();
+        EsValueFacade::new_obj(vec![
+            (
+                "canonicalName".to_string(),
+                canonical_name.to_es_value_facade(),
+            ),
+            ("namespace".to_string(), self.namespace.to_es_value_facade()),
+            (
+                "className".to_string(),
+                self.class_name.to_es_value_facade(),
+            ),
+            ("doc".to_string(), self.doc.to_es_value_facade()),
+            ("extends".to_string(), self.extends.to_es_value_facade()),
+            ("singleton".to_string(), self.singleton.to_es_value_facade()),
+            (
+                "constructable".to_string(),
+                self.constructable.to_es_value_facade(),
+            ),
+            ("methods".to_string(), self.methods.to_es_value_facade()),
+            (
+                "properties".to_string(),
+                self.properties.to_es_value_facade(),
+            ),
+            ("constants".to_string(), self.constants.to_es_value_facade()),
+            ("events".to_string(), self.events.to_es_value_facade()),
+            (
+                "staticMethods".to_string(),
+                self.static_methods.to_es_value_facade(),
+            ),
+            (
+                "staticProperties".to_string(),
+                self.static_properties.to_es_value_facade(),
+            ),
+            (
+                "staticConstants".to_string(),
+                self.static_constants.to_es_value_facade(),
+            ),
+            (
+                "staticEvents".to_string(),
+                self.static_events.to_es_value_facade(),
+            ),
+            ("iterable".to_string(), self.iterable.to_es_value_facade()),
+            ("indexed".to_string(), self.indexed.to_es_value_facade()),
+            (
+                "indexWritable".to_string(),
+                self.index_writable.to_es_value_facade(),
+            ),
+            (
+                "dynamicProperties".to_string(),
+                self.dynamic_properties.to_es_value_facade(),
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::esreflection::{
+        insert_instance, remove_instance, with_instance, EsProxy, EsProxyBuilder,
+        EsTypedProxyBuilder, EventListenerOptions, PropertyAttributes,
+    };
+    use crate::esruntime::tests::TEST_RT;
+    use crate::esvaluefacade::EsValueFacade;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct TestCounter {
+        count: i32,
+    }
+
+    #[test]
+    fn test_typed_proxy() {
+        log::info!("test: test_typed_proxy");
+        let rt = TEST_RT.clone();
+        let finalized = Arc::new(Mutex::new(vec![]));
+        let finalized2 = finalized.clone();
+        EsTypedProxyBuilder::new(vec!["test", "typed"], "Counter")
+            .constructor(|args| {
+                let start = if !args.is_empty() && args[0].is_i32() {
+                    *args[0].get_i32()
+                } else {
+                    0
+                };
+                Ok(TestCounter { count: start })
+            })
+            .method("increment", |counter: &mut TestCounter, _args| {
+                counter.count += 1;
+                Ok(EsValueFacade::new_i32(counter.count))
+            })
+            .property(
+                "count",
+                |counter| Ok(EsValueFacade::new_i32(counter.count)),
+                |counter, val| {
+                    counter.count = *val.get_i32();
+                    Ok(())
+                },
+            )
+            .finalizer(move |counter| {
+                finalized2.lock().unwrap().push(counter.count);
+            })
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "{let c1 = new test.typed.Counter(10); let c2 = new test.typed.Counter(); \
+                 c1.increment(); c2.increment(); c2.count = 7; c2.increment(); \
+                 c1.count + ',' + c2.count;}",
+                "test_typed_proxy.es",
+            )
+            .ok()
+            .expect("script failed");
+        assert_eq!(esvf.get_string(), "11,8");
+    }
+
+    #[test]
+    fn test_typed_proxy_remove_instance() {
+        log::info!("test: test_typed_proxy_remove_instance");
+        let rt = TEST_RT.clone();
+        // the finalizer of a typed proxy gets the state which remove_instance returns
+        let (count, in_use_res, removed_twice) = rt.do_in_es_runtime_thread_sync(|_sm_rt| {
+            let obj_id = insert_instance(TestCounter { count: 3 });
+            with_instance(obj_id, |counter: &mut TestCounter| counter.count += 1)
+                .ok()
+                .expect("instance not found");
+            let in_use_res = with_instance(obj_id, |_outer: &mut TestCounter| {
+                with_instance(obj_id, |_inner: &mut TestCounter| ())
+            })
+            .ok()
+            .expect("instance not found");
+            let counter = remove_instance::<TestCounter>(obj_id).expect("instance not removed");
+            let removed_twice = remove_instance::<TestCounter>(obj_id).is_some();
+            (counter.count, in_use_res, removed_twice)
+        });
+        assert_eq!(count, 4);
+        assert!(in_use_res.is_err());
+        assert!(!removed_twice);
+    }
+
+    #[test]
+    fn test_new_instance() {
+        log::info!("test: test_new_instance");
+        let rt = TEST_RT.clone();
+        let es_proxy = EsProxyBuilder::new(vec!["test", "newinstance"], "Connection")
+            .constructor(|args| {
+                if args.len() == 1 && args[0].is_string() {
+                    Ok(1)
+                } else {
+                    Err("Connection needs a url".to_string())
+                }
+            })
+            .method("query", |obj_id, _args| {
+                Ok(EsValueFacade::new_i32(*obj_id * 42))
+            })
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let conn = es_proxy
+            .new_instance(
+                &rt,
+                vec![EsValueFacade::new_str("db://localhost".to_string())],
+            )
+            .ok()
+            .expect("could not create instance");
+        assert!(conn.is_proxy_instance());
+        assert_eq!(
+            conn.get_proxy_instance().get_canonical_name(),
+            "test.newinstance.Connection"
+        );
+        assert_eq!(conn.get_proxy_instance().get_obj_id(), 1);
+        rt.eval_sync(
+            "this.test_new_instance_use = function(conn){return (typeof conn.query) + ',' + conn.query();};",
+            "test_new_instance.es",
+        )
+        .ok()
+        .expect("script failed");
+        let esvf = rt
+            .call_sync(vec![], "test_new_instance_use", vec![conn])
+            .ok()
+            .expect("call failed");
+        assert_eq!(esvf.get_string(), "function,42");
+
+        let res = es_proxy.new_instance(&rt, vec![]);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_proxy_instance_ref() {
+        log::info!("test: test_proxy_instance_ref");
+        let rt = TEST_RT.clone();
+        EsTypedProxyBuilder::new(vec!["test", "instanceref"], "Counter")
+            .constructor(|_args| Ok(TestCounter { count: 3 }))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+        EsProxyBuilder::new(vec!["test", "instanceref"], "Plain")
+            .constructor(|_args| Ok(5))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+        rt.add_global_sync_function("test_proxy_instance_ref_inspect", |args| {
+            let arg = &args[0];
+            if !arg.is_proxy_instance() {
+                return Ok(EsValueFacade::new_str("not an instance".to_string()));
+            }
+            let instance = arg.get_proxy_instance();
+            let count = instance
+                .with_state(|counter: &mut TestCounter| {
+                    counter.count += 1;
+                    counter.count
+                })
+                .unwrap_or(-1);
+            Ok(EsValueFacade::new_str(format!(
+                "{}#{}:{}",
+                instance.get_canonical_name(),
+                instance.get_obj_id(),
+                count
+            )))
+        });
+        let esvf = rt
+            .eval_sync(
+                "{let counter = new test.instanceref.Counter(); \
+                 test_proxy_instance_ref_inspect(counter); \
+                 [test_proxy_instance_ref_inspect(counter), \
+                 test_proxy_instance_ref_inspect(new test.instanceref.Plain()), \
+                 test_proxy_instance_ref_inspect(test.instanceref.Plain), \
+                 test_proxy_instance_ref_inspect({a: 1})].join(',').replace(/#\\d+:/, '#n:');}",
+                "test_proxy_instance_ref.es",
+            )
+            .ok()
+            .expect("script failed");
+        assert_eq!(
+            esvf.get_string(),
+            "test.instanceref.Counter#n:5,test.instanceref.Plain#5:-1,not an instance,not an instance"
+        );
+    }
+
+    #[test]
+    fn test_async_method() {
+        log::info!("test: test_async_method");
+        let rt = TEST_RT.clone();
+        EsProxyBuilder::new(vec!["test", "asyncmethod"], "Plain")
+            .constructor(|_args| Ok(7))
+            .async_method("load", |obj_id, args| {
+                Ok(EsValueFacade::new_i32(*obj_id * *args[0].get_i32()))
+            })
+            .static_async_method("fail", |_args| Err("static failed".to_string()))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+        EsTypedProxyBuilder::new(vec!["test", "asyncmethod"], "Counter")
+            .constructor(|_args| Ok(TestCounter { count: 10 }))
+            .async_method("add", |counter: &mut TestCounter, args| {
+                counter.count += *args[0].get_i32();
+                let count = counter.count;
+                Ok(move || Ok(EsValueFacade::new_i32(count)))
+            })
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "(async function(){\
+                 let plain = new test.asyncmethod.Plain(); \
+                 let counter = new test.asyncmethod.Counter(); \
+                 let p = plain.load(3); \
+                 let isPromise = p instanceof Promise; \
+                 let loaded = await p; \
+                 let added = await counter.add(5); \
+                 let failed = await test.asyncmethod.Plain.fail().catch((err) => err); \
+                 return [isPromise, loaded, added, failed].join(',');\
+                 })();",
+                "test_async_method.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .expect("promise timed out")
+            .ok()
+            .expect("promise was rejected");
+        assert_eq!(res.get_string(), "true,21,15,static failed");
+    }
+
+    #[test]
+    fn test_dispatch_event_sync() {
+        log::info!("test: test_dispatch_event_sync");
+        let rt = TEST_RT.clone();
+        let es_proxy = EsProxyBuilder::new(vec!["test", "eventsync"], "Door")
+            .constructor(|_args| Ok(33))
+            .event("closing")
+            .static_event("opened")
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        rt.eval_sync(
+            "{this.test_dispatch_event_sync_door = new test.eventsync.Door(); \
+             this.test_dispatch_event_sync_door.addEventListener('closing', (evt) => {return 'first';}); \
+             this.test_dispatch_event_sync_door.addEventListener('closing', (evt) => {return 'once';}, {once: true}); \
+             this.test_dispatch_event_sync_door.addEventListener('closing', (evt) => {if (evt.force !== true) {evt.preventDefault();}});}",
+            "test_dispatch_event_sync.es",
+        )
+        .ok()
+        .expect("script failed");
+
+        let res = es_proxy.dispatch_event_sync(&rt, 33, "closing", EsValueFacade::new_obj(vec![]));
+        assert!(res.default_prevented);
+        let results: Vec<String> = res
+            .results
+            .iter()
+            .filter(|esvf| esvf.is_string())
+            .map(|esvf| esvf.get_string().clone())
+            .collect();
+        assert_eq!(results, vec!["first", "once"]);
+
+        let res = es_proxy.dispatch_event_sync(
+            &rt,
+            33,
+            "closing",
+            EsValueFacade::new_obj(vec![("force".to_string(), EsValueFacade::new_bool(true))]),
+        );
+        assert!(!res.default_prevented);
+        assert_eq!(res.results.len(), 2);
+
+        // rust listeners are called for events dispatched from script
+        let listener_id = es_proxy
+            .add_event_listener(&rt, 33, "closing", EventListenerOptions::default(), |evt| {
+                if evt.is_object() && evt.get_object().contains_key("veto") {
+                    Ok(EsValueFacade::new_bool(false))
+                } else {
+                    Ok(EsValueFacade::new_str("rust".to_string()))
+                }
+            })
+            .ok()
+            .expect("could not add listener");
+        assert!(es_proxy
+            .add_event_listener(
+                &rt,
+                33,
+                "no_such_event",
+                EventListenerOptions::default(),
+                |_evt| { Ok(EsValueFacade::undefined()) }
+            )
+            .is_err());
+
+        let esvf = rt
+            .eval_sync(
+                "({allowed: this.test_dispatch_event_sync_door.dispatchEvent('closing', {force: true}), \
+                 vetoed: this.test_dispatch_event_sync_door.dispatchEvent('closing', {force: true, veto: 1})});",
+                "test_dispatch_event_sync2.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert!(res["allowed"].get_boolean());
+        assert!(!res["vetoed"].get_boolean());
+
+        es_proxy.remove_event_listener(&rt, 33, "closing", listener_id);
+        let res = es_proxy.dispatch_event_sync(
+            &rt,
+            33,
+            "closing",
+            EsValueFacade::new_obj(vec![("force".to_string(), EsValueFacade::new_bool(true))]),
+        );
+        assert_eq!(res.results.len(), 2);
+
+        // static events
+        let static_calls = Arc::new(Mutex::new(0));
+        let static_calls2 = static_calls.clone();
+        es_proxy
+            .add_static_event_listener(
+                &rt,
+                "opened",
+                EventListenerOptions { once: true },
+                move |_evt| {
+                    *static_calls2.lock().unwrap() += 1;
+                    Ok(EsValueFacade::new_i32(1))
+                },
+            )
+            .ok()
+            .expect("could not add static listener");
+        let res = es_proxy.dispatch_static_event_sync(&rt, "opened", EsValueFacade::undefined());
+        assert_eq!(res.results.len(), 1);
+        assert!(!res.default_prevented);
+        let res = es_proxy.dispatch_static_event_sync(&rt, "opened", EsValueFacade::undefined());
+        assert_eq!(res.results.len(), 0);
+        assert_eq!(*static_calls.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_extends() {
+        log::info!("test: test_extends");
+        let rt = TEST_RT.clone();
+        EsProxyBuilder::new(vec!["test", "extends"], "Animal")
+            .constructor(|args| {
+                if args.is_empty() {
+                    Ok(1)
+                } else {
+                    Ok(*args[0].get_i32())
+                }
+            })
+            .method("getId", |obj_id, _args| Ok(EsValueFacade::new_i32(*obj_id)))
+            .method("speak", |_obj_id, _args| {
+                Ok(EsValueFacade::new_str("...".to_string()))
+            })
+            .static_method("kingdom", |_args| {
+                Ok(EsValueFacade::new_str("animalia".to_string()))
+            })
+            .event("born")
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+        EsProxyBuilder::new(vec!["test", "extends"], "Dog")
+            .extends("test.extends.Animal")
+            .method("speak", |_obj_id, _args| {
+                Ok(EsValueFacade::new_str("woof".to_string()))
+            })
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        // an unknown parent is an error instead of a panic in the worker thread
+        let unknown_res = EsProxyBuilder::new(vec!["test", "extends"], "Fish")
+            .extends("test.extends.Unknown")
+            .build(&rt);
+        assert_eq!(
+            unknown_res.err().unwrap(),
+            "proxy Fish extends test.extends.Unknown which has not been built"
+        );
+        let esvf = rt
+            .eval_sync("typeof test.extends.Fish;", "test_extends_unknown.es")
+            .ok()
+            .expect("script failed");
+        assert_eq!(esvf.get_string(), "undefined");
+
+        // a class which is extended can not be replaced
+        let replace_res = EsProxyBuilder::new(vec!["test", "extends"], "Animal")
+            .constructor(|_args| Ok(2))
+            .build(&rt);
+        assert_eq!(
+            replace_res.err().unwrap(),
+            "could not replace test.extends.Animal: test.extends.Animal is extended by test.extends.Dog"
+        );
+
+        let esvf = rt
+            .eval_sync(
+                "{let dog = new test.extends.Dog(7); \
+                 dog.addEventListener('born', () => {}); \
+                 ({id: dog.getId(), speak: dog.speak(), kingdom: test.extends.Dog.kingdom(), \
+                 isDog: dog instanceof test.extends.Dog, isAnimal: dog instanceof test.extends.Animal, \
+                 animalIsDog: new test.extends.Animal() instanceof test.extends.Dog, \
+                 constructor: dog.constructor === test.extends.Dog});}",
+                "test_extends.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        // inherited from the parent class
+        assert_eq!(res["id"].get_i32(), &7);
+        // overridden by the subclass
+        assert_eq!(res["speak"].get_string(), "woof");
+        // static members of the parent are available on the subclass
+        assert_eq!(res["kingdom"].get_string(), "animalia");
+        assert!(res["isDog"].get_boolean());
+        assert!(res["isAnimal"].get_boolean());
+        assert!(!res["animalIsDog"].get_boolean());
+        assert!(res["constructor"].get_boolean());
+
+        let esvf = rt
+            .eval_sync(
+                "{class Cat extends test.extends.Animal { \
+                     constructor(id) {super(id * 2); this.lives = 9;} \
+                     speak() {return 'meow';} \
+                     purr() {return 'purr';} \
+                 }; \
+                 let cat = new Cat(5); \
+                 ({id: cat.getId(), speak: cat.speak(), purr: cat.purr(), lives: cat.lives, \
+                 kingdom: Cat.kingdom(), isCat: cat instanceof Cat, \
+                 isAnimal: cat instanceof test.extends.Animal});}",
+                "test_extends2.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        // the script class passes its own constructor args to the proxy constructor
+        assert_eq!(res["id"].get_i32(), &10);
+        assert_eq!(res["speak"].get_string(), "meow");
+        assert_eq!(res["purr"].get_string(), "purr");
+        assert_eq!(res["lives"].get_i32(), &9);
+        assert_eq!(res["kingdom"].get_string(), "animalia");
+        assert!(res["isCat"].get_boolean());
+        assert!(res["isAnimal"].get_boolean());
+    }
+
+    #[test]
+    fn test_property_attributes() {
+        log::info!("test: test_property_attributes");
+        let rt = TEST_RT.clone();
+        EsProxyBuilder::new(vec!["test", "attributes"], "Item")
+            .constructor(|_args| Ok(5))
+            .readonly_property("id", |obj_id| Ok(EsValueFacade::new_i32(*obj_id)))
+            .property(
+                "name",
+                |_obj_id| Ok(EsValueFacade::new_str("item".to_string())),
+                |_obj_id, _val| Ok(()),
+            )
+            .accessor(
+                "secret",
+                Some(Box::new(|_obj_id: &i32| {
+                    Ok(EsValueFacade::new_str("hidden".to_string()))
+                })),
+                None,
+                PropertyAttributes {
+                    enumerable: false,
+                    configurable: false,
+                },
+            )
+            .accessor(
+                "password",
+                None,
+                Some(Box::new(|_obj_id: &i32, _val: EsValueFacade| Ok(()))),
+                PropertyAttributes::default(),
+            )
+            .constant("KIND", EsValueFacade::new_str("item".to_string()))
+            .static_constant("MAX", EsValueFacade::new_i32(10))
+            .static_readonly_property("count", || Ok(EsValueFacade::new_i32(1)))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "(function(){'use strict'; \
+                 let item = new test.attributes.Item(); let res = {}; \
+                 res.keys = Object.keys(item).sort().join('|'); \
+                 res.json = JSON.parse(JSON.stringify(item)).id; \
+                 try {item.id = 6; res.readonly = 'assigned';} catch(ex) {res.readonly = 'readonly';} \
+                 res.secret = item.secret; res.password = typeof item.password; res.constant = item.KIND; \
+                 try {item.KIND = 'x'; res.constantAssign = 'assigned';} catch(ex) {res.constantAssign = 'constant';} \
+                 try {delete item.name; res.delete = 'deleted';} catch(ex) {res.delete = 'permanent';} \
+                 try {test.attributes.Item.count = 2; res.staticReadonly = 'assigned';} catch(ex) {res.staticReadonly = 'readonly';} \
+                 res.statics = test.attributes.Item.MAX + test.attributes.Item.count; \
+                 return res;})();",
+                "test_property_attributes.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        // secret is not enumerable
+        assert_eq!(res["keys"].get_string(), "id|name|password");
+        assert_eq!(res["json"].get_i32(), &5);
+        assert_eq!(res["readonly"].get_string(), "readonly");
+        assert_eq!(res["secret"].get_string(), "hidden");
+        // password is write only
+        assert_eq!(res["password"].get_string(), "undefined");
+        assert_eq!(res["constant"].get_string(), "item");
+        assert_eq!(res["constantAssign"].get_string(), "constant");
+        // name is not configurable
+        assert_eq!(res["delete"].get_string(), "permanent");
+        assert_eq!(res["staticReadonly"].get_string(), "readonly");
+        assert_eq!(res["statics"].get_i32(), &11);
+    }
+
+    #[test]
+    fn test_singleton() {
+        log::info!("test: test_singleton");
+        let rt = TEST_RT.clone();
+        let es_proxy = EsProxyBuilder::new(vec!["test", "singleton"], "App")
+            .static_readonly_property("version", || Ok(EsValueFacade::new_str("1.0".to_string())))
+            .static_method("getName", |_args| {
+                Ok(EsValueFacade::new_str("app".to_string()))
+            })
+            .static_constant("MAX", EsValueFacade::new_i32(3))
+            .static_event("started")
+            .build_singleton(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = {}; let app = test.singleton.App; \
+                 res.type = typeof app; res.str = String(app); \
+                 res.version = app.version; res.name = app.getName(); res.max = app.MAX; \
+                 try {new test.singleton.App(); res.construct = 'constructed';} catch(ex) {res.construct = 'not a constructor';} \
+                 app.addEventListener('started', (evt) => {return 'started';}); \
+                 res;}",
+                "test_singleton.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(res["type"].get_string(), "object");
+        assert_eq!(res["str"].get_string(), "[object test.singleton.App]");
+        assert_eq!(res["version"].get_string(), "1.0");
+        assert_eq!(res["name"].get_string(), "app");
+        assert_eq!(res["max"].get_i32(), &3);
+        assert_eq!(res["construct"].get_string(), "not a constructor");
+
+        let res = es_proxy.dispatch_static_event_sync(&rt, "started", EsValueFacade::undefined());
+        assert_eq!(res.results.len(), 1);
+        assert_eq!(res.results[0].get_string(), "started");
+        assert!(es_proxy.new_instance(&rt, vec![]).is_err());
+
+        // instance members are an error instead of a panic
+        let invalid_res = EsProxyBuilder::new(vec!["test", "singleton"], "Invalid")
+            .method("m", |_obj_id, _args| Ok(EsValueFacade::undefined()))
+            .build_singleton(&rt);
+        assert_eq!(
+            invalid_res.err().unwrap(),
+            "singleton test.singleton.Invalid can only have static members"
+        );
+    }
+
+    struct TestList {
+        items: Vec<i32>,
+    }
+
+    #[test]
+    fn test_iterable() {
+        log::info!("test: test_iterable");
+        let rt = TEST_RT.clone();
+        EsTypedProxyBuilder::new(vec!["test", "iterable"], "List")
+            .constructor(|args| {
+                Ok(TestList {
+                    items: args.iter().map(|arg| *arg.get_i32()).collect(),
+                })
+            })
+            .iterator(|list: &TestList| {
+                Ok(list.items.clone().into_iter().map(EsValueFacade::new_i32))
+            })
+            .indexed(
+                |list| Ok(list.items.len() as u32),
+                |list, index| match list.items.get(index as usize) {
+                    Some(item) => Ok(EsValueFacade::new_i32(*item)),
+                    None => Ok(EsValueFacade::undefined()),
+                },
+            )
+            .indexed_setter(|list, index, val| {
+                list.items[index as usize] = *val.get_i32();
+                Ok(())
+            })
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "{let list = new test.iterable.List(1, 2, 3); let res = {}; \
+                 let items = []; for (let item of list) {items.push(item);} res.forOf = items.join('|'); \
+                 list[1] = 5; \
+                 res.spread = [...list].join('|'); \
+                 res.length = list.length; res.first = list[0]; res.second = list[1]; \
+                 res.outOfRange = typeof list[3]; res.hasLast = 2 in list; res.hasOutOfRange = 3 in list; \
+                 res.keys = Object.keys(list).join('|'); \
+                 res.arrayFrom = Array.from(list).length; \
+                 res;}",
+                "test_iterable.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(res["forOf"].get_string(), "1|2|3");
+        // the index setter changed the second item
+        assert_eq!(res["spread"].get_string(), "1|5|3");
+        assert_eq!(res["length"].get_i32(), &3);
+        assert_eq!(res["first"].get_i32(), &1);
+        assert_eq!(res["second"].get_i32(), &5);
+        assert_eq!(res["outOfRange"].get_string(), "undefined");
+        assert!(res["hasLast"].get_boolean());
+        assert!(!res["hasOutOfRange"].get_boolean());
+        assert_eq!(res["keys"].get_string(), "0|1|2");
+        assert_eq!(res["arrayFrom"].get_i32(), &3);
+    }
+
+    struct TestConfig {
+        values: BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn test_dynamic_properties() {
+        log::info!("test: test_dynamic_properties");
+        let rt = TEST_RT.clone();
+        EsTypedProxyBuilder::new(vec!["test", "dynamic"], "Config")
+            .constructor(|_args| {
+                let mut values = BTreeMap::new();
+                values.insert("host".to_string(), "localhost".to_string());
+                values.insert("port".to_string(), "8080".to_string());
+                Ok(TestConfig { values })
+            })
+            .method("getSize", |config: &mut TestConfig, _args| {
+                Ok(EsValueFacade::new_i32(config.values.len() as i32))
+            })
+            .method("forget", |config: &mut TestConfig, args| {
+                config.values.remove(args[0].get_string());
+                Ok(EsValueFacade::undefined())
+            })
+            .dynamic_properties(
+                |config: &TestConfig, name| match config.values.get(name) {
+                    Some(val) => Ok(EsValueFacade::new_str(val.clone())),
+                    None => Ok(EsValueFacade::undefined()),
+                },
+                |config, name, val| {
+                    if name.eq("locked") {
+                        return Err("locked is read only".to_string());
+                    }
+                    config
+                        .values
+                        .insert(name.to_string(), val.get_string().clone());
+                    Ok(())
+                },
+                |config, name| Ok(config.values.contains_key(name)),
+                |config, name| Ok(config.values.remove(name).is_some()),
+                |config| Ok(config.values.keys().cloned().collect()),
+            )
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "{let cfg = new test.dynamic.Config(); let res = {}; \
+                 res.keys = Object.keys(cfg).join('|'); \
+                 res.host = cfg.host; res.port = cfg.port; res.missing = typeof cfg.missing; \
+                 cfg.user = 'admin'; cfg.port = '9090'; \
+                 res.user = cfg.user; res.assignedPort = cfg.port; \
+                 res.hasUser = 'user' in cfg; res.hasMissing = 'missing' in cfg; \
+                 delete cfg.host; \
+                 res.hasDeleted = 'host' in cfg; res.keysAfterDelete = Object.keys(cfg).join('|'); \
+                 res.size = cfg.getSize(); \
+                 try {cfg.locked = 'yes'; res.locked = 'assigned';} catch(ex) {res.locked = 'read only';} \
+                 res.hasLocked = 'locked' in cfg; \
+                 res;}",
+                "test_dynamic_properties.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(res["keys"].get_string(), "host|port");
+        assert_eq!(res["host"].get_string(), "localhost");
+        assert_eq!(res["port"].get_string(), "8080");
+        assert_eq!(res["missing"].get_string(), "undefined");
+        // new and existing properties are passed to the setter
+        assert_eq!(res["user"].get_string(), "admin");
+        assert_eq!(res["assignedPort"].get_string(), "9090");
+        assert!(res["hasUser"].get_boolean());
+        assert!(!res["hasMissing"].get_boolean());
+        assert!(!res["hasDeleted"].get_boolean());
+        assert_eq!(res["keysAfterDelete"].get_string(), "port|user");
+        assert_eq!(res["size"].get_i32(), &2);
+        // a failing setter does not add the property
+        assert_eq!(res["locked"].get_string(), "read only");
+        assert!(!res["hasLocked"].get_boolean());
+
+        // a key which was removed in rust is no longer enumerated or found after the keys were listed again
+        let esvf = rt
+            .eval_sync(
+                "{let cfg = new test.dynamic.Config(); let res = {hasBefore: 'port' in cfg}; \
+                 cfg.forget('port'); \
+                 res.keys = Object.keys(cfg).join('|'); res.hasAfter = 'port' in cfg; \
+                 res;}",
+                "test_dynamic_properties2.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert!(res["hasBefore"].get_boolean());
+        assert_eq!(res["keys"].get_string(), "host");
+        assert!(!res["hasAfter"].get_boolean());
+    }
+
+    #[test]
+    fn test_unregister() {
+        log::info!("test: test_unregister");
+        let rt = TEST_RT.clone();
+        let es_proxy = EsProxyBuilder::new(vec!["test", "unregister"], "Plugin")
+            .constructor(|_args| Ok(1))
+            .method("version", |_obj_id, _args| Ok(EsValueFacade::new_i32(1)))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        rt.eval_sync(
+            "this.test_unregister_plugin = new test.unregister.Plugin(); \
+             this.test_unregister_ctor = test.unregister.Plugin;",
+            "test_unregister.es",
+        )
+        .ok()
+        .expect("script failed");
+
+        // replace the class
+        EsProxyBuilder::new(vec!["test", "unregister"], "Plugin")
+            .constructor(|_args| Ok(2))
+            .method("version", |_obj_id, _args| Ok(EsValueFacade::new_i32(2)))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = {}; \
+                 res.oldInstance = this.test_unregister_plugin.version(); \
+                 res.newInstance = new test.unregister.Plugin().version(); \
+                 try {new this.test_unregister_ctor(); res.oldCtor = 'constructed';} catch(ex) {res.oldCtor = 'not registered';} \
+                 res;}",
+                "test_unregister2.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        // existing instances keep the replaced class
+        assert_eq!(res["oldInstance"].get_i32(), &1);
+        assert_eq!(res["newInstance"].get_i32(), &2);
+        assert_eq!(res["oldCtor"].get_string(), "not registered");
+
+        es_proxy.unregister(&rt).ok().expect("could not unregister");
+        assert!(es_proxy.unregister(&rt).is_err());
+        assert!(es_proxy.new_instance(&rt, vec![]).is_err());
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = {type: typeof test.unregister.Plugin}; \
+                 res.oldInstance = this.test_unregister_plugin.version(); \
+                 delete this.test_unregister_plugin; delete this.test_unregister_ctor; \
+                 res;}",
+                "test_unregister3.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(res["type"].get_string(), "undefined");
+        assert_eq!(res["oldInstance"].get_i32(), &1);
+    }
+
+    #[test]
+    fn test_describe() {
+        log::info!("test: test_describe");
+        let rt = TEST_RT.clone();
+        let es_proxy = EsProxyBuilder::new(vec!["test", "describe"], "Shape")
+            .doc("a shape")
+            .constructor(|_args| Ok(1))
+            .method("area", |_obj_id, _args| Ok(EsValueFacade::new_i32(4)))
+            .member_doc("area", "calculate the area")
+            .readonly_property("name", |_obj_id| {
+                Ok(EsValueFacade::new_str("square".to_string()))
+            })
+            .member_doc("name", "the name of the shape")
+            .property(
+                "color",
+                |_obj_id| Ok(EsValueFacade::new_str("red".to_string())),
+                |_obj_id, _val| Ok(()),
+            )
+            .constant("SIDES", EsValueFacade::new_i32(4))
+            .event("changed")
+            .member_doc("changed", "fired when the color changes")
+            .static_method("create", |_args| Ok(EsValueFacade::undefined()))
+            .build(&rt)
+            .ok()
+            .expect("could not build proxy");
+        EsProxyBuilder::new(vec!["test", "describe"], "Registry")
+            .static_method("lookup", |_args| Ok(EsValueFacade::undefined()))
+            .build_singleton(&rt)
+            .ok()
+            .expect("could not build proxy");
+
+        let desc = es_proxy.describe(&rt).expect("class was not registered");
+        assert_eq!(desc.get_canonical_name(), "test.describe.Shape");
+        assert_eq!(desc.doc.as_deref(), Some("a shape"));
+        assert!(desc.constructable);
+        assert!(!desc.singleton);
+        let prop_names: Vec<&str> = desc.properties.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(prop_names, vec!["color", "name"]);
+        assert!(desc.properties[0].writable);
+        assert!(!desc.properties[1].writable);
+        assert_eq!(
+            desc.properties[1].doc.as_deref(),
+            Some("the name of the shape")
+        );
+        assert_eq!(desc.constants[0].name, "SIDES");
+        assert_eq!(desc.static_methods[0].name, "create");
+        assert!(EsProxy::describe_all(&rt)
+            .iter()
+            .any(|d| d.get_canonical_name() == "test.describe.Registry" && d.singleton));
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = {}; \
+                 res.instanceTag = String(new test.describe.Shape()); \
+                 res.singletonTag = Object.prototype.toString.call(test.describe.Registry); \
+                 let desc = esses.describe('test.describe.Shape'); \
+                 res.methods = desc.methods.map((m) => m.name + ':' + m.doc).join('|'); \
+                 res.event = desc.events[0].name; \
+                 res.byInstance = esses.describe(new test.describe.Shape()).className; \
+                 res.byConstructor = esses.describe(test.describe.Shape).staticMethods[0].name; \
+                 res.bySingleton = esses.describe(test.describe.Registry).singleton; \
+                 res.noProxy = esses.describe({}) === null; \
+                 res;}",
+                "test_describe.es",
+            )
+            .ok()
+            .expect("script failed");
+        let res = esvf.get_object();
+        assert_eq!(
+            res["instanceTag"].get_string(),
+            "[object test.describe.Shape]"
+        );
+        assert_eq!(
+            res["singletonTag"].get_string(),
+            "[object test.describe.Registry]"
+        );
+        assert_eq!(res["methods"].get_string(), "area:calculate the area");
+        assert_eq!(res["event"].get_string(), "changed");
+        // describe accepts a canonical name, an instance, a constructor or a singleton
+        assert_eq!(res["byInstance"].get_string(), "Shape");
+        assert_eq!(res["byConstructor"].get_string(), "create");
+        assert!(res["bySingleton"].get_boolean());
+        assert!(res["noProxy"].get_boolean());
+
+        let dts = EsProxy::generate_typescript_declarations(&rt);
+        assert!(dts.contains("declare function setImmediate(...args: any[]): any;\n"));
+        assert!(!dts.contains("__describe_proxy"));
+        assert!(dts.contains("declare namespace test.describe {\n"));
+        assert!(dts.contains("    /**\n     * a shape\n     */\n    export class Shape {\n        constructor(...args: any[]);\n"));
+        assert!(dts.contains("        readonly SIDES: any;\n        color: any;\n"));
+        assert!(dts.contains("        /**\n         * the name of the shape\n         */\n        readonly name: any;\n"));
+        assert!(dts.contains("        area(...args: any[]): any;\n"));
+        assert!(dts.contains(
+            "        addEventListener(type: \"changed\", listener: (evt: any) => any): void;\n"
+        ));
+        assert!(dts.contains("        static create(...args: any[]): any;\n"));
+        assert!(dts.contains(
+            "    export const Registry: {\n        lookup(...args: any[]): any;\n    };\n"
+        ));
+    }
+}
@@ -3,8 +3,11 @@ use std::{str, thread};
 use std::sync::{Arc, Weak};
 
 use crate::es_sys_scripts;
+use crate::esconversions::TypedFunction;
 use crate::features;
 
+use crate::esfuture::EsFuture;
+use crate::esobjectref::EsObjectRef;
 use crate::esruntimeinner::EsRuntimeInner;
 use crate::esvaluefacade::EsValueFacade;
 use crate::jsapi_utils::EsErrorInfo;
@@ -28,6 +31,36 @@ pub struct EsRuntime {
 
 pub type ModuleCodeLoader = dyn Fn(&str) -> String + Send + Sync + 'static;
 
+/// the type for the handler of uncaught errors, see EsRuntimeBuilder::on_uncaught_error
+pub type UncaughtErrorHandler = dyn Fn(&EsErrorInfo) + Send + Sync + 'static;
+
+/// the type for the handler of rejected promises, see EsRuntimeBuilder::promise_rejection_handler
+pub type PromiseRejectionHandler = dyn Fn(&PromiseRejectionInfo) + Send + Sync + 'static;
+
+/// the reason why a PromiseRejectionHandler was called
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromiseRejectionState {
+    /// a promise was rejected and still had no rejection handler after all pending promise jobs ran
+    Unhandled,
+    /// a rejection handler was added to a promise which was reported as Unhandled before
+    Handled,
+    /// a promise job (e.g. a then or catch callback) threw an error which could not be passed to a promise
+    JobFailed,
+}
+
+/// info about a rejected promise which is passed to the PromiseRejectionHandler
+pub struct PromiseRejectionInfo {
+    /// the state of the rejection
+    pub state: PromiseRejectionState,
+    /// the id of the promise, this is the same for the Unhandled and Handled notifications of a promise
+    /// and 0 for failed jobs
+    pub promise_id: u64,
+    /// the reason the promise was rejected with
+    pub reason: EsValueFacade,
+    /// the stack of the reason if it was an Error, or the location of the error for failed jobs
+    pub stack: String,
+}
+
 impl EsRuntime {
     /// create a builder to instantiate an EsRuntime
     pub fn builder() -> EsRuntimeBuilder {
@@ -100,6 +133,22 @@ impl EsRuntime {
         self.do_with_inner(move |inner| inner.eval_sync(code, file_name))
     }
 
+    /// eval a script and get the resulting object as a live EsObjectRef instead of a copy
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let obj_ref = rt.eval_object_ref_sync("({a: 1});", "test_eval_object_ref.es").ok().expect("script failed");
+    /// assert_eq!(obj_ref.keys(), vec!["a"]);
+    /// ```
+    pub fn eval_object_ref_sync(
+        &self,
+        code: &str,
+        file_name: &str,
+    ) -> Result<EsObjectRef, EsErrorInfo> {
+        self.do_with_inner(move |inner| inner.eval_object_ref_sync(code, file_name))
+    }
+
     /// load a script module and run it
     /// # Example
     /// ```rust
@@ -153,6 +202,36 @@ impl EsRuntime {
         self.do_with_inner(move |inner| inner.call(obj_names, function_name, args))
     }
 
+    /// eval a script and get a Future for the result
+    /// the returned Future does not depend on a specific executor
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use futures::executor::block_on;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let esvf = block_on(rt.eval_async("(3 * 5);", "test_eval_async.es")).ok().expect("script failed");
+    /// assert_eq!(esvf.get_i32(), &15);
+    /// ```
+    pub fn eval_async(
+        &self,
+        eval_code: &str,
+        file_name: &str,
+    ) -> EsFuture<Result<EsValueFacade, EsErrorInfo>> {
+        self.do_with_inner(move |inner| inner.eval_async(eval_code, file_name))
+    }
+
+    /// call a function by name and get a Future for the result
+    /// if the function returns a Promise you can await that with EsValueFacade::get_promise_future
+    pub fn call_async(
+        &self,
+        obj_names: Vec<&'static str>,
+        function_name: &str,
+        args: Vec<EsValueFacade>,
+    ) -> EsFuture<Result<EsValueFacade, EsErrorInfo>> {
+        self.do_with_inner(move |inner| inner.call_async(obj_names, function_name, args))
+    }
+
     pub fn do_with_inner<R, F: FnOnce(&EsRuntimeInner) -> R>(&self, f: F) -> R {
         let inner = self.inner.clone();
         f(&*inner)
@@ -217,6 +296,33 @@ impl EsRuntime {
         })
     }
 
+    /// add a global function with typed arguments and a typed result, the arguments are converted
+    /// with FromEsValue and the result with ToEsValue (see the esconversions mod)
+    /// if the number or the types of the arguments do not match the function a TypeError is thrown in script
+    /// missing arguments are passed as undefined so they can be declared as Option
+    ///
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.add_global_typed_function("test_add_typed", |a: i32, b: f64| -> Result<f64, String> {
+    ///     Ok(a as f64 + b)
+    /// });
+    /// let esvf = rt.eval_sync("test_add_typed(1, 2.5);", "test_add_global_typed_function.es").ok().expect("test_add_global_typed_function failed");
+    /// assert_eq!(esvf.get_f64(), &3.5);
+    /// let esvf = rt.eval_sync("try {test_add_typed('1', 2.5);} catch(ex) {ex instanceof TypeError;}", "test_add_global_typed_function2.es").ok().expect("test_add_global_typed_function failed");
+    /// assert!(esvf.get_boolean());
+    /// ```
+    pub fn add_global_typed_function<A, F>(&self, name: &'static str, func: F)
+    where
+        F: TypedFunction<A>,
+    {
+        self.do_with_inner(move |inner| {
+            inner.add_global_typed_function(name, func);
+        })
+    }
+
     /// add a global function to the runtime which is callable just like any other js function
     /// this async variant will run the method in a separate thread and return the result as a Promise
     /// # Example
@@ -247,11 +353,11 @@ impl EsRuntime {
 #[cfg(test)]
 pub mod tests {
 
-    use crate::esruntime::EsRuntime;
+    use crate::esruntime::{EsRuntime, PromiseRejectionState};
     use crate::esvaluefacade::EsValueFacade;
     use crate::jsapi_utils::EsErrorInfo;
     use log::LevelFilter;
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::thread;
     use std::time::Duration;
 
@@ -416,4 +522,90 @@ pub mod tests {
         let esvf_res = prom_res.ok().unwrap();
         assert_eq!(&123, esvf_res.get_i32());
     }
+
+    #[test]
+    fn test_promise_rejection_handler() {
+        log::info!("test: test_promise_rejection_handler");
+        let rejections = Arc::new(Mutex::new(vec![]));
+        let rejections2 = rejections.clone();
+        let rt = EsRuntime::builder()
+            .promise_rejection_handler(move |info| {
+                let rejections = &mut *rejections2.lock().unwrap();
+                rejections.push((info.state, info.promise_id, info.stack.clone()));
+            })
+            .build();
+
+        rt.eval_sync(
+            "Promise.reject(new Error('oops')); 1;",
+            "test_promise_rejection_handler.es",
+        )
+        .ok()
+        .expect("script failed");
+        rt.eval_sync(
+            "this.p = Promise.reject(1); 1;",
+            "test_promise_rejection_handler2.es",
+        )
+        .ok()
+        .expect("script failed");
+        rt.eval_sync(
+            "p.catch(() => {}); 1;",
+            "test_promise_rejection_handler3.es",
+        )
+        .ok()
+        .expect("script failed");
+        // handled before all promise jobs ran, so these are never reported
+        rt.eval_sync(
+            "Promise.reject(2).catch(() => {}); let p4 = Promise.reject(3); Promise.resolve().then(() => {}).then(() => {p4.catch(() => {});}); 1;",
+            "test_promise_rejection_handler4.es",
+        )
+        .ok()
+        .expect("script failed");
+        // run the queued jobs, every round of the worker runs the jobs which were queued in the previous one
+        for _x in 0..5 {
+            rt.do_in_es_runtime_thread_sync(|_sm_rt| {});
+        }
+
+        let rejections = &*rejections.lock().unwrap();
+        assert_eq!(rejections.len(), 3);
+        assert_eq!(rejections[0].0, PromiseRejectionState::Unhandled);
+        assert!(rejections[0]
+            .2
+            .contains("test_promise_rejection_handler.es"));
+        assert_eq!(rejections[1].0, PromiseRejectionState::Unhandled);
+        assert_eq!(rejections[2].0, PromiseRejectionState::Handled);
+        assert_eq!(rejections[1].1, rejections[2].1);
+    }
+
+    #[test]
+    fn test_add_global_typed_function() {
+        log::info!("test: test_add_global_typed_function");
+        let rt: Arc<EsRuntime> = TEST_RT.clone();
+        rt.add_global_typed_function(
+            "test_typed_join",
+            |parts: Vec<String>, sep: Option<String>| -> Result<String, String> {
+                Ok(parts.join(sep.unwrap_or_else(|| ",".to_string()).as_str()))
+            },
+        );
+        let esvf = rt
+            .eval_sync(
+                "test_typed_join(['a', 'b']) + test_typed_join(['c', 'd'], '-');",
+                "test_add_global_typed_function.es",
+            )
+            .ok()
+            .expect("script failed");
+        assert_eq!(esvf.get_string(), "a,bc-d");
+
+        let esvf = rt
+            .eval_sync(
+                "{let res = []; \
+                 try {test_typed_join('a');} catch(ex) {res.push(ex instanceof TypeError);} \
+                 try {test_typed_join();} catch(ex) {res.push(ex instanceof TypeError);} \
+                 try {test_typed_join([], '', 1);} catch(ex) {res.push(ex instanceof TypeError);} \
+                 res.join(',');}",
+                "test_add_global_typed_function2.es",
+            )
+            .ok()
+            .expect("script failed");
+        assert_eq!(esvf.get_string(), "true,true,true");
+    }
 }
@@ -1,5 +1,10 @@
-use crate::esruntime::{EsRuntime, ModuleCodeLoader};
+use crate::esruntime::{
+    EsRuntime, ModuleCodeLoader, PromiseRejectionHandler, PromiseRejectionInfo,
+    UncaughtErrorHandler,
+};
 use crate::esruntimeinner::EsRuntimeInner;
+use crate::jsapi_utils::EsErrorInfo;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// The EsRuntimeWrapperBuilder struct can be used to initialize a new EsRuntimeWrapper
@@ -18,6 +23,10 @@ pub struct EsRuntimeBuilder {
     gc_interval: Option<Duration>,
     pub(crate) module_code_loader: Option<Box<ModuleCodeLoader>>,
     pub(crate) module_cache_size: usize,
+    pub(crate) include_symbol_props: bool,
+    pub(crate) include_non_enumerable_props: bool,
+    pub(crate) promise_rejection_handler: Option<Arc<PromiseRejectionHandler>>,
+    pub(crate) uncaught_error_handler: Option<Arc<UncaughtErrorHandler>>,
     built: bool,
 }
 
@@ -28,6 +37,10 @@ impl EsRuntimeBuilder {
             gc_interval: None,
             module_code_loader: None,
             module_cache_size: 50,
+            include_symbol_props: false,
+            include_non_enumerable_props: false,
+            promise_rejection_handler: None,
+            uncaught_error_handler: None,
             built: false,
         }
     }
@@ -51,6 +64,71 @@ impl EsRuntimeBuilder {
         self
     }
 
+    /// when set to true objects which are converted to an EsValueFacade will also contain their
+    /// Symbol keyed properties, these can be retrieved with EsValueFacade::get_symbol_props()
+    /// props keyed by a unique Symbol (Symbol(description)) are not included, see EsValueFacade::get_symbol_props()
+    pub fn include_symbol_props(&mut self, include: bool) -> &mut Self {
+        self.include_symbol_props = include;
+        self
+    }
+
+    /// when set to true objects which are converted to an EsValueFacade will also contain their
+    /// non-enumerable properties
+    pub fn include_non_enumerable_props(&mut self, include: bool) -> &mut Self {
+        self.include_non_enumerable_props = include;
+        self
+    }
+
+    /// set a handler which is called when a promise is rejected without a rejection handler,
+    /// when a rejection handler is added to such a promise later, or when a promise job fails
+    /// a rejection is only reported as Unhandled if the promise still has no rejection handler after all
+    /// pending promise jobs have run, so Promise.reject(x).catch(f) is never reported
+    /// if no handler is set these are logged as errors (and Handled at debug level)
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esruntime::PromiseRejectionState;
+    ///
+    /// let rt = EsRuntimeBuilder::new()
+    ///     .promise_rejection_handler(|info| {
+    ///         if info.state == PromiseRejectionState::Unhandled {
+    ///             println!("unhandled rejection of promise {}: {}", info.promise_id, info.stack);
+    ///         }
+    ///     })
+    ///     .build();
+    /// rt.eval_sync("Promise.reject(new Error('oops')); 1;", "test_promise_rejection_handler.es").ok().expect("script failed");
+    /// ```
+    pub fn promise_rejection_handler<H>(&mut self, handler: H) -> &mut Self
+    where
+        H: Fn(&PromiseRejectionInfo) + Send + Sync + 'static,
+    {
+        self.promise_rejection_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// set a handler which is called for every uncaught error from an async script context,
+    /// e.g. an error thrown from a setImmediate callback or from an event listener
+    /// the error is first dispatched to the script (onerror and addEventListener('error', ...))
+    /// if no handler is set these are logged as errors
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new()
+    ///     .on_uncaught_error(|err| {
+    ///         println!("uncaught error: {}", err.err_msg());
+    ///     })
+    ///     .build();
+    /// rt.eval_sync("setImmediate(() => {throw Error('oops');}); 1;", "test_on_uncaught_error.es").ok().expect("script failed");
+    /// ```
+    pub fn on_uncaught_error<H>(&mut self, handler: H) -> &mut Self
+    where
+        H: Fn(&EsErrorInfo) + Send + Sync + 'static,
+    {
+        self.uncaught_error_handler = Some(Arc::new(handler));
+        self
+    }
+
     /// build a new EsRuntimeWrapper based on the settings of this builder
     /// please note that this can be used only once
     pub fn build(&mut self) -> EsRuntime {
@@ -68,7 +146,14 @@ impl EsRuntimeBuilder {
             None
         };
 
-        let inner = EsRuntimeInner::build(mcl_opt, self.module_cache_size);
+        let inner = EsRuntimeInner::build(
+            mcl_opt,
+            self.module_cache_size,
+            self.include_symbol_props,
+            self.include_non_enumerable_props,
+            self.promise_rejection_handler.take(),
+            self.uncaught_error_handler.take(),
+        );
         let wrapper = EsRuntime::new_inner(inner);
         if self.gc_interval.is_some() {
             wrapper.start_gc_deamon(self.gc_interval.unwrap());
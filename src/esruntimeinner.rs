@@ -1,5 +1,12 @@
-use crate::esruntime::ModuleCodeLoader;
+use crate::esconversions::{TypedFunction, TypedFunctionError};
+use crate::esfuture::{new_es_future, EsFuture};
+use crate::esobjectref::EsObjectRef;
+use crate::esruntime::{
+    ModuleCodeLoader, PromiseRejectionHandler, PromiseRejectionInfo, PromiseRejectionState,
+    UncaughtErrorHandler,
+};
 use crate::esvaluefacade::EsValueFacade;
+use crate::jsapi_utils;
 use crate::jsapi_utils::EsErrorInfo;
 use crate::microtaskmanager::MicroTaskManager;
 use crate::spidermonkeyruntimewrapper::SmRuntime;
@@ -13,18 +20,73 @@ pub struct EsRuntimeInner {
     pub(crate) _pre_cleanup_tasks: Vec<Box<dyn Fn(&EsRuntimeInner) -> () + Send + Sync>>,
     pub(crate) module_source_loader: Option<Box<dyn Fn(&str) -> String + Send + Sync>>,
     pub(crate) module_cache_size: usize,
+    pub(crate) include_symbol_props: bool,
+    pub(crate) include_non_enumerable_props: bool,
+    pub(crate) promise_rejection_handler: Option<Arc<PromiseRejectionHandler>>,
+    pub(crate) uncaught_error_handler: Option<Arc<UncaughtErrorHandler>>,
+}
+
+/// the result of an async job which was dropped before it ran (e.g. because the runtime was dropped)
+fn dropped_job_result() -> Result<EsValueFacade, EsErrorInfo> {
+    Err(EsErrorInfo {
+        message: "the runtime was dropped before the job was run".to_string(),
+        filename: "".to_string(),
+        lineno: 0,
+        column: 0,
+    })
 }
 
 impl EsRuntimeInner {
     pub(crate) fn build(
         module_source_loader: Option<Box<ModuleCodeLoader>>,
         module_cache_size: usize,
+        include_symbol_props: bool,
+        include_non_enumerable_props: bool,
+        promise_rejection_handler: Option<Arc<PromiseRejectionHandler>>,
+        uncaught_error_handler: Option<Arc<UncaughtErrorHandler>>,
     ) -> Self {
         EsRuntimeInner {
             task_manager: MicroTaskManager::new(),
             _pre_cleanup_tasks: vec![],
             module_source_loader,
             module_cache_size,
+            include_symbol_props,
+            include_non_enumerable_props,
+            promise_rejection_handler,
+            uncaught_error_handler,
+        }
+    }
+
+    /// pass an uncaught error to the UncaughtErrorHandler, or log it if there is no handler
+    pub(crate) fn report_uncaught_error(&self, err: &EsErrorInfo) {
+        if let Some(handler) = self.uncaught_error_handler.as_ref() {
+            handler(err);
+        } else {
+            log::error!("uncaught error: {}", err.err_msg());
+        }
+    }
+
+    /// pass a rejected promise to the PromiseRejectionHandler, or log it if there is no handler
+    /// (a rejection which was handled after all is only logged at debug level)
+    pub(crate) fn report_promise_rejection(&self, info: PromiseRejectionInfo) {
+        if let Some(handler) = self.promise_rejection_handler.as_ref() {
+            handler(&info);
+        } else if info.state == PromiseRejectionState::Handled {
+            log::debug!("rejected promise {} was handled", info.promise_id);
+        } else if info.reason.is_string() {
+            log::error!(
+                "promise rejection ({:?}) with reason: {} at {}",
+                info.state,
+                info.reason.get_string(),
+                info.stack
+            );
+        } else {
+            log::error!(
+                "promise rejection ({:?}) with reason: {} at {}",
+                info.state,
+                info.reason.as_js_expression_str(),
+                info.stack
+            );
         }
     }
 
@@ -58,6 +120,45 @@ impl EsRuntimeInner {
         }))
     }
 
+    pub fn call_async(
+        &self,
+        obj_names: Vec<&'static str>,
+        function_name: &str,
+        args: Vec<EsValueFacade>,
+    ) -> EsFuture<Result<EsValueFacade, EsErrorInfo>> {
+        debug!(
+            "call_async {} in thread {}",
+            function_name,
+            thread_id::get()
+        );
+        let f_n = function_name.to_string();
+        let (fut, resolver) = new_es_future(dropped_job_result);
+
+        self.do_in_es_runtime_thread(Box::new(move |sm_rt: &SmRuntime| {
+            resolver.resolve(sm_rt.call(obj_names, f_n.as_str(), args));
+        }));
+
+        fut
+    }
+
+    pub fn eval_async(
+        &self,
+        eval_code: &str,
+        file_name: &str,
+    ) -> EsFuture<Result<EsValueFacade, EsErrorInfo>> {
+        debug!("eval_async {} in thread {}", eval_code, thread_id::get());
+
+        let eval_code = eval_code.to_string();
+        let file_name = file_name.to_string();
+        let (fut, resolver) = new_es_future(dropped_job_result);
+
+        self.do_in_es_runtime_thread(Box::new(move |sm_rt: &SmRuntime| {
+            resolver.resolve(sm_rt.eval(eval_code.as_str(), file_name.as_str()));
+        }));
+
+        fut
+    }
+
     pub fn eval(&self, eval_code: &str, file_name: &str) {
         debug!("eval {} in thread {}", eval_code, thread_id::get());
 
@@ -82,6 +183,19 @@ impl EsRuntimeInner {
         }))
     }
 
+    pub fn eval_object_ref_sync(
+        &self,
+        code: &str,
+        file_name: &str,
+    ) -> Result<EsObjectRef, EsErrorInfo> {
+        let eval_code = code.to_string();
+        let file_name = file_name.to_string();
+
+        self.do_in_es_runtime_thread_sync(Box::new(move |sm_rt: &SmRuntime| {
+            sm_rt.eval_object_ref(eval_code.as_str(), file_name.as_str())
+        }))
+    }
+
     pub fn eval_void_sync(&self, code: &str, file_name: &str) -> Result<(), EsErrorInfo> {
         let eval_code = code.to_string();
         let file_name = file_name.to_string();
@@ -241,6 +355,42 @@ impl EsRuntimeInner {
             });
         });
     }
+
+    pub fn add_global_typed_function<A, F>(&self, name: &'static str, func: F)
+    where
+        F: TypedFunction<A>,
+    {
+        self.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt.add_global_function(name, move |cx, args: CallArgs| {
+                let args_vec = EsValueFacade::new_v_vec_from_call_args(&args);
+
+                match func.invoke(args_vec) {
+                    Ok(esvf) => {
+                        args.rval().set(esvf.to_es_value(cx));
+                        true
+                    }
+                    Err(TypedFunctionError::TypeError(msg)) => {
+                        // throw a TypeError
+                        let err = EsValueFacade::new_error(
+                            "TypeError",
+                            format!("{}: {}", name, msg).as_str(),
+                            None,
+                            None,
+                        );
+                        rooted!(in (cx) let err_root = err.to_es_value(cx));
+                        jsapi_utils::set_pending_exception(cx, err_root.handle());
+                        false
+                    }
+                    Err(TypedFunctionError::Failed(js_err)) => {
+                        // report es err
+                        let s = format!("method failed\ncaused by: {}\0", js_err);
+                        unsafe { JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char) };
+                        false
+                    }
+                }
+            });
+        });
+    }
 }
 
 impl Drop for EsRuntimeInner {
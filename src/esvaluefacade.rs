@@ -1,6 +1,9 @@
 use log::trace;
 
 use crate::debugmutex::DebugMutex;
+use crate::esfuture::{new_es_future, EsFuture};
+use crate::esobjectref::EsObjectRef;
+use crate::esreflection::EsProxyInstanceRef;
 use crate::esruntime::EsRuntime;
 use crate::esruntimeinner::EsRuntimeInner;
 use crate::jsapi_utils::arrays::{get_array_element, get_array_length, new_array, object_is_array};
@@ -10,19 +13,54 @@ use crate::spidermonkeyruntimewrapper::SmRuntime;
 use crate::utils::AutoIdMap;
 use crate::{jsapi_utils, spidermonkeyruntimewrapper};
 use either::Either;
+use indexmap::IndexMap;
+use mozjs::jsapi::CallArgs;
 use mozjs::jsapi::JSContext;
 use mozjs::jsapi::JSObject;
+use mozjs::jsapi::JS_ReportErrorASCII;
 use mozjs::jsval::{BooleanValue, DoubleValue, Int32Value, JSVal, ObjectValue, UndefinedValue};
 use mozjs::rust::{HandleObject, HandleValue, Runtime};
 use std::cell::RefCell;
-use std::collections::HashMap;
-use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
-use std::sync::{Arc, Weak};
+use std::rc::Rc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 
+/// the type for rust closures which can be passed to the script engine as a function
+pub type RustFunction =
+    dyn Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static;
+
+/// a description of an Error, when passed to the script engine this becomes an instance of Error
+/// (or of the built-in Error subclass with the same name like TypeError or RangeError)
+pub struct EsErrorDescription {
+    /// the name of the Error, e.g. TypeError
+    pub name: String,
+    /// the message of the Error
+    pub message: String,
+    /// an optional code which is set as the code property of the Error
+    pub code: Option<String>,
+    /// optional data which is set as the data property of the Error
+    pub data: Option<EsValueFacade>,
+}
+
 struct RustManagedEsVar {
     obj_id: i32,
-    opt_receiver: Option<Receiver<Result<EsValueFacade, EsValueFacade>>>,
+    // taken by the first consumer of the result of the promise
+    promise_future: Mutex<Option<EsFuture<Result<EsValueFacade, EsValueFacade>>>>,
+}
+
+/// the result of a promise of which the result was already consumed
+fn promise_result_consumed() -> Result<EsValueFacade, EsValueFacade> {
+    Err(EsValueFacade::new_str(
+        "the result of the promise was already consumed".to_string(),
+    ))
+}
+
+/// the result of a promise which was dropped without being settled (e.g. because the runtime was dropped)
+fn promise_dropped() -> Result<EsValueFacade, EsValueFacade> {
+    Err(EsValueFacade::new_str(
+        "the promise was dropped without being settled".to_string(),
+    ))
 }
 
 /// the EsValueFacade is a converter between rust variables and script objects
@@ -44,42 +82,40 @@ pub struct EsValueFacade {
     val_f64: Option<f64>,
     val_boolean: Option<bool>,
     val_managed_var: Option<RustManagedEsVar>,
-    val_object: Option<HashMap<String, EsValueFacade>>,
+    val_object: Option<IndexMap<String, EsValueFacade>>,
+    val_symbol_props: Option<Vec<(String, EsValueFacade)>>,
     val_array: Option<Vec<EsValueFacade>>,
     val_promise: Option<usize>,
     val_js_function: Option<(usize, Arc<EsRuntimeInner>)>,
+    val_object_ref: Option<EsObjectRef>,
+    val_proxy_instance: Option<EsProxyInstanceRef>,
+    val_rust_function: Option<(String, Arc<RustFunction>)>,
+    val_error: Option<EsErrorDescription>,
 }
 
 thread_local! {
-    static PROMISE_RESOLUTION_TRANSMITTERS: RefCell<HashMap<i32, Sender<Result<EsValueFacade, EsValueFacade>>>> = RefCell::new(HashMap::new());
+    static NEXT_MANAGED_VAR_ID: RefCell<i32> = RefCell::new(0);
 }
 
 type PromiseAnswersMap = AutoIdMap<PromiseResultContainerOption>;
 
+/// the Error constructors an EsErrorDescription may be converted to by name
+const BUILTIN_ERROR_TYPES: [&str; 7] = [
+    "Error",
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+];
+
 lazy_static! {
     static ref PROMISE_ANSWERS: Arc<DebugMutex<PromiseAnswersMap>> =
         Arc::new(DebugMutex::new(AutoIdMap::new(), "PROMISE_ANSWERS"));
 }
 
 impl EsValueFacade {
-    pub(crate) fn resolve_future(man_obj_id: i32, res: Result<EsValueFacade, EsValueFacade>) {
-        PROMISE_RESOLUTION_TRANSMITTERS.with(|rc| {
-            let map: &mut HashMap<i32, Sender<Result<EsValueFacade, EsValueFacade>>> =
-                &mut *rc.borrow_mut();
-            let opt: Option<Sender<Result<EsValueFacade, EsValueFacade>>> = map.remove(&man_obj_id);
-            if let Some(opt_c) = opt {
-                // todo, if get_result_blocking times out, does this panic and crash the server?
-                let send_res = opt_c.send(res);
-                if send_res.is_err() {
-                    // esvaluefacade was dropped
-                    log::debug!("could not send res, maybe the EsValueFacade was dropped");
-                }
-            } else {
-                panic!("no transmitter found {}", man_obj_id);
-            }
-        })
-    }
-
     /// create a new EsValueFacade representing an undefined value
     pub fn undefined() -> Self {
         EsValueFacade {
@@ -89,9 +125,14 @@ impl EsValueFacade {
             val_boolean: None,
             val_managed_var: None,
             val_object: None,
+            val_symbol_props: None,
             val_array: None,
             val_promise: None,
             val_js_function: None,
+            val_object_ref: None,
+            val_proxy_instance: None,
+            val_rust_function: None,
+            val_error: None,
         }
     }
 
@@ -102,10 +143,116 @@ impl EsValueFacade {
         ret
     }
 
-    /// create a new EsValueFacade representing a basic object with properties as defined in the HashMap
-    pub fn new_obj(props: HashMap<String, EsValueFacade>) -> Self {
+    /// create a new EsValueFacade representing a basic object with properties as defined in the map
+    /// the properties are defined in the order in which they are iterated, so if you care about the
+    /// order of the props in script you should pass an IndexMap or a Vec of (name, value) tuples
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let esvf = EsValueFacade::new_obj(vec![
+    ///     ("b".to_string(), EsValueFacade::new_i32(1)),
+    ///     ("a".to_string(), EsValueFacade::new_i32(2)),
+    /// ]);
+    /// let keys: Vec<&String> = esvf.get_object().keys().collect();
+    /// assert_eq!(keys, vec!["b", "a"]);
+    /// ```
+    pub fn new_obj<M>(props: M) -> Self
+    where
+        M: IntoIterator<Item = (String, EsValueFacade)>,
+    {
+        let mut ret = Self::undefined();
+        ret.val_object = Some(props.into_iter().collect());
+        ret
+    }
+
+    /// create a new EsValueFacade representing a basic object with string and Symbol keyed properties
+    /// the Symbol keyed props are defined using the well known Symbol for descriptions like "Symbol.iterator"
+    /// and the registered Symbol (Symbol.for(description)) for all other descriptions
+    pub fn new_obj_with_symbols<M, S>(props: M, symbol_props: S) -> Self
+    where
+        M: IntoIterator<Item = (String, EsValueFacade)>,
+        S: IntoIterator<Item = (String, EsValueFacade)>,
+    {
+        let mut ret = Self::new_obj(props);
+        ret.val_symbol_props = Some(symbol_props.into_iter().collect());
+        ret
+    }
+
+    /// create a new EsValueFacade representing a live object in the script engine
+    /// when passed to the script engine the original object is used instead of a copy
+    pub fn new_object_ref(obj_ref: EsObjectRef) -> Self {
+        let mut ret = Self::undefined();
+        ret.val_object_ref = Some(obj_ref);
+        ret
+    }
+
+    /// create a new EsValueFacade representing a function which invokes a rust closure
+    /// when passed to the script engine this becomes a regular function which may be called any number of times,
+    /// the closure is dropped when all functions created from it have been garbage collected
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.eval_sync("this.test_new_function = function(cb){return cb(1) + cb(2);};", "test_new_function.es")
+    ///     .ok().expect("script failed");
+    /// let func = EsValueFacade::new_function(|args| {
+    ///     Ok(EsValueFacade::new_i32(args[0].get_i32() * 10))
+    /// });
+    /// let esvf = rt.call_sync(vec![], "test_new_function", vec![func]).ok().expect("call failed");
+    /// assert_eq!(esvf.get_i32(), &30);
+    /// ```
+    pub fn new_function<F>(func: F) -> Self
+    where
+        F: Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static,
+    {
+        Self::new_named_function("", func)
+    }
+
+    /// create a new EsValueFacade representing a named function which invokes a rust closure
+    /// the name is used as the name property of the function in script and in the error thrown
+    /// when the closure fails, see EsValueFacade::new_function
+    pub fn new_named_function<F>(name: &str, func: F) -> Self
+    where
+        F: Fn(Vec<EsValueFacade>) -> Result<EsValueFacade, String> + Send + Sync + 'static,
+    {
+        let mut ret = Self::undefined();
+        ret.val_rust_function = Some((name.to_string(), Arc::new(func)));
+        ret
+    }
+
+    /// create a new EsValueFacade representing an Error
+    /// if name is the name of a built-in Error subclass (e.g. TypeError) an instance of that class is created,
+    /// else an instance of Error is created and its name property is set to name
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.eval_sync("this.test_new_error = function(e){return (e instanceof TypeError) + ' ' + e.code;};", "test_new_error.es")
+    ///     .ok().expect("script failed");
+    /// let err = EsValueFacade::new_error("TypeError", "that's not my type", Some("E_TYPE"), None);
+    /// let esvf = rt.call_sync(vec![], "test_new_error", vec![err]).ok().expect("call failed");
+    /// assert_eq!(esvf.get_string(), "true E_TYPE");
+    /// ```
+    pub fn new_error(
+        name: &str,
+        message: &str,
+        code: Option<&str>,
+        data: Option<EsValueFacade>,
+    ) -> Self {
         let mut ret = Self::undefined();
-        ret.val_object = Some(props);
+        ret.val_error = Some(EsErrorDescription {
+            name: name.to_string(),
+            message: message.to_string(),
+            code: code.map(|c| c.to_string()),
+            data,
+        });
         ret
     }
 
@@ -164,6 +311,25 @@ impl EsValueFacade {
     pub fn new_promise<C>(resolver: C) -> EsValueFacade
     where
         C: FnOnce() -> Result<EsValueFacade, String> + Send + 'static,
+    {
+        Self::new_promise_esvf(move || resolver().map_err(EsValueFacade::new_str))
+    }
+
+    /// create a new EsValueFacade representing a Promise, this works like new_promise but the promise is
+    /// rejected with an EsValueFacade, you can use EsValueFacade::new_error to reject the promise with an Error
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let esvf_prom = EsValueFacade::new_promise_esvf(|| {
+    ///     Err(EsValueFacade::new_error("RangeError", "value out of range", Some("E_RANGE"), None))
+    /// });
+    /// ```
+    pub fn new_promise_esvf<C>(resolver: C) -> EsValueFacade
+    where
+        C: FnOnce() -> Result<EsValueFacade, EsValueFacade> + Send + 'static,
     {
         // create a lazy_static map in a Mutex
         // the mutex contains a Map<usize, Either<Result<EsValueFacade, EsErrorInfo>, EsPersistentRooted>>
@@ -198,109 +364,160 @@ impl EsValueFacade {
             trace!("running prom reso task for {}", id);
             let res = resolver();
             trace!("got prom result for {}, ok={}", id, res.is_ok());
-            let either_opt: Option<(PromiseResultContainer, Result<EsValueFacade, String>)> = {
-                // locked scope
-                let map: &mut PromiseAnswersMap = &mut PROMISE_ANSWERS.lock("in_task").unwrap();
-
-                if map.contains_key(&id) {
-                    let val = map.get(&id).unwrap();
-                    if val.is_none() {
-                        trace!("PROMISE_ANSWERS had Some for {} setting to val", id);
-                        // set result in left
-                        let new_val = Some(Either::Left(res));
-                        map.replace(&id, new_val);
-                        None
-                    } else {
-                        trace!("PROMISE_ANSWERS had Some resolve promise in right");
-                        // resolve promise in right
-                        // we are in a different thread here
-                        // we need a weakref to the runtime here, os we can run in the es thread
-                        // will be stored in a tuple with the EsPersisistentRooted
+            Self::settle_prepped_promise(id, res);
+        };
 
-                        let eith = map.remove(&id).unwrap();
+        trace!("spawning prom reso task for {}", id);
 
-                        Some((eith, res))
+        // run task
+        EsRuntime::add_helper_task(task);
 
-                        // eith and thus EsPersistentRooted is dropped here
-                    }
-                } else {
-                    // EsValueFacade was dropped before instantiating a promise obj
-                    // do nothing
-                    trace!("PROMISE_ANSWERS had no val for {}", id);
+        let mut ret = Self::undefined();
+
+        ret.val_promise = Some(id);
+
+        ret
+    }
+
+    /// resolve or reject a promise created by new_promise or new_deferred
+    /// if the promise object was not created yet the result is stored until to_es_value is called
+    fn settle_prepped_promise(id: usize, res: Result<EsValueFacade, EsValueFacade>) {
+        let either_opt: Option<(PromiseResultContainer, Result<EsValueFacade, EsValueFacade>)> = {
+            // locked scope
+            let map: &mut PromiseAnswersMap = &mut PROMISE_ANSWERS.lock("in_task").unwrap();
+
+            if map.contains_key(&id) {
+                let val = map.get(&id).unwrap();
+                if val.is_none() {
+                    trace!("PROMISE_ANSWERS had Some for {} setting to val", id);
+                    // set result in left
+                    let new_val = Some(Either::Left(res));
+                    map.replace(&id, new_val);
                     None
+                } else {
+                    trace!("PROMISE_ANSWERS had Some resolve promise in right");
+                    // resolve promise in right
+                    // we are probably in a different thread here
+                    // we need a weakref to the runtime here, os we can run in the es thread
+                    // will be stored in a tuple with the EsPersisistentRooted
+
+                    let eith = map.remove(&id).unwrap();
+
+                    Some((eith, res))
+
+                    // eith and thus EsPersistentRooted is dropped here
                 }
-            }; // end of locked scope
-
-            if let Some((eith, res)) = either_opt {
-                if eith.is_right() {
-                    // in our right we have a rooted promise and a weakref to our runtimeinner
-                    let (prom_regged_id, weak_rt_ref) = eith.right().unwrap();
-                    trace!("found promise with id {} in right", prom_regged_id);
-
-                    let rt_opt = weak_rt_ref.upgrade();
-                    if let Some(rti) = rt_opt {
-                        rti.do_in_es_runtime_thread_sync(Box::new(move |sm_rt: &SmRuntime| {
-                            // resolve or reject promise
-                            sm_rt.do_with_jsapi(move|_rt, cx, _global| {
-
-                                let prom_obj: *mut JSObject = {
-                                    let prom_epr: EsPersistentRooted = spidermonkeyruntimewrapper::consume_cached_object(prom_regged_id);
-                                    trace!("epr should drop here");
-                                    prom_epr.get()
-                                };
-                                trace!("epr should be dropped here");
-                                rooted!(in (cx) let mut prom_obj_root = prom_obj);
-                                trace!("rooted promise");
-
-                                if res.is_ok() {
-                                    trace!("rooting result");
-                                    rooted!(in (cx) let res_root = res.ok().unwrap().to_es_value(cx));
-                                    trace!("resolving prom");
-                                    let resolve_prom_res = jsapi_utils::promises::resolve_promise(
-                                        cx,
-                                        prom_obj_root.handle(),
-                                        res_root.handle(),
+            } else {
+                // EsValueFacade was dropped before instantiating a promise obj
+                // do nothing
+                trace!("PROMISE_ANSWERS had no val for {}", id);
+                None
+            }
+        }; // end of locked scope
+
+        if let Some((eith, res)) = either_opt {
+            if eith.is_right() {
+                // in our right we have a rooted promise and a weakref to our runtimeinner
+                let (prom_regged_id, weak_rt_ref) = eith.right().unwrap();
+                trace!("found promise with id {} in right", prom_regged_id);
+
+                let rt_opt = weak_rt_ref.upgrade();
+                if let Some(rti) = rt_opt {
+                    rti.do_in_es_runtime_thread_sync(Box::new(move |sm_rt: &SmRuntime| {
+                        // resolve or reject promise
+                        sm_rt.do_with_jsapi(move |_rt, cx, _global| {
+                            let prom_obj: *mut JSObject = {
+                                let prom_epr: EsPersistentRooted =
+                                    spidermonkeyruntimewrapper::consume_cached_object(
+                                        prom_regged_id,
                                     );
-                                    if resolve_prom_res.is_err() {
-                                        panic!("could not resolve promise {} because of error: {}", prom_regged_id, resolve_prom_res.err().unwrap().err_msg());
-                                    }
-                                } else {
-                                    trace!("rooting err result");
-                                    let err_str = res.err().unwrap();
-                                    let err_val = jsapi_utils::new_es_value_from_str(cx, err_str.as_str());
-                                    rooted!(in (cx) let res_root = err_val);
-                                    trace!("rejecting prom");
-                                    let reject_prom_res = jsapi_utils::promises::reject_promise(
-                                        cx,
-                                        prom_obj_root.handle(),
-                                        res_root.handle(),
+                                trace!("epr should drop here");
+                                prom_epr.get()
+                            };
+                            trace!("epr should be dropped here");
+                            rooted!(in (cx) let mut prom_obj_root = prom_obj);
+                            trace!("rooted promise");
+
+                            if res.is_ok() {
+                                trace!("rooting result");
+                                rooted!(in (cx) let res_root = res.ok().unwrap().to_es_value(cx));
+                                trace!("resolving prom");
+                                let resolve_prom_res = jsapi_utils::promises::resolve_promise(
+                                    cx,
+                                    prom_obj_root.handle(),
+                                    res_root.handle(),
+                                );
+                                if resolve_prom_res.is_err() {
+                                    panic!(
+                                        "could not resolve promise {} because of error: {}",
+                                        prom_regged_id,
+                                        resolve_prom_res.err().unwrap().err_msg()
                                     );
-                                    if reject_prom_res.is_err() {
-                                        panic!("could not reject promise {} because of error: {}", prom_regged_id, reject_prom_res.err().unwrap().err_msg());
-                                    }
                                 }
-                            });
-                        }));
-                    } else {
-                        trace!("rt was dropped before getting val for {}", id);
-                    }
+                            } else {
+                                trace!("rooting err result");
+                                rooted!(in (cx) let res_root = res.err().unwrap().to_es_value(cx));
+                                trace!("rejecting prom");
+                                let reject_prom_res = jsapi_utils::promises::reject_promise(
+                                    cx,
+                                    prom_obj_root.handle(),
+                                    res_root.handle(),
+                                );
+                                if reject_prom_res.is_err() {
+                                    panic!(
+                                        "could not reject promise {} because of error: {}",
+                                        prom_regged_id,
+                                        reject_prom_res.err().unwrap().err_msg()
+                                    );
+                                }
+                            }
+                        });
+                    }));
                 } else {
-                    // wtf
-                    panic!("eith had unexpected left");
+                    trace!("rt was dropped before getting val for {}", id);
                 }
+            } else {
+                // wtf
+                panic!("eith had unexpected left");
             }
-        };
+        }
+    }
 
-        trace!("spawning prom reso task for {}", id);
+    /// create a new EsValueFacade representing a Promise which is resolved later by calling resolve or reject
+    /// on the returned EsPromiseResolver, the resolver may be sent to and used from any thread
+    ///
+    /// if the resolver is dropped without being used the promise is rejected
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::esvaluefacade::EsValueFacade;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.eval_sync("this.test_new_deferred = function(p){p.then((res) => {console.log('p resolved with %s', res);});};",
+    ///     "test_new_deferred.es").ok().expect("script failed");
+    /// let (esvf_prom, resolver) = EsValueFacade::new_deferred();
+    /// rt.call_sync(vec![], "test_new_deferred", vec![esvf_prom]).ok().expect("call failed");
+    /// std::thread::spawn(move || {
+    ///     // e.g. wait for a socket here
+    ///     resolver.resolve(EsValueFacade::new_i32(123));
+    /// });
+    /// ```
+    pub fn new_deferred() -> (EsValueFacade, EsPromiseResolver) {
+        let id = {
+            // locked scope
+            let map: &mut PromiseAnswersMap = &mut PROMISE_ANSWERS.lock("new_deferred").unwrap();
 
-        // run task
-        EsRuntime::add_helper_task(task);
+            map.insert(None)
+        }; // end locked scope
 
-        let mut ret = Self::undefined();
+        trace!("prepping deferred promise {}", id);
 
-        ret.val_promise = Some(id);
+        let mut esvf = Self::undefined();
+        esvf.val_promise = Some(id);
 
-        ret
+        (esvf, EsPromiseResolver { id, settled: false })
     }
 
     pub(crate) fn new_v(
@@ -308,6 +525,37 @@ impl EsValueFacade {
         context: *mut JSContext,
         global: HandleObject,
         rval_handle: HandleValue,
+    ) -> Self {
+        // see which props the EsRuntime wants us to copy
+        let (include_symbols, include_non_enumerable) =
+            spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+                let sm_rt: &SmRuntime = &*sm_rt_rc.borrow();
+                if let Some(inner) = sm_rt.opt_esrt_inner.as_ref().and_then(|w| w.upgrade()) {
+                    (
+                        inner.include_symbol_props,
+                        inner.include_non_enumerable_props,
+                    )
+                } else {
+                    (false, false)
+                }
+            });
+        Self::new_v2(
+            rt,
+            context,
+            global,
+            rval_handle,
+            include_symbols,
+            include_non_enumerable,
+        )
+    }
+
+    fn new_v2(
+        rt: &Runtime,
+        context: *mut JSContext,
+        global: HandleObject,
+        rval_handle: HandleValue,
+        include_symbols: bool,
+        include_non_enumerable: bool,
     ) -> Self {
         let mut val_string = None;
         let mut val_i32 = None;
@@ -315,8 +563,10 @@ impl EsValueFacade {
         let mut val_boolean = None;
         let mut val_managed_var = None;
         let mut val_object = None;
+        let mut val_symbol_props = None;
         let mut val_array = None;
         let mut val_js_function = None;
+        let mut val_proxy_instance = None;
 
         let rval: JSVal = *rval_handle;
 
@@ -332,8 +582,11 @@ impl EsValueFacade {
             trace!("EsValueFacade::new got string {}", es_str);
 
             val_string = Some(es_str);
+        } else if rval.is_object() && jsapi_utils::reflection::is_proxy_instance(rval.to_object()) {
+            // instances of proxy classes are passed by reference instead of copying their props
+            val_proxy_instance = Some(EsProxyInstanceRef::new(context, rval.to_object()));
         } else if rval.is_object() {
-            let mut map = HashMap::new();
+            let mut map = IndexMap::new();
             let obj: *mut JSObject = rval.to_object();
             rooted!(in(context) let obj_root = obj);
 
@@ -356,55 +609,64 @@ impl EsValueFacade {
                             get_res.err().unwrap().err_msg()
                         );
                     }
-                    vals.push(EsValueFacade::new_v(
+                    vals.push(EsValueFacade::new_v2(
                         rt,
                         context,
                         global,
                         arr_element_root.handle(),
+                        include_symbols,
+                        include_non_enumerable,
                     ));
                 }
 
                 val_array = Some(vals);
             } else if jsapi_utils::promises::object_is_promise(context, obj_root.handle()) {
-                // call esses.registerPromiseForResolutionInRust(prom);
+                // add native reactions to the promise which complete a future on settlement
+                let obj_id = NEXT_MANAGED_VAR_ID.with(|rc| {
+                    let id = &mut *rc.borrow_mut();
+                    *id += 1;
+                    *id
+                });
 
-                rooted!(in (context) let mut id_val = UndefinedValue());
+                let (promise_future, resolver) = new_es_future(promise_dropped);
+                // only one of the reactions will be called, they share the resolver
+                let resolver_rc = Rc::new(RefCell::new(Some(resolver)));
+                let reject_resolver_rc = resolver_rc.clone();
+                let failed_resolver_rc = resolver_rc.clone();
 
-                // ok it's a promise, now we're gonna call a method which will add then and catch to
-                // the promise so the result is reported to rust under an id
-                let reg_res: Result<(), EsErrorInfo> = jsapi_utils::functions::call_obj_method_name(
+                let reactions_res = jsapi_utils::promises::add_promise_reactions(
                     context,
-                    global,
-                    vec!["esses"],
-                    "registerPromiseForResolutionInRust",
-                    vec![rval],
-                    id_val.handle_mut(),
+                    obj_root.handle(),
+                    move |_cx, val| {
+                        if let Some(resolver) = resolver_rc.borrow_mut().take() {
+                            trace!("resolving future from promise from esvf obj_id:{}", obj_id);
+                            resolver.resolve(Ok(EsValueFacade::new_v_in_current_rt(val)));
+                        }
+                    },
+                    move |_cx, val| {
+                        if let Some(resolver) = reject_resolver_rc.borrow_mut().take() {
+                            trace!("rejecting future from promise from esvf obj_id:{}", obj_id);
+                            resolver.resolve(Err(EsValueFacade::new_v_in_current_rt(val)));
+                        }
+                    },
                 );
 
-                if reg_res.is_err() {
-                    panic!(
-                        "could not reg promise due to error {}",
-                        reg_res.err().unwrap().err_msg()
-                    );
-                } else {
-                    let obj_id = id_val.to_int32();
-
-                    let (tx, rx) = channel();
-                    let opt_receiver = Some(rx);
-
-                    PROMISE_RESOLUTION_TRANSMITTERS.with(move |rc| {
-                        let map: &mut HashMap<i32, Sender<Result<EsValueFacade, EsValueFacade>>> =
-                            &mut *rc.borrow_mut();
-                        map.insert(obj_id, tx);
-                    });
+                if let Err(err) = reactions_res {
+                    // the future is rejected so waiting for it does not hang
+                    if let Some(resolver) = failed_resolver_rc.borrow_mut().take() {
+                        resolver.resolve(Err(EsValueFacade::new_str(format!(
+                            "could not add promise reactions due to error {}",
+                            err.err_msg()
+                        ))));
+                    }
+                }
 
-                    let rmev: RustManagedEsVar = RustManagedEsVar {
-                        obj_id,
-                        opt_receiver,
-                    };
+                let rmev: RustManagedEsVar = RustManagedEsVar {
+                    obj_id,
+                    promise_future: Mutex::new(Some(promise_future)),
+                };
 
-                    val_managed_var = Some(rmev);
-                }
+                val_managed_var = Some(rmev);
             } else if jsapi_utils::functions::object_is_function(obj) {
                 // wrap function in persistentrooted
 
@@ -415,28 +677,41 @@ impl EsValueFacade {
                 let cached_id = spidermonkeyruntimewrapper::register_cached_object(context, obj);
                 val_js_function = Some((cached_id, rti_ref));
             } else {
-                let prop_names: Vec<String> =
-                    objects::get_js_obj_prop_names(context, obj_root.handle());
-                for prop_name in prop_names {
-                    rooted!(in (context) let mut prop_val_root = UndefinedValue());
-                    let prop_val_res = objects::get_es_obj_prop_val(
-                        context,
-                        obj_root.handle(),
-                        prop_name.as_str(),
-                        prop_val_root.handle_mut(),
-                    );
-
-                    if prop_val_res.is_err() {
-                        panic!(
-                            "error getting prop {}: {}",
-                            prop_name,
-                            prop_val_res.err().unwrap().err_msg()
+                let mut symbol_props = vec![];
+                let props_res = objects::get_js_obj_props(
+                    context,
+                    obj_root.handle(),
+                    include_symbols,
+                    include_non_enumerable,
+                    |key, prop_val_handle| {
+                        let prop_esvf = EsValueFacade::new_v2(
+                            rt,
+                            context,
+                            global,
+                            prop_val_handle,
+                            include_symbols,
+                            include_non_enumerable,
                         );
-                    }
+                        match key {
+                            objects::PropertyKeyName::Str(prop_name) => {
+                                map.insert(prop_name, prop_esvf);
+                            }
+                            objects::PropertyKeyName::Symbol(descr) => {
+                                symbol_props.push((descr, prop_esvf));
+                            }
+                        }
+                    },
+                );
+
+                if props_res.is_err() {
+                    panic!(
+                        "error getting props: {}",
+                        props_res.err().unwrap().err_msg()
+                    );
+                }
 
-                    let prop_esvf =
-                        EsValueFacade::new_v(rt, context, global, prop_val_root.handle());
-                    map.insert(prop_name, prop_esvf);
+                if include_symbols {
+                    val_symbol_props = Some(symbol_props);
                 }
             }
 
@@ -450,12 +725,42 @@ impl EsValueFacade {
             val_boolean,
             val_managed_var,
             val_object,
+            val_symbol_props,
             val_array,
             val_promise: None,
             val_js_function,
+            val_object_ref: None,
+            val_proxy_instance,
+            val_rust_function: None,
+            val_error: None,
         }
     }
 
+    /// create a new EsValueFacade from a value in the current runtime
+    /// this may only be called from the worker thread of an EsRuntime
+    pub(crate) fn new_v_in_current_rt(val: HandleValue) -> Self {
+        spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+            let sm_rt = &*sm_rt_rc.borrow();
+            sm_rt.do_with_jsapi(|rt, cx, global| EsValueFacade::new_v(rt, cx, global, val))
+        })
+    }
+
+    /// convert the arguments of a native function call to a Vec of EsValueFacades
+    pub(crate) fn new_v_vec_from_call_args(args: &CallArgs) -> Vec<EsValueFacade> {
+        let mut args_vec = vec![];
+        spidermonkeyruntimewrapper::SM_RT.with(|sm_rt_rc| {
+            let sm_rt = &*sm_rt_rc.borrow();
+            sm_rt.do_with_jsapi(|rt, cx, global| {
+                for x in 0..args.argc_ {
+                    let arg = args.get(x);
+                    let var_arg: HandleValue = unsafe { mozjs::rust::Handle::from_raw(arg) };
+                    args_vec.push(EsValueFacade::new_v(rt, cx, global, var_arg));
+                }
+            })
+        });
+        args_vec
+    }
+
     /// get the String value
     pub fn get_string(&self) -> &String {
         self.val_string.as_ref().expect("not a string")
@@ -521,11 +826,71 @@ impl EsValueFacade {
         }
 
         let rmev: &RustManagedEsVar = self.val_managed_var.as_ref().expect("not a managed var");
-        let rx = rmev.opt_receiver.as_ref().expect("not a waiting promise");
-        rx.recv_timeout(timeout)
+        let promise_future = &mut *rmev.promise_future.lock().unwrap();
+        match promise_future.take() {
+            None => Ok(promise_result_consumed()),
+            Some(fut) => match fut.get_result_blocking(timeout) {
+                Ok(result) => Ok(result),
+                Err(fut) => {
+                    // put it back so it can be waited for again
+                    promise_future.replace(fut);
+                    Err(RecvTimeoutError::Timeout)
+                }
+            },
+        }
+    }
+
+    /// get a Future for the result of a promise, this can be awaited in any async runtime
+    /// the result can only be consumed once, either by this Future, by get_promise_result_blocking or
+    /// by on_settled, when it was already consumed the Future completes with an Err
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use futures::executor::block_on;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let esvf_prom = rt.eval_sync(
+    ///     "let p = new Promise((resolve, reject) => {setImmediate(() => {resolve(123);});}); p;",
+    ///     "test_get_promise_future.es").ok().expect("script failed");
+    /// let esvf = block_on(esvf_prom.get_promise_future()).ok().expect("promise was rejected");
+    /// assert_eq!(esvf.get_i32(), &123);
+    /// ```
+    pub fn get_promise_future(&self) -> EsFuture<Result<EsValueFacade, EsValueFacade>> {
+        if !self.is_promise() {
+            return EsFuture::new_resolved(Err(EsValueFacade::new_str(
+                "esvf was not a Promise".to_string(),
+            )));
+        }
+
+        let rmev: &RustManagedEsVar = self.val_managed_var.as_ref().expect("not a managed var");
+        let promise_future = rmev.promise_future.lock().unwrap().take();
+        promise_future.unwrap_or_else(|| EsFuture::new_resolved(promise_result_consumed()))
+    }
+
+    /// invoke a callback when a promise is settled, the callback receives Ok(value) if the promise was
+    /// resolved or Err(value) if it was rejected
+    /// the callback is usually invoked from the worker thread of the EsRuntime so it should not block,
+    /// like get_promise_future this consumes the result of the promise, so if the result was already
+    /// consumed (e.g. by an earlier on_settled) the callback is invoked immediately with an Err
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// let esvf_prom = rt.eval_sync("Promise.resolve(123);", "test_on_settled.es").ok().expect("script failed");
+    /// esvf_prom.on_settled(|res| {
+    ///     println!("promise resolved to {}", res.ok().expect("promise was rejected").get_i32());
+    /// });
+    /// ```
+    pub fn on_settled<C>(&self, callback: C)
+    where
+        C: FnOnce(Result<EsValueFacade, EsValueFacade>) + Send + 'static,
+    {
+        self.get_promise_future().on_resolved(callback);
     }
 
     /// get the value as a Map of EsValueFacades, this works when the value was an object in the script engine
+    /// the entries are in the order in which the script engine enumerates the properties of the object
     /// # Example
     /// ```no_run
     /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
@@ -536,10 +901,34 @@ impl EsValueFacade {
     /// assert!(map.contains_key("a"));
     /// assert!(map.contains_key("b"));
     /// ```
-    pub fn get_object(&self) -> &HashMap<String, EsValueFacade> {
+    pub fn get_object(&self) -> &IndexMap<String, EsValueFacade> {
         self.val_object.as_ref().unwrap()
     }
 
+    /// get the Symbol keyed props of an object as a Vec of (description, value) tuples
+    /// objects returned from the script engine only contain these if the EsRuntime was built with
+    /// EsRuntimeBuilder::include_symbol_props(true)
+    ///
+    /// only props keyed by a registered Symbol (Symbol.for(description)) or a well known Symbol
+    /// (with a description like "Symbol.iterator") are included, props keyed by a unique Symbol
+    /// (Symbol(description)) are skipped because that Symbol can not be recreated from its description
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new().include_symbol_props(true).build();
+    /// let esvf = rt.eval_sync("({a: 1, [Symbol.for('b')]: 2, [Symbol('c')]: 3});", "test_get_symbol_props.es").ok().expect("script failed");
+    /// let symbol_props = esvf.get_symbol_props();
+    /// assert_eq!(symbol_props.len(), 1);
+    /// assert_eq!(symbol_props[0].0.as_str(), "b");
+    /// ```
+    pub fn get_symbol_props(&self) -> &[(String, EsValueFacade)] {
+        match self.val_symbol_props.as_ref() {
+            Some(symbol_props) => symbol_props.as_slice(),
+            None => &[],
+        }
+    }
+
     /// get the value as a Vec of EsValueFacades, this works when the value was an array in the script engine
     /// # Example
     /// ```no_run
@@ -555,6 +944,16 @@ impl EsValueFacade {
         self.val_array.as_ref().unwrap()
     }
 
+    /// consume the EsValueFacade and get the Vec of EsValueFacades it represents, this panics if the value is not an array
+    pub fn into_array(mut self) -> Vec<EsValueFacade> {
+        self.val_array.take().unwrap()
+    }
+
+    /// consume the EsValueFacade and get the map of properties it represents, this panics if the value is not an object
+    pub fn into_object(mut self) -> IndexMap<String, EsValueFacade> {
+        self.val_object.take().unwrap()
+    }
+
     /// invoke the function that was returned from the script engine
     /// # Example
     /// ```no_run
@@ -573,6 +972,15 @@ impl EsValueFacade {
     /// ```
     pub fn invoke_function(&self, args: Vec<EsValueFacade>) -> Result<EsValueFacade, EsErrorInfo> {
         trace!("EsValueFacade.invoke_function()");
+        if let Some((_name, rust_func)) = self.val_rust_function.as_ref() {
+            // no need to bother the script engine
+            return rust_func(args).map_err(|err| EsErrorInfo {
+                message: err,
+                filename: "".to_string(),
+                lineno: 0,
+                column: 0,
+            });
+        }
         let rt_arc = self.val_js_function.as_ref().unwrap().1.clone();
         let cached_id = self.val_js_function.as_ref().unwrap().0;
 
@@ -626,6 +1034,23 @@ impl EsValueFacade {
         })
     }
 
+    /// check if the value is undefined (or null), this is the case when no other value is set
+    pub fn is_undefined(&self) -> bool {
+        self.val_string.is_none()
+            && self.val_i32.is_none()
+            && self.val_f64.is_none()
+            && self.val_boolean.is_none()
+            && self.val_managed_var.is_none()
+            && self.val_object.is_none()
+            && self.val_array.is_none()
+            && self.val_promise.is_none()
+            && self.val_js_function.is_none()
+            && self.val_object_ref.is_none()
+            && self.val_proxy_instance.is_none()
+            && self.val_rust_function.is_none()
+            && self.val_error.is_none()
+    }
+
     /// check if the value is a String
     pub fn is_string(&self) -> bool {
         self.val_string.is_some()
@@ -666,6 +1091,43 @@ impl EsValueFacade {
         self.val_js_function.is_some()
     }
 
+    /// check if the value is a rust closure created with EsValueFacade::new_function
+    pub fn is_rust_function(&self) -> bool {
+        self.val_rust_function.is_some()
+    }
+
+    /// check if the value is an Error created with EsValueFacade::new_error
+    pub fn is_error(&self) -> bool {
+        self.val_error.is_some()
+    }
+
+    /// get the description of an Error created with EsValueFacade::new_error
+    pub fn get_error(&self) -> &EsErrorDescription {
+        self.val_error.as_ref().expect("not an error")
+    }
+
+    /// check if the value is a live EsObjectRef
+    pub fn is_object_ref(&self) -> bool {
+        self.val_object_ref.is_some()
+    }
+
+    /// get the EsObjectRef, this works when the value was created with EsValueFacade::new_object_ref
+    pub fn get_object_ref(&self) -> &EsObjectRef {
+        self.val_object_ref.as_ref().unwrap()
+    }
+
+    /// check if the value is an instance of a proxy class which was passed from script
+    pub fn is_proxy_instance(&self) -> bool {
+        self.val_proxy_instance.is_some()
+    }
+
+    /// get the reference to the proxy instance, this works when is_proxy_instance() is true
+    pub fn get_proxy_instance(&self) -> &EsProxyInstanceRef {
+        self.val_proxy_instance
+            .as_ref()
+            .expect("not a proxy instance")
+    }
+
     pub fn as_js_expression_str(&self) -> String {
         if self.is_boolean() {
             if self.get_boolean() {
@@ -750,8 +1212,60 @@ impl EsValueFacade {
                     val_root.handle(),
                 );
             }
+            for (descr, prop_esvf) in self.get_symbol_props() {
+                rooted!(in(context) let mut val_root = prop_esvf.to_es_value(context));
+                let set_res = jsapi_utils::objects::set_es_obj_symbol_prop_val(
+                    context,
+                    obj_root.handle(),
+                    descr.as_str(),
+                    val_root.handle(),
+                );
+                if set_res.is_err() {
+                    panic!(
+                        "could not set symbol prop {}: {}",
+                        descr,
+                        set_res.err().unwrap().err_msg()
+                    );
+                }
+            }
 
             ObjectValue(obj)
+        } else if self.is_error() {
+            self.to_es_error_value(context)
+        } else if self.is_object_ref() {
+            ObjectValue(self.get_object_ref().get_obj())
+        } else if self.is_proxy_instance() {
+            ObjectValue(self.get_proxy_instance().get_object_ref().get_obj())
+        } else if self.is_rust_function() {
+            let (name, rust_func) = self.val_rust_function.as_ref().unwrap().clone();
+            let func_obj = jsapi_utils::functions::new_callback_function(
+                context,
+                name.as_str(),
+                0,
+                move |cx, args| {
+                    let args_vec = Self::new_v_vec_from_call_args(&args);
+                    match rust_func(args_vec) {
+                        Ok(esvf) => {
+                            args.rval().set(esvf.to_es_value(cx));
+                            true
+                        }
+                        Err(js_err) => {
+                            let display_name = if name.is_empty() {
+                                "<anonymous>"
+                            } else {
+                                name.as_str()
+                            };
+                            let s = format!(
+                                "function {} failed\ncaused by: {}\0",
+                                display_name, js_err
+                            );
+                            unsafe { JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char) };
+                            false
+                        }
+                    }
+                },
+            );
+            ObjectValue(func_obj)
         } else if self.is_prepped_promise() {
             self.to_es_promise_value(context)
 
@@ -763,6 +1277,92 @@ impl EsValueFacade {
         }
     }
 
+    fn to_es_error_value(&self, context: *mut JSContext) -> JSVal {
+        let err_desc = self.get_error();
+        rooted!(in (context) let global_root = unsafe { mozjs::jsapi::CurrentGlobalOrNull(context) });
+
+        // only the built-in Error types are constructed by name, anything else is an Error
+        // with a name property
+        rooted!(in (context) let mut constructor_root = UndefinedValue());
+        let is_builtin = BUILTIN_ERROR_TYPES.contains(&err_desc.name.as_str());
+        let constructor_name = if is_builtin {
+            err_desc.name.as_str()
+        } else {
+            "Error"
+        };
+        let get_res = objects::get_es_obj_prop_val(
+            context,
+            global_root.handle(),
+            constructor_name,
+            constructor_root.handle_mut(),
+        );
+        if get_res.is_err()
+            || !constructor_root.is_object()
+            || !jsapi_utils::functions::object_is_function(constructor_root.to_object())
+        {
+            unsafe { mozjs::jsapi::JS_ClearPendingException(context) };
+            log::warn!(
+                "could not get {}, passing error as string: {}",
+                constructor_name,
+                err_desc.message
+            );
+            return jsapi_utils::new_es_value_from_str(context, err_desc.message.as_str());
+        }
+
+        rooted!(in (context) let mut err_obj_root = mozjs::jsval::NullValue().to_object_or_null());
+        let construct_res = spidermonkeyruntimewrapper::do_with_rooted_esvf_vec(
+            context,
+            vec![EsValueFacade::new_str(err_desc.message.clone())],
+            |hva| {
+                objects::new_from_constructor(
+                    context,
+                    constructor_root.handle(),
+                    hva,
+                    err_obj_root.handle_mut(),
+                )
+            },
+        );
+        if let Err(e) = construct_res {
+            unsafe { mozjs::jsapi::JS_ClearPendingException(context) };
+            log::warn!(
+                "could not construct {}, passing error as string: {}",
+                constructor_name,
+                e.err_msg()
+            );
+            return jsapi_utils::new_es_value_from_str(context, err_desc.message.as_str());
+        }
+
+        if !is_builtin {
+            rooted!(in (context) let name_root = jsapi_utils::new_es_value_from_str(context, err_desc.name.as_str()));
+            objects::set_es_obj_prop_val(
+                context,
+                err_obj_root.handle(),
+                "name",
+                name_root.handle(),
+            );
+        }
+        if let Some(code) = err_desc.code.as_ref() {
+            rooted!(in (context) let code_root = jsapi_utils::new_es_value_from_str(context, code.as_str()));
+            objects::set_es_obj_prop_val(
+                context,
+                err_obj_root.handle(),
+                "code",
+                code_root.handle(),
+            );
+        }
+        if let Some(data) = err_desc.data.as_ref() {
+            rooted!(in (context) let data_root = data.to_es_value(context));
+            objects::set_es_obj_prop_val(
+                context,
+                err_obj_root.handle(),
+                "data",
+                data_root.handle(),
+            );
+        }
+
+        ObjectValue(*err_obj_root)
+    }
+
     fn to_es_promise_value(&self, context: *mut JSContext) -> JSVal {
         trace!("to_es_value.7 prepped_promise");
         let map: &mut PromiseAnswersMap = &mut PROMISE_ANSWERS.lock("to_es_value.7").unwrap();
@@ -810,9 +1410,7 @@ impl EsValueFacade {
                         }
                     } else {
                         // reject prom
-                        let err_str = res.err().unwrap();
-                        let err_val = jsapi_utils::new_es_value_from_str(context, err_str.as_str());
-                        rooted!(in (context) let res_root = err_val);
+                        rooted!(in (context) let res_root = res.err().unwrap().to_es_value(context));
 
                         let prom_reje_res = jsapi_utils::promises::reject_promise(
                             context,
@@ -837,7 +1435,43 @@ impl EsValueFacade {
     }
 }
 
-type PromiseResultContainer = Either<Result<EsValueFacade, String>, (usize, Weak<EsRuntimeInner>)>;
+type PromiseResultContainer =
+    Either<Result<EsValueFacade, EsValueFacade>, (usize, Weak<EsRuntimeInner>)>;
+
+/// the resolver for a promise created by EsValueFacade::new_deferred
+pub struct EsPromiseResolver {
+    id: usize,
+    settled: bool,
+}
+
+impl EsPromiseResolver {
+    /// resolve the promise with a value
+    pub fn resolve(mut self, val: EsValueFacade) {
+        self.settled = true;
+        EsValueFacade::settle_prepped_promise(self.id, Ok(val));
+    }
+
+    /// reject the promise with a value
+    pub fn reject(mut self, err: EsValueFacade) {
+        self.settled = true;
+        EsValueFacade::settle_prepped_promise(self.id, Err(err));
+    }
+}
+
+impl Drop for EsPromiseResolver {
+    fn drop(&mut self) {
+        if !self.settled {
+            trace!("EsPromiseResolver {} dropped without result", self.id);
+            EsValueFacade::settle_prepped_promise(
+                self.id,
+                Err(EsValueFacade::new_str(
+                    "promise resolver was dropped without a result".to_string(),
+                )),
+            );
+        }
+    }
+}
+
 type PromiseResultContainerOption = Option<PromiseResultContainer>;
 
 impl Drop for EsValueFacade {
@@ -848,7 +1482,8 @@ impl Drop for EsValueFacade {
                 &mut PROMISE_ANSWERS.lock("EsValueFacade::drop").unwrap();
             let id = self.val_promise.as_ref().unwrap();
             if let Some(opt) = map.get(id) {
-                if opt.is_none() {
+                // also remove the answer if it was already there but the promise was never passed to script
+                if opt.is_none() || opt.as_ref().unwrap().is_left() {
                     map.remove(id);
                 }
             }
@@ -870,8 +1505,9 @@ mod tests {
     use crate::esruntimeinner::EsRuntimeInner;
     use crate::esvaluefacade::EsValueFacade;
     use crate::jsapi_utils::EsErrorInfo;
+    use indexmap::IndexMap;
     use std::collections::HashMap;
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     #[test]
@@ -1046,6 +1682,163 @@ mod tests {
         assert_eq!(esvf_prom_resolved.get_i32().clone(), 123 as i32);
     }
 
+    #[test]
+    fn test_promise_future() {
+        log::info!("test: test_promise_future");
+
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let res = futures::executor::block_on(async {
+            let esvf_prom = rt
+                .eval_async(
+                    "new Promise((resolve, reject) => {setImmediate(() => {resolve(321);});});",
+                    "test_promise_future.es",
+                )
+                .await
+                .ok()
+                .expect("script failed");
+            assert!(esvf_prom.is_promise());
+            esvf_prom.get_promise_future().await
+        });
+
+        let esvf = res.ok().expect("promise was rejected");
+        assert_eq!(esvf.get_i32(), &321);
+
+        let rejected = futures::executor::block_on(async {
+            let esvf_prom = rt
+                .eval_async(
+                    "Promise.reject('test_promise_future rejected');",
+                    "test_promise_future2.es",
+                )
+                .await
+                .ok()
+                .expect("script failed");
+            esvf_prom.get_promise_future().await
+        });
+        assert_eq!(
+            rejected
+                .err()
+                .expect("promise was not rejected")
+                .get_string(),
+            "test_promise_future rejected"
+        );
+    }
+
+    #[test]
+    fn test_promise_future_consumed() {
+        log::info!("test: test_promise_future_consumed");
+
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let esvf_prom = rt
+            .eval_sync("Promise.resolve(7);", "test_promise_future_consumed.es")
+            .ok()
+            .expect("script failed");
+        let first = futures::executor::block_on(esvf_prom.get_promise_future());
+        assert_eq!(first.ok().expect("promise was rejected").get_i32(), &7);
+
+        // a second consumer gets an error instead of waiting forever
+        let second = futures::executor::block_on(esvf_prom.get_promise_future());
+        assert_eq!(
+            second
+                .err()
+                .expect("second consumer got a result")
+                .get_string(),
+            "the result of the promise was already consumed"
+        );
+    }
+
+    #[test]
+    fn test_wait_for_prom_without_esses() {
+        log::info!("test: test_wait_for_prom_without_esses");
+
+        // promises are tracked natively so they should not depend on any script globals
+        let rt = crate::esruntimebuilder::EsRuntimeBuilder::new().build();
+        rt.eval_sync(
+            "this.esses = undefined;",
+            "test_wait_for_prom_without_esses.es",
+        )
+        .ok()
+        .unwrap();
+        let esvf_prom = rt
+            .eval_sync(
+                "Promise.resolve(5);",
+                "test_wait_for_prom_without_esses2.es",
+            )
+            .ok()
+            .unwrap();
+        assert!(esvf_prom.is_promise());
+        let res = esvf_prom
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(res.ok().unwrap().get_i32(), &5);
+
+        let esvf_prom = rt
+            .eval_sync(
+                "Promise.reject('nope');",
+                "test_wait_for_prom_without_esses3.es",
+            )
+            .ok()
+            .unwrap();
+        let res = esvf_prom
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(res.err().unwrap().get_string(), "nope");
+    }
+
+    #[test]
+    fn test_on_settled() {
+        log::info!("test: test_on_settled");
+
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let esvf_prom = rt
+            .eval_sync(
+                "new Promise((resolve, reject) => {setImmediate(() => {reject('test_on_settled');});});",
+                "test_on_settled.es",
+            )
+            .ok()
+            .unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        esvf_prom.on_settled(move |res| {
+            tx.send(res.err().unwrap().get_string().clone()).unwrap();
+        });
+        let rejection = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(rejection, "test_on_settled");
+    }
+
+    #[test]
+    fn test_on_settled_twice() {
+        log::info!("test: test_on_settled_twice");
+
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let esvf_prom = rt
+            .eval_sync(
+                "new Promise((resolve, reject) => {setImmediate(() => {resolve(12);});});",
+                "test_on_settled_twice.es",
+            )
+            .ok()
+            .unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx2 = tx.clone();
+        esvf_prom.on_settled(move |res| {
+            tx.send(res.map(|esvf| *esvf.get_i32()).map_err(|_e| ()))
+                .unwrap();
+        });
+        // the second callback is not lost and does not replace the first one
+        esvf_prom.on_settled(move |res| {
+            tx2.send(res.map(|esvf| *esvf.get_i32()).map_err(|_e| ()))
+                .unwrap();
+        });
+        let mut results = vec![
+            rx.recv_timeout(Duration::from_secs(10)).unwrap(),
+            rx.recv_timeout(Duration::from_secs(10)).unwrap(),
+        ];
+        results.sort();
+        assert_eq!(results, vec![Err(()), Ok(12)]);
+    }
+
     #[test]
     fn test_wait_for_prom2() {
         log::info!("test: test_wait_for_prom2");
@@ -1128,7 +1921,7 @@ mod tests {
 
         assert!(esvf.is_object());
 
-        let map: &HashMap<String, EsValueFacade> = esvf.get_object();
+        let map: &IndexMap<String, EsValueFacade> = esvf.get_object();
 
         let esvf_a = map.get(&"a".to_string()).unwrap();
 
@@ -1136,6 +1929,107 @@ mod tests {
         assert_eq!(esvf_a.get_i32(), &1);
     }
 
+    #[test]
+    fn test_object_prop_order() {
+        log::info!("test: test_object_prop_order");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let esvf = rt
+            .eval_sync("({b: 1, a: 2, c: 3});", "test_object_prop_order.es")
+            .ok()
+            .unwrap();
+
+        let keys: Vec<&String> = esvf.get_object().keys().collect();
+        assert_eq!(keys, vec!["b", "a", "c"]);
+
+        // and back again
+        let res = rt.call_sync(vec!["JSON"], "stringify", vec![esvf]);
+        let res_esvf = res.ok().expect("stringify failed");
+        assert_eq!(res_esvf.get_string(), "{\"b\":1,\"a\":2,\"c\":3}");
+    }
+
+    #[test]
+    fn test_object_symbol_props() {
+        log::info!("test: test_object_symbol_props");
+        let rt = crate::esruntimebuilder::EsRuntimeBuilder::new()
+            .include_symbol_props(true)
+            .include_non_enumerable_props(true)
+            .build();
+        let esvf = rt
+            .eval_sync(
+                "let o = {a: 1, [Symbol.for('s')]: 2, [Symbol('u')]: 4, [Symbol.toStringTag]: 'T'}; \
+                 Object.defineProperty(o, 'h', {value: 3, enumerable: false}); o;",
+                "test_object_symbol_props.es",
+            )
+            .ok()
+            .unwrap();
+
+        let map = esvf.get_object();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("h").unwrap().get_i32(), &3);
+        // the unique Symbol('u') is skipped
+        let symbol_props = esvf.get_symbol_props();
+        assert_eq!(symbol_props.len(), 2);
+        assert_eq!(symbol_props[0].0.as_str(), "s");
+        assert_eq!(symbol_props[0].1.get_i32(), &2);
+        assert_eq!(symbol_props[1].0.as_str(), "Symbol.toStringTag");
+
+        rt.eval_sync(
+            "this.test_object_symbol_props = function(obj){return obj[Symbol.for('s')] + String(obj);};",
+            "test_object_symbol_props2.es",
+        )
+        .ok()
+        .unwrap();
+        let res = rt.call_sync(vec![], "test_object_symbol_props", vec![esvf]);
+        let res_esvf = res.ok().expect("call failed");
+        // the well known Symbol.toStringTag is recreated as the well known Symbol
+        assert_eq!(res_esvf.get_string(), "2[object T]");
+    }
+
+    #[test]
+    fn test_new_function() {
+        log::info!("test: test_new_function");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        rt.eval_sync(
+            "this.test_new_function = function(cb){let ret = 0; for (let x = 0; x < 3; x++){ret += cb(x);} return ret;};",
+            "test_new_function.es",
+        )
+        .ok()
+        .unwrap();
+
+        let invocations = Arc::new(Mutex::new(vec![]));
+        let invocations2 = invocations.clone();
+        let func = EsValueFacade::new_function(move |args| {
+            invocations2.lock().unwrap().push(*args[0].get_i32());
+            Ok(EsValueFacade::new_i32(args[0].get_i32() + 1))
+        });
+        assert!(func.is_rust_function());
+
+        let res = rt.call_sync(vec![], "test_new_function", vec![func]);
+        let res_esvf = res.ok().expect("call failed");
+        assert_eq!(res_esvf.get_i32(), &6);
+        assert_eq!(&*invocations.lock().unwrap(), &vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_new_named_function_error() {
+        log::info!("test: test_new_named_function_error");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        rt.eval_sync(
+            "this.test_new_named_function_error = function(cb){try {cb(); return cb.name + ' did not fail';} catch(ex) {return cb.name + ': ' + ex.message;}};",
+            "test_new_named_function_error.es",
+        )
+        .ok()
+        .unwrap();
+
+        let func = EsValueFacade::new_named_function("check", |_args| Err("not ok".to_string()));
+        let res = rt.call_sync(vec![], "test_new_named_function_error", vec![func]);
+        let res_esvf = res.ok().expect("call failed");
+        assert_eq!(
+            res_esvf.get_string(),
+            "check: function check failed\ncaused by: not ok"
+        );
+    }
+
     #[test]
     fn test_getset_array() {
         log::info!("test: test_getset_array");
@@ -1284,4 +2178,101 @@ mod tests {
 
         assert_eq!(&"123foo", res_str);
     }
+
+    #[test]
+    fn test_deferred_prom() {
+        log::info!("test: test_deferred_prom");
+        let rt: &EsRuntime = &*crate::esruntime::tests::TEST_RT.clone();
+
+        rt.eval_sync("this.test_deferred_prom_func = (prom) => {return prom.then((p_res) => {return p_res + 'foo';}).catch((p_err) => {return p_err + 'bar';});};", "test_deferred_prom.es").ok().unwrap();
+
+        let (prom_esvf, resolver) = EsValueFacade::new_deferred();
+        let p2_esvf = rt
+            .call_sync(vec![], "test_deferred_prom_func", vec![prom_esvf])
+            .ok()
+            .unwrap();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            resolver.resolve(EsValueFacade::new_i32(456));
+        });
+
+        let res = p2_esvf
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(res.ok().unwrap().get_string(), "456foo");
+
+        // dropping the resolver should reject the promise
+        let (prom_esvf, resolver) = EsValueFacade::new_deferred();
+        let p3_esvf = rt
+            .call_sync(vec![], "test_deferred_prom_func", vec![prom_esvf])
+            .ok()
+            .unwrap();
+        drop(resolver);
+
+        let res = p3_esvf
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(
+            res.ok().unwrap().get_string(),
+            "promise resolver was dropped without a resultbar"
+        );
+    }
+
+    #[test]
+    fn test_prom_reject_with_error() {
+        log::info!("test: test_prom_reject_with_error");
+        let rt: &EsRuntime = &*crate::esruntime::tests::TEST_RT.clone();
+
+        rt.eval_sync("this.test_prom_reject_with_error_func = (prom) => {return prom.catch((p_err) => {return [p_err instanceof TypeError, p_err instanceof Error, p_err.name, p_err.message, p_err.code, p_err.data.a].join(',');});};", "test_prom_reject_with_error.es").ok().unwrap();
+
+        let prom_esvf = EsValueFacade::new_promise_esvf(|| {
+            Err(EsValueFacade::new_error(
+                "TypeError",
+                "bad type",
+                Some("E_BAD"),
+                Some(EsValueFacade::new_obj(vec![(
+                    "a".to_string(),
+                    EsValueFacade::new_i32(7),
+                )])),
+            ))
+        });
+        let res = rt
+            .call_sync(vec![], "test_prom_reject_with_error_func", vec![prom_esvf])
+            .ok()
+            .unwrap()
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(
+            res.ok().unwrap().get_string(),
+            "true,true,TypeError,bad type,E_BAD,7"
+        );
+
+        // a custom name should result in an Error with that name
+        let (prom_esvf, resolver) = EsValueFacade::new_deferred();
+        let p_esvf = rt
+            .call_sync(vec![], "test_prom_reject_with_error_func", vec![prom_esvf])
+            .ok()
+            .unwrap();
+        resolver.reject(EsValueFacade::new_error(
+            "MyBizError",
+            "biz failed",
+            None,
+            Some(EsValueFacade::new_obj(vec![(
+                "a".to_string(),
+                EsValueFacade::new_i32(8),
+            )])),
+        ));
+        let res = p_esvf
+            .get_promise_result_blocking(Duration::from_secs(10))
+            .ok()
+            .unwrap();
+        assert_eq!(
+            res.ok().unwrap().get_string(),
+            "false,true,MyBizError,biz failed,,8"
+        );
+    }
 }
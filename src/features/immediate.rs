@@ -1,5 +1,5 @@
 use crate::esruntime::EsRuntime;
-use log::{error, trace};
+use log::trace;
 use mozjs::jsapi::JS_ReportErrorASCII;
 use mozjs::jsval::ObjectValue;
 
@@ -67,11 +67,8 @@ pub(crate) fn init(rt: &EsRuntime) {
                         vec![],
                         rval.handle_mut(),
                     );
-                    if res.is_err() {
-                        error!(
-                            "error executing setImmediate func: {}",
-                            res.err().unwrap().err_msg()
-                        );
+                    if let Err(err) = res {
+                        crate::spidermonkeyruntimewrapper::report_uncaught_error(err);
                     } else {
                         trace!("executed setImmediate function");
                     }
@@ -86,6 +83,7 @@ pub(crate) fn init(rt: &EsRuntime) {
 #[cfg(test)]
 pub mod tests {
     use crate::esruntimebuilder::EsRuntimeBuilder;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_set_immediate() {
@@ -102,4 +100,41 @@ pub mod tests {
         }
         assert!(res.is_ok())
     }
+
+    #[test]
+    fn test_set_immediate_uncaught_error() {
+        log::info!("test: test_set_immediate_uncaught_error");
+        let errors = Arc::new(Mutex::new(vec![]));
+        let errors2 = errors.clone();
+        let rt = EsRuntimeBuilder::new()
+            .on_uncaught_error(move |err| {
+                let errors = &mut *errors2.lock().unwrap();
+                errors.push(err.message.clone());
+            })
+            .build();
+        rt.eval_sync(
+            "this.script_errors = []; \
+             this.onerror = (msg) => {script_errors.push('onerror: ' + msg);}; \
+             addEventListener('error', (evt) => {script_errors.push('listener: ' + evt.message);}); \
+             addEventListener('unknown', (evt) => {script_errors.push('unknown: ' + evt.message);}); \
+             this.__dispatch_error_event = () => {script_errors.push('replaced');}; \
+             setImmediate(function(){throw Error('boom');});",
+            "test_set_immediate_uncaught_error.es",
+        )
+        .ok()
+        .expect("script failed");
+
+        let script_errors = rt
+            .eval_sync(
+                "script_errors.length;",
+                "test_set_immediate_uncaught_error2.es",
+            )
+            .ok()
+            .expect("script failed");
+        assert_eq!(script_errors.get_i32(), &2);
+
+        let errors = &*errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("boom"));
+    }
 }
@@ -0,0 +1,32 @@
+use crate::esconversions::ToEsValue;
+use crate::esruntime::EsRuntime;
+use crate::jsapi_utils::reflection::{get_proxy, get_proxy_for_class_or_instance};
+use mozjs::jsval::NullValue;
+
+/// adds __describe_proxy(target) which is used by esses.describe()
+/// target may be the canonical name of a proxy class, its constructor, a singleton or an instance
+pub(crate) fn init(rt: &EsRuntime) {
+    rt.do_in_es_runtime_thread_sync(|sm_rt| {
+        sm_rt.add_global_function("__describe_proxy", |cx, args| {
+            let target = *args.get(0);
+            let proxy_opt = if target.is_string() {
+                crate::jsapi_utils::es_value_to_str(cx, target)
+                    .ok()
+                    .and_then(|name| get_proxy(name.as_str()))
+            } else if target.is_object() {
+                get_proxy_for_class_or_instance(cx, target.to_object())
+            } else {
+                None
+            };
+
+            match proxy_opt {
+                Some(proxy) => {
+                    let esvf = proxy.describe().to_es_value_facade();
+                    args.rval().set(esvf.to_es_value(cx));
+                }
+                None => args.rval().set(NullValue()),
+            }
+            true
+        });
+    });
+}
@@ -35,10 +35,11 @@ use mozjs::jsapi::JS_ClearPendingException;
 use mozjs::jsapi::JS_GetPendingException;
 use mozjs::jsapi::JS_IsExceptionPending;
 use mozjs::jsapi::JS_NewStringCopyN;
+use mozjs::jsapi::JS_SetPendingException;
 use mozjs::jsapi::JS_TypeOfValue;
 use mozjs::jsapi::JS_GC;
 use mozjs::jsval::{JSVal, StringValue, UndefinedValue};
-use mozjs::rust::{HandleObject, MutableHandleValue, Runtime};
+use mozjs::rust::{HandleObject, HandleValue, MutableHandleValue, Runtime};
 use std::str;
 
 pub mod arrays;
@@ -49,6 +50,12 @@ pub mod promises;
 pub mod reflection;
 pub mod rooting;
 
+/// set the pending exception, this is the native equivalent of a throw statement
+/// the native function which calls this should return false
+pub fn set_pending_exception(context: *mut JSContext, val: HandleValue) {
+    unsafe { JS_SetPendingException(context, val.into()) };
+}
+
 /// get the type of a JSVal
 pub fn get_type_of(context: *mut JSContext, val: JSVal) -> JSType {
     rooted!(in(context) let val_root = val);
@@ -1,6 +1,11 @@
 use crate::jsapi_utils::objects::get_es_obj_prop_val;
 use crate::jsapi_utils::{get_type_of, report_es_ex, EsErrorInfo};
+use crate::utils::AutoIdMap;
+use mozjs::jsapi::CallArgs;
+use mozjs::jsapi::JSClass;
+use mozjs::jsapi::JSClassOps;
 use mozjs::jsapi::JSContext;
+use mozjs::jsapi::JSFreeOp;
 use mozjs::jsapi::JSFunction;
 use mozjs::jsapi::JSNative;
 use mozjs::jsapi::JSObject;
@@ -10,13 +15,23 @@ use mozjs::jsapi::JS_CallFunctionValue;
 
 use log::trace;
 use mozjs::jsapi::JS_DefineFunction;
+use mozjs::jsapi::JS_GetFunctionObject;
 use mozjs::jsapi::JS_NewArrayObject;
 use mozjs::jsapi::JS_NewFunction;
+use mozjs::jsapi::JS_NewObject;
 use mozjs::jsapi::JS_ObjectIsFunction;
+use mozjs::jsapi::JS_ReportErrorASCII;
+use mozjs::jsapi::JS_SetReservedSlot;
 use mozjs::jsapi::JS::HandleValueArray;
+use mozjs::jsapi::JSCLASS_FOREGROUND_FINALIZE;
+use mozjs::jsapi::JSCLASS_RESERVED_SLOTS_SHIFT;
 use mozjs::jsval::JSVal;
 use mozjs::jsval::UndefinedValue;
+use mozjs::jsval::{Int32Value, ObjectValue};
 use mozjs::rust::{HandleObject, HandleValue, MutableHandleValue};
+use std::cell::RefCell;
+use std::ptr;
+use std::rc::Rc;
 
 /// call a method by name
 pub fn call_method_name(
@@ -311,6 +326,148 @@ pub fn new_native_constructor(
     //https://developer.mozilla.org/en-US/docs/Mozilla/Projects/SpiderMonkey/JSAPI_reference/JS_DefineFunction
 }
 
+/// the type for closures which are used as a native function by new_callback_function
+pub type CallbackFunction = dyn Fn(*mut JSContext, CallArgs) -> bool + 'static;
+
+thread_local! {
+    static CALLBACK_FUNCTIONS: RefCell<AutoIdMap<Rc<CallbackFunction>>> = RefCell::new(AutoIdMap::new());
+}
+
+static CALLBACK_HOLDER_CLASS_OPS: JSClassOps = JSClassOps {
+    addProperty: None,
+    delProperty: None,
+    enumerate: None,
+    newEnumerate: None,
+    resolve: None,
+    mayResolve: None,
+    finalize: Some(callback_holder_finalize),
+    call: None,
+    hasInstance: None,
+    construct: None,
+    trace: None,
+};
+
+/// the class of the object which holds the id of the closure for a callback function
+/// the holder is only referenced from the function so when it is finalized the function is gone as well
+static CALLBACK_HOLDER_CLASS: JSClass = JSClass {
+    name: b"CallbackHolder\0" as *const u8 as *const libc::c_char,
+    flags: JSCLASS_FOREGROUND_FINALIZE | (1 << JSCLASS_RESERVED_SLOTS_SHIFT),
+    cOps: &CALLBACK_HOLDER_CLASS_OPS as *const JSClassOps,
+    spec: ptr::null(),
+    ext: ptr::null(),
+    oOps: ptr::null(),
+};
+
+/// create a new function which invokes a rust closure when called
+/// unlike define_native_function this does not define the function on an object and the closure is dropped
+/// when the function is garbage collected
+/// # Example
+/// ```no_run
+/// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+/// use es_runtime::jsapi_utils::functions::new_callback_function;
+/// use mozjs::jsval::{Int32Value, ObjectValue};
+///
+/// let rt = EsRuntimeBuilder::new().build();
+/// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+///     sm_rt.do_with_jsapi(|_rt, cx, global| {
+///         let func = new_callback_function(cx, "my_callback", 0, |_cx, args| {
+///             args.rval().set(Int32Value(123));
+///             true
+///         });
+///         mozjs::rooted!(in (cx) let func_val = ObjectValue(func));
+///         es_runtime::jsapi_utils::objects::set_es_obj_prop_val(cx, global, "my_callback", func_val.handle());
+///     });
+/// });
+/// ```
+pub fn new_callback_function<F>(
+    cx: *mut JSContext,
+    function_name: &str,
+    nargs: u32,
+    callback: F,
+) -> *mut JSObject
+where
+    F: Fn(*mut JSContext, CallArgs) -> bool + 'static,
+{
+    let id = CALLBACK_FUNCTIONS.with(|cf_rc| {
+        let map = &mut *cf_rc.borrow_mut();
+        map.insert(Rc::new(callback))
+    });
+
+    trace!("new_callback_function {} with id {}", function_name, id);
+
+    rooted!(in (cx) let holder_root = unsafe { JS_NewObject(cx, &CALLBACK_HOLDER_CLASS) });
+    unsafe { JS_SetReservedSlot(holder_root.get(), 0, &Int32Value(id as i32)) };
+
+    let n = format!("{}\0", function_name);
+    let func: *mut JSFunction = unsafe {
+        mozjs::jsapi::js::NewFunctionWithReserved(
+            cx,
+            Some(callback_function_native),
+            nargs,
+            0,
+            n.as_ptr() as *const libc::c_char,
+        )
+    };
+    rooted!(in (cx) let func_obj_root = unsafe { JS_GetFunctionObject(func) });
+    unsafe {
+        mozjs::jsapi::js::SetFunctionNativeReserved(
+            func_obj_root.get(),
+            0,
+            &ObjectValue(holder_root.get()),
+        )
+    };
+
+    func_obj_root.get()
+}
+
+fn get_callback_holder_id(holder: *mut JSObject) -> usize {
+    let mut id_val = UndefinedValue();
+    unsafe { mozjs::glue::JS_GetReservedSlot(holder, 0, &mut id_val) };
+    id_val.to_int32() as usize
+}
+
+unsafe extern "C" fn callback_function_native(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    let args = CallArgs::from_vp(vp, argc);
+    let callee: *mut JSObject = args.callee();
+    let holder_val: JSVal = *mozjs::jsapi::js::GetFunctionNativeReserved(callee, 0);
+    let id = get_callback_holder_id(holder_val.to_object());
+
+    // clone the Rc so the map is not borrowed while the callback runs
+    let callback_opt = CALLBACK_FUNCTIONS.with(|cf_rc| {
+        let map = &*cf_rc.borrow();
+        map.get(&id).cloned()
+    });
+
+    if let Some(callback) = callback_opt {
+        callback(cx, args)
+    } else {
+        JS_ReportErrorASCII(
+            cx,
+            b"callback function was already dropped\0".as_ptr() as *const libc::c_char,
+        );
+        false
+    }
+}
+
+unsafe extern "C" fn callback_holder_finalize(_fop: *mut JSFreeOp, object: *mut JSObject) {
+    let id = get_callback_holder_id(object);
+    trace!("callback_holder_finalize {}", id);
+    let callback_opt = CALLBACK_FUNCTIONS.with(|cf_rc| {
+        let map = &mut *cf_rc.borrow_mut();
+        if map.contains_key(&id) {
+            Some(map.remove(&id))
+        } else {
+            None
+        }
+    });
+    // drop outside of the borrow in case the closure owns anything which uses the map on drop
+    drop(callback_opt);
+}
+
 #[cfg(test)]
 mod tests {
     use crate::jsapi_utils;
@@ -1,17 +1,34 @@
 use crate::jsapi_utils::{es_jsstring_to_string, es_value_to_str, report_es_ex, EsErrorInfo};
 use log::trace;
+use mozjs::glue::RUST_JSID_IS_INT;
 use mozjs::glue::RUST_JSID_IS_STRING;
+use mozjs::glue::RUST_JSID_IS_SYMBOL;
+use mozjs::glue::RUST_JSID_TO_INT;
 use mozjs::glue::RUST_JSID_TO_STRING;
+use mozjs::glue::RUST_JSID_TO_SYMBOL;
+use mozjs::glue::RUST_SYMBOL_TO_JSID;
 use mozjs::jsapi::HandleValueArray;
 use mozjs::jsapi::JSClass;
 use mozjs::jsapi::JSContext;
+use mozjs::jsapi::JSNative;
 use mozjs::jsapi::JSObject;
+use mozjs::jsapi::JSString;
+use mozjs::jsapi::JS_DeleteProperty;
 use mozjs::jsapi::JS_GetConstructor;
 use mozjs::jsapi::JS_GetProperty;
+use mozjs::jsapi::JS_GetPropertyById;
 use mozjs::jsapi::JS_GetPrototype;
+use mozjs::jsapi::JS_HasOwnProperty;
 use mozjs::jsapi::JS_NewObjectWithGivenProto;
 use mozjs::jsapi::JS_NewPlainObject;
+use mozjs::jsapi::JS_NewStringCopyN;
+use mozjs::jsapi::JS_SetProperty;
+use mozjs::jsapi::JS_SetPropertyById;
+use mozjs::jsapi::JS_SetPrototype;
+use mozjs::jsapi::ObjectOpResult;
+use mozjs::jsapi::JSITER_HIDDEN;
 use mozjs::jsapi::JSITER_OWNONLY;
+use mozjs::jsapi::JSITER_SYMBOLS;
 use mozjs::jsval::{JSVal, ObjectValue, UndefinedValue};
 use mozjs::rust::jsapi_wrapped::GetPropertyKeys;
 use mozjs::rust::wrappers::JS_DefineProperty;
@@ -191,6 +208,49 @@ pub fn get_prototype(
     }
 }
 
+/// set the prototype of an object, like Object.setPrototypeOf(obj, proto); in script
+pub fn set_prototype(
+    context: *mut JSContext,
+    obj: HandleObject,
+    proto: HandleObject,
+) -> Result<(), EsErrorInfo> {
+    let ok = unsafe { JS_SetPrototype(context, obj.into(), proto.into()) };
+
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// check if an object has an own property, the prototype chain is not checked
+pub fn has_own_es_obj_prop(
+    context: *mut JSContext,
+    obj: HandleObject,
+    prop_name: &str,
+) -> Result<bool, EsErrorInfo> {
+    let n = format!("{}\0", prop_name);
+    let mut found = false;
+    let ok = unsafe {
+        JS_HasOwnProperty(
+            context,
+            obj.into(),
+            n.as_ptr() as *const libc::c_char,
+            &mut found,
+        )
+    };
+
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    Ok(found)
+}
+
 /// get the constructor of an object
 #[allow(dead_code)]
 pub fn get_constructor(
@@ -214,6 +274,7 @@ pub fn get_constructor(
 }
 
 /// get all the propertynames of an object
+/// integer keys (e.g. of {1: 'a'}) are returned as their string representation
 #[allow(dead_code)]
 pub fn get_js_obj_prop_names(context: *mut JSContext, obj: HandleObject) -> Vec<String> {
     let mut ids = unsafe { IdVector::new(context) };
@@ -224,6 +285,12 @@ pub fn get_js_obj_prop_names(context: *mut JSContext, obj: HandleObject) -> Vec<
 
     for x in 0..ids.len() {
         rooted!(in(context) let id = ids[x]);
+        if unsafe { RUST_JSID_IS_INT(id.handle().into()) } {
+            ret.push(format!("{}", unsafe {
+                RUST_JSID_TO_INT(id.handle().into())
+            }));
+            continue;
+        }
         assert!(unsafe { RUST_JSID_IS_STRING(id.handle().into()) });
         rooted!(in(context) let id_str = unsafe{RUST_JSID_TO_STRING(id.handle().into())});
         let prop_name = es_jsstring_to_string(context, *id_str);
@@ -232,6 +299,226 @@ pub fn get_js_obj_prop_names(context: *mut JSContext, obj: HandleObject) -> Vec<
     ret
 }
 
+/// the name of a property key as passed to the consumer of get_js_obj_props
+pub enum PropertyKeyName {
+    /// a string key, integer keys are converted to their string representation
+    Str(String),
+    /// a Symbol key, this contains the description of the Symbol
+    /// only registered Symbols (Symbol.for(description)) and well known Symbols (e.g. Symbol.iterator)
+    /// are passed to the consumer because only those can be recreated from their description,
+    /// see set_es_obj_symbol_prop_val
+    Symbol(String),
+}
+
+/// the well known Symbols by the name after "Symbol." in their description
+const WELL_KNOWN_SYMBOLS: &[(&str, mozjs::jsapi::SymbolCode)] = &[
+    (
+        "isConcatSpreadable",
+        mozjs::jsapi::SymbolCode::isConcatSpreadable,
+    ),
+    ("iterator", mozjs::jsapi::SymbolCode::iterator),
+    ("match", mozjs::jsapi::SymbolCode::match_),
+    ("replace", mozjs::jsapi::SymbolCode::replace),
+    ("search", mozjs::jsapi::SymbolCode::search),
+    ("species", mozjs::jsapi::SymbolCode::species),
+    ("hasInstance", mozjs::jsapi::SymbolCode::hasInstance),
+    ("split", mozjs::jsapi::SymbolCode::split),
+    ("toPrimitive", mozjs::jsapi::SymbolCode::toPrimitive),
+    ("toStringTag", mozjs::jsapi::SymbolCode::toStringTag),
+    ("unscopables", mozjs::jsapi::SymbolCode::unscopables),
+    ("asyncIterator", mozjs::jsapi::SymbolCode::asyncIterator),
+    ("matchAll", mozjs::jsapi::SymbolCode::matchAll),
+];
+
+/// get the SymbolCode of a well known Symbol by its description e.g. "Symbol.iterator"
+fn get_well_known_symbol_code(description: &str) -> Option<mozjs::jsapi::SymbolCode> {
+    let name = description.strip_prefix("Symbol.")?;
+    WELL_KNOWN_SYMBOLS
+        .iter()
+        .find(|(wks_name, _code)| *wks_name == name)
+        .map(|(_name, code)| *code)
+}
+
+/// iterate over the own properties of an object in the order in which the engine enumerates them
+/// (integer keys ascending, then string keys in insertion order, then Symbol keys in insertion order)
+///
+/// the consumer is called with the key and value of every property
+/// by default only enumerable string keyed props are visited, use include_symbols and
+/// include_non_enumerable to visit the others as well
+pub fn get_js_obj_props<C>(
+    context: *mut JSContext,
+    obj: HandleObject,
+    include_symbols: bool,
+    include_non_enumerable: bool,
+    mut consumer: C,
+) -> Result<(), EsErrorInfo>
+where
+    C: FnMut(PropertyKeyName, HandleValue),
+{
+    let mut flags = JSITER_OWNONLY;
+    if include_symbols {
+        flags |= JSITER_SYMBOLS;
+    }
+    if include_non_enumerable {
+        flags |= JSITER_HIDDEN;
+    }
+
+    let mut ids = unsafe { IdVector::new(context) };
+
+    if !unsafe { GetPropertyKeys(context, obj, flags, ids.handle_mut()) } {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    for x in 0..ids.len() {
+        rooted!(in(context) let id = ids[x]);
+
+        let key = if unsafe { RUST_JSID_IS_STRING(id.handle().into()) } {
+            rooted!(in(context) let id_str = unsafe{RUST_JSID_TO_STRING(id.handle().into())});
+            PropertyKeyName::Str(es_jsstring_to_string(context, *id_str))
+        } else if unsafe { RUST_JSID_IS_INT(id.handle().into()) } {
+            PropertyKeyName::Str(format!("{}", unsafe {
+                RUST_JSID_TO_INT(id.handle().into())
+            }))
+        } else if unsafe { RUST_JSID_IS_SYMBOL(id.handle().into()) } {
+            rooted!(in(context) let symbol = unsafe{RUST_JSID_TO_SYMBOL(id.handle().into())});
+            let code = unsafe { mozjs::jsapi::GetSymbolCode(symbol.handle().into()) };
+            let descr: *mut JSString =
+                unsafe { mozjs::jsapi::GetSymbolDescription(symbol.handle().into()) };
+            let descr_str = if descr.is_null() {
+                "".to_string()
+            } else {
+                es_jsstring_to_string(context, descr)
+            };
+            // a unique Symbol can not be recreated from its description and a registered
+            // Symbol named like a well known Symbol would be recreated as the well known one
+            let recreatable = match code {
+                mozjs::jsapi::SymbolCode::UniqueSymbol => false,
+                mozjs::jsapi::SymbolCode::InSymbolRegistry => {
+                    get_well_known_symbol_code(descr_str.as_str()).is_none()
+                }
+                _ => true,
+            };
+            if !recreatable {
+                trace!("get_js_obj_props: skipping Symbol {}", descr_str);
+                continue;
+            }
+            PropertyKeyName::Symbol(descr_str)
+        } else {
+            trace!("get_js_obj_props: skipping unsupported key type");
+            continue;
+        };
+
+        rooted!(in(context) let mut prop_val_root = UndefinedValue());
+        let ok = unsafe {
+            JS_GetPropertyById(
+                context,
+                obj.into(),
+                id.handle().into(),
+                prop_val_root.handle_mut().into(),
+            )
+        };
+        if !ok {
+            if let Some(err) = report_es_ex(context) {
+                return Err(err);
+            }
+        }
+
+        consumer(key, prop_val_root.handle());
+    }
+
+    Ok(())
+}
+
+/// set a Symbol keyed property of an object
+/// a description of a well known Symbol (e.g. "Symbol.iterator") sets the property for that Symbol,
+/// any other description uses the registered Symbol for the description, this is the same Symbol
+/// as returned by Symbol.for(description) in script
+#[allow(dead_code)]
+pub fn set_es_obj_symbol_prop_val(
+    context: *mut JSContext,
+    obj: HandleObject,
+    symbol_description: &str,
+    prop_val: HandleValue,
+) -> Result<(), EsErrorInfo> {
+    if let Some(code) = get_well_known_symbol_code(symbol_description) {
+        return set_es_obj_well_known_symbol_prop_val(context, obj, code, prop_val);
+    }
+
+    rooted!(in(context) let descr_root = unsafe {
+        JS_NewStringCopyN(
+            context,
+            symbol_description.as_ptr() as *const libc::c_char,
+            symbol_description.len(),
+        )
+    });
+    rooted!(in(context) let symbol_root = unsafe {
+        mozjs::jsapi::GetSymbolFor(context, descr_root.handle().into())
+    });
+    rooted!(in(context) let mut id_root = mozjs::jsapi::PropertyKey::default());
+    unsafe { RUST_SYMBOL_TO_JSID(symbol_root.get(), id_root.handle_mut().into()) };
+
+    let ok = unsafe {
+        JS_SetPropertyById(
+            context,
+            obj.into(),
+            id_root.handle().into(),
+            prop_val.into(),
+        )
+    };
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// set the Symbol.toStringTag of an object, Object.prototype.toString() then returns [object tag] for the object
+pub fn set_es_obj_to_string_tag(
+    context: *mut JSContext,
+    obj: HandleObject,
+    tag: &str,
+) -> Result<(), EsErrorInfo> {
+    rooted!(in(context) let tag_root = crate::jsapi_utils::new_es_value_from_str(context, tag));
+    set_es_obj_well_known_symbol_prop_val(
+        context,
+        obj,
+        mozjs::jsapi::SymbolCode::toStringTag,
+        tag_root.handle(),
+    )
+}
+
+/// set a property of an object keyed by a well known Symbol like Symbol.iterator
+pub fn set_es_obj_well_known_symbol_prop_val(
+    context: *mut JSContext,
+    obj: HandleObject,
+    symbol_code: mozjs::jsapi::SymbolCode,
+    prop_val: HandleValue,
+) -> Result<(), EsErrorInfo> {
+    rooted!(in(context) let symbol_root = unsafe {
+        mozjs::jsapi::GetWellKnownSymbol(context, symbol_code)
+    });
+    rooted!(in(context) let mut id_root = mozjs::jsapi::PropertyKey::default());
+    unsafe { RUST_SYMBOL_TO_JSID(symbol_root.get(), id_root.handle_mut().into()) };
+
+    let ok = unsafe {
+        JS_SetPropertyById(
+            context,
+            obj.into(),
+            id_root.handle().into(),
+            prop_val.into(),
+        )
+    };
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
 /// set a property of an object
 #[allow(dead_code)]
 pub fn set_es_obj_prop_val(
@@ -252,7 +539,113 @@ pub fn set_es_obj_prop_val(
     }
 }
 
-/// set a property of an object
+/// assign a value to a property of an object
+/// unlike set_es_obj_prop_val this invokes setters and respects readonly props, just like obj[prop_name] = val; in script
+pub fn assign_es_obj_prop_val(
+    context: *mut JSContext,
+    obj: HandleObject,
+    prop_name: &str,
+    prop_val: HandleValue,
+) -> Result<(), EsErrorInfo> {
+    let n = format!("{}\0", prop_name);
+    let ok = unsafe {
+        JS_SetProperty(
+            context,
+            obj.into(),
+            n.as_ptr() as *const libc::c_char,
+            prop_val.into(),
+        )
+    };
+
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// delete a property of an object, like delete obj[prop_name]; in script
+/// returns Ok(false) if the prop could not be deleted (e.g. because it is not configurable)
+pub fn delete_es_obj_prop(
+    context: *mut JSContext,
+    obj: HandleObject,
+    prop_name: &str,
+) -> Result<bool, EsErrorInfo> {
+    let n = format!("{}\0", prop_name);
+    let mut op_result = ObjectOpResult { code_: 0 };
+    let ok = unsafe {
+        JS_DeleteProperty(
+            context,
+            obj.into(),
+            n.as_ptr() as *const libc::c_char,
+            &mut op_result,
+        )
+    };
+
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    // ObjectOpResult::OkCode is 0
+    Ok(op_result.code_ == 0)
+}
+
+/// set a non-enumerable property of an object, the property is writable and configurable
+pub fn set_es_obj_prop_val_hidden(
+    context: *mut JSContext,
+    obj: HandleObject,
+    prop_name: &str,
+    prop_val: HandleValue,
+) {
+    let prop_name_str = format!("{}\0", prop_name);
+    unsafe {
+        JS_DefineProperty(
+            context,
+            obj,
+            prop_name_str.as_ptr() as *const libc::c_char,
+            prop_val,
+            0,
+        );
+    }
+}
+
+/// define a property with native getter and setter functions, the functions are named "get [prop_name]" and "set [prop_name]"
+/// a property without a setter is read only, flags are JSPROP_* flags like JSPROP_ENUMERATE and JSPROP_PERMANENT
+/// but should not include JSPROP_GETTER or JSPROP_SETTER as those mean the accessors are functions instead of natives
+pub fn define_native_getter_setter(
+    context: *mut JSContext,
+    obj: HandleObject,
+    prop_name: &str,
+    getter: JSNative,
+    setter: JSNative,
+    flags: u32,
+) -> Result<(), EsErrorInfo> {
+    let n = format!("{}\0", prop_name);
+    let ok = unsafe {
+        mozjs::jsapi::JS_DefineProperty1(
+            context,
+            obj.into(),
+            n.as_ptr() as *const libc::c_char,
+            getter,
+            setter,
+            flags,
+        )
+    };
+
+    if !ok {
+        if let Some(err) = report_es_ex(context) {
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// set a read only property of an object which can not be deleted, the property is not enumerable
 #[allow(dead_code)]
 pub fn set_es_obj_prop_val_permanent(
     context: *mut JSContext,
@@ -267,7 +660,7 @@ pub fn set_es_obj_prop_val_permanent(
             obj,
             prop_name_str.as_ptr() as *const libc::c_char,
             prop_val,
-            (mozjs::jsapi::JSPROP_PERMANENT & mozjs::jsapi::JSPROP_READONLY) as u32,
+            (mozjs::jsapi::JSPROP_PERMANENT | mozjs::jsapi::JSPROP_READONLY) as u32,
         );
     }
 }
@@ -282,9 +675,9 @@ mod tests {
     };
     use crate::jsapi_utils::{es_value_to_str, report_es_ex};
     use crate::spidermonkeyruntimewrapper::SmRuntime;
+    use indexmap::IndexMap;
     use mozjs::jsval::{JSVal, UndefinedValue};
     use std::borrow::Borrow;
-    use std::collections::HashMap;
     use std::sync::Arc;
 
     #[test]
@@ -436,7 +829,7 @@ mod tests {
         let res = rt.eval_sync("({a: 1, b: 'abc', c: true, d: 'much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string much larger string'});", "test_get_obj_props");
         assert!(res.is_ok());
         let map = res.ok().unwrap();
-        let map: &HashMap<String, EsValueFacade> = map.get_object();
+        let map: &IndexMap<String, EsValueFacade> = map.get_object();
         assert_eq!(map.get(&"b".to_string()).unwrap().get_string(), "abc");
     }
 
@@ -1,12 +1,16 @@
+use crate::jsapi_utils::functions::new_callback_function;
 use crate::jsapi_utils::objects::{get_constructor, get_es_obj_prop_val_as_string};
 use crate::jsapi_utils::{report_es_ex, EsErrorInfo};
+use mozjs::jsapi::CallArgs;
 use mozjs::jsapi::JSContext;
 use mozjs::jsapi::JSObject;
-use mozjs::jsval::NullValue;
+use mozjs::jsapi::PromiseState;
+use mozjs::jsval::{NullValue, UndefinedValue};
+use mozjs::rust::jsapi_wrapped::AddPromiseReactions;
 use mozjs::rust::jsapi_wrapped::NewPromiseObject;
 use mozjs::rust::jsapi_wrapped::RejectPromise;
 use mozjs::rust::jsapi_wrapped::ResolvePromise;
-use mozjs::rust::{HandleObject, HandleValue};
+use mozjs::rust::{HandleObject, HandleValue, MutableHandleValue};
 
 pub fn object_is_promise(context: *mut JSContext, obj: HandleObject) -> bool {
     // todo this is not the best way of doing this, we need to get the promise object of the global scope and see if that is the same as the objects constructor
@@ -78,14 +82,118 @@ pub fn reject_promise(
     }
 }
 
+/// get the state of a promise (Pending, Fulfilled or Rejected)
+pub fn get_promise_state(promise: HandleObject) -> PromiseState {
+    unsafe { mozjs::rust::jsapi_wrapped::GetPromiseState(promise) }
+}
+
+/// get the result of a settled promise, this is the resolution value if the promise was fulfilled
+/// or the rejection value if the promise was rejected
+/// this fails if the promise is still pending
+pub fn get_promise_result(
+    promise: HandleObject,
+    mut ret_val: MutableHandleValue,
+) -> Result<(), EsErrorInfo> {
+    if get_promise_state(promise) == PromiseState::Pending {
+        return Err(EsErrorInfo {
+            message: "promise is still pending".to_string(),
+            filename: "".to_string(),
+            lineno: 0,
+            column: 0,
+        });
+    }
+    ret_val.set(unsafe { mozjs::rust::jsapi_wrapped::GetPromiseResult(promise) });
+    Ok(())
+}
+
+/// add reactions to a promise, these are called with the value of the promise when it is resolved or rejected
+/// this is the native equivalent of promise.then(on_resolved, on_rejected);
+/// the reactions are called from the job queue of the runtime, so never synchronously from this method
+/// # Example
+/// ```no_run
+/// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+/// use es_runtime::jsapi_utils;
+/// use es_runtime::jsapi_utils::promises::add_promise_reactions;
+/// use mozjs::jsval::UndefinedValue;
+///
+/// let rt = EsRuntimeBuilder::new().build();
+/// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+///     sm_rt.do_with_jsapi(|rt, cx, global| {
+///         mozjs::rooted!(in (cx) let mut rval = UndefinedValue());
+///         jsapi_utils::eval(rt, global, "Promise.resolve(1);", "test_add_promise_reactions.es", rval.handle_mut())
+///             .ok().expect("script failed");
+///         mozjs::rooted!(in (cx) let prom_root = rval.to_object());
+///         add_promise_reactions(cx, prom_root.handle(), |_cx, val| {
+///             println!("resolved to {}", val.to_int32());
+///         }, |_cx, _err| {
+///             println!("rejected");
+///         }).ok().expect("could not add reactions");
+///     });
+/// });
+/// ```
+pub fn add_promise_reactions<R, J>(
+    context: *mut JSContext,
+    promise: HandleObject,
+    on_resolved: R,
+    on_rejected: J,
+) -> Result<(), EsErrorInfo>
+where
+    R: Fn(*mut JSContext, HandleValue) + 'static,
+    J: Fn(*mut JSContext, HandleValue) + 'static,
+{
+    rooted!(in (context) let on_resolved_root = new_reaction_function(context, "onResolved", on_resolved));
+    rooted!(in (context) let on_rejected_root = new_reaction_function(context, "onRejected", on_rejected));
+
+    let ok = unsafe {
+        AddPromiseReactions(
+            context,
+            promise,
+            on_resolved_root.handle(),
+            on_rejected_root.handle(),
+        )
+    };
+    if ok {
+        Ok(())
+    } else if let Some(err) = report_es_ex(context) {
+        Err(err)
+    } else {
+        Err(EsErrorInfo {
+            message: "unknown error adding promise reactions".to_string(),
+            filename: "".to_string(),
+            lineno: 0,
+            column: 0,
+        })
+    }
+}
+
+fn new_reaction_function<F>(context: *mut JSContext, name: &str, reaction: F) -> *mut JSObject
+where
+    F: Fn(*mut JSContext, HandleValue) + 'static,
+{
+    new_callback_function(context, name, 1, move |cx, args: CallArgs| {
+        let val: HandleValue = if args.argc_ > 0 {
+            unsafe { mozjs::rust::Handle::from_raw(args.get(0)) }
+        } else {
+            HandleValue::undefined()
+        };
+        reaction(cx, val);
+        args.rval().set(UndefinedValue());
+        true
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::jsapi_utils;
-    use crate::jsapi_utils::promises::object_is_promise;
+    use crate::jsapi_utils::promises::{
+        add_promise_reactions, get_promise_result, get_promise_state, object_is_promise,
+    };
     use crate::jsapi_utils::report_es_ex;
     use crate::jsapi_utils::tests::test_with_sm_rt;
     use log::trace;
+    use mozjs::jsapi::PromiseState;
     use mozjs::jsval::UndefinedValue;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_x() {
@@ -159,4 +267,66 @@ mod tests {
         });
         assert_eq!(res, false);
     }
+
+    #[test]
+    fn test_promise_state_and_reactions() {
+        log::info!("test: test_promise_state_and_reactions");
+        let rt = crate::esruntime::tests::TEST_RT.clone();
+        let outcome = Arc::new(Mutex::new(vec![]));
+        let outcome2 = outcome.clone();
+        let outcome3 = outcome.clone();
+        rt.do_in_es_runtime_thread_sync(move |sm_rt| {
+            sm_rt.do_with_jsapi(|rt, cx, global| {
+                rooted!(in(cx) let mut rval = UndefinedValue());
+                jsapi_utils::eval(
+                    rt,
+                    global,
+                    "this.test_promise_state_resolve = null; new Promise((res, rej) => {test_promise_state_resolve = res;});",
+                    "test_promise_state_and_reactions.es",
+                    rval.handle_mut(),
+                )
+                .ok()
+                .expect("script failed");
+                rooted!(in(cx) let prom_root = rval.to_object());
+                assert!(get_promise_state(prom_root.handle()) == PromiseState::Pending);
+                rooted!(in(cx) let mut res_root = UndefinedValue());
+                assert!(get_promise_result(prom_root.handle(), res_root.handle_mut()).is_err());
+
+                add_promise_reactions(
+                    cx,
+                    prom_root.handle(),
+                    move |_cx, val| {
+                        let outcome = &mut *outcome2.lock().unwrap();
+                        outcome.push(format!("resolved {}", val.to_int32()));
+                    },
+                    move |_cx, _val| {
+                        let outcome = &mut *outcome3.lock().unwrap();
+                        outcome.push("rejected".to_string());
+                    },
+                )
+                .ok()
+                .expect("could not add reactions");
+
+                jsapi_utils::eval(
+                    rt,
+                    global,
+                    "test_promise_state_resolve(12);",
+                    "test_promise_state_and_reactions2.es",
+                    rval.handle_mut(),
+                )
+                .ok()
+                .expect("script failed");
+
+                assert!(get_promise_state(prom_root.handle()) == PromiseState::Fulfilled);
+                get_promise_result(prom_root.handle(), res_root.handle_mut())
+                    .ok()
+                    .expect("could not get result");
+                assert_eq!(res_root.to_int32(), 12);
+            });
+        });
+        // the reactions run as jobs after the previous task, so by now they should be done
+        rt.do_in_es_runtime_thread_sync(|_sm_rt| {});
+        let outcome = &*outcome.lock().unwrap();
+        assert_eq!(outcome, &vec!["resolved 12".to_string()]);
+    }
 }
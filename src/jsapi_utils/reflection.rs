@@ -49,7 +49,7 @@
 //!        // and an event that may be dispatched
 //!        .event("saved")
 //!        // when done build your proxy
-//!        .build(cx, global);
+//!        .build(cx, global).ok().expect("could not build proxy");
 //!
 //!        let esvf = sm_rt.eval(
 //!            "// create a new instance of your Proxy\n\
@@ -81,6 +81,7 @@
 
 use crate::jsapi_utils::rooting::EsPersistentRooted;
 use crate::jsapi_utils::{es_jsid_to_string, EsErrorInfo};
+use crate::utils::AutoIdMap;
 
 use mozjs::jsapi::CallArgs;
 use mozjs::jsapi::HandleValueArray;
@@ -92,7 +93,7 @@ use mozjs::jsapi::JSNative;
 use mozjs::jsapi::JSObject;
 use mozjs::jsapi::JS_ReportErrorASCII;
 use mozjs::jsapi::JSCLASS_FOREGROUND_FINALIZE;
-use mozjs::jsval::{NullValue, ObjectValue, UndefinedValue};
+use mozjs::jsval::{BooleanValue, NullValue, ObjectValue, UndefinedValue};
 use mozjs::rust::{HandleObject, HandleValue, MutableHandleObject, MutableHandleValue};
 
 use core::ptr;
@@ -101,6 +102,7 @@ use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::ptr::replace;
+use std::rc::Rc;
 use std::sync::Arc;
 
 pub type Constructor = Box<dyn Fn(*mut JSContext, Vec<HandleValue>) -> Result<i32, String>>;
@@ -112,47 +114,172 @@ pub type StaticSetter = Box<dyn Fn(*mut JSContext, HandleValue) -> Result<(), St
 pub type StaticGetter = Box<dyn Fn(*mut JSContext, MutableHandleValue) -> Result<(), String>>;
 pub type StaticMethod =
     Box<dyn Fn(*mut JSContext, Vec<HandleValue>, MutableHandleValue) -> Result<(), String>>;
+/// a rust event listener, it gets the event object and may set a result
+pub type EventListener =
+    Box<dyn Fn(*mut JSContext, HandleValue, MutableHandleValue) -> Result<(), String>>;
+/// creates the value of a constant, this is called once when the proxy class is built
+pub type ConstantValue = Box<dyn FnOnce(*mut JSContext, MutableHandleValue)>;
+/// produces the next value of an iterator, returns Ok(false) when the iterator is done
+pub type IteratorNext = Box<dyn FnMut(*mut JSContext, MutableHandleValue) -> Result<bool, String>>;
+/// creates a new iterator for an instance, this is called for every for..of loop or spread
+pub type IteratorFactory = Box<dyn Fn(*mut JSContext, i32) -> Result<IteratorNext, String>>;
+pub type IndexLength = Rc<dyn Fn(*mut JSContext, i32) -> Result<u32, String>>;
+pub type IndexGetter =
+    Box<dyn Fn(*mut JSContext, i32, u32, MutableHandleValue) -> Result<(), String>>;
+pub type IndexSetter = Box<dyn Fn(*mut JSContext, i32, u32, HandleValue) -> Result<(), String>>;
+pub type DynamicGetter =
+    Box<dyn Fn(*mut JSContext, i32, &str, MutableHandleValue) -> Result<(), String>>;
+pub type DynamicSetter = Box<dyn Fn(*mut JSContext, i32, &str, HandleValue) -> Result<(), String>>;
+pub type DynamicHas = Box<dyn Fn(*mut JSContext, i32, &str) -> Result<bool, String>>;
+/// deletes a dynamic property, returns Ok(false) if the property can not be deleted
+pub type DynamicDelete = Box<dyn Fn(*mut JSContext, i32, &str) -> Result<bool, String>>;
+pub type DynamicKeys = Box<dyn Fn(*mut JSContext, i32) -> Result<Vec<String>, String>>;
+
+/// the hooks of a proxy class with an open ended set of properties
+struct DynamicProperties {
+    getter: DynamicGetter,
+    setter: DynamicSetter,
+    has: DynamicHas,
+    delete: DynamicDelete,
+    keys: DynamicKeys,
+}
+
+/// the attributes of a property of a proxy class
+/// the default is an enumerable property which can not be deleted or redefined
+#[derive(Clone, Copy, Debug)]
+pub struct PropertyAttributes {
+    /// the property is listed by Object.keys(), for..in and JSON.stringify()
+    pub enumerable: bool,
+    /// the property may be deleted or redefined from script
+    pub configurable: bool,
+}
+
+impl Default for PropertyAttributes {
+    fn default() -> Self {
+        PropertyAttributes {
+            enumerable: true,
+            configurable: false,
+        }
+    }
+}
+
+impl PropertyAttributes {
+    /// the JSPROP_* flags for defining the property
+    /// JSPROP_GETTER and JSPROP_SETTER are not set because the accessors are native functions
+    fn to_flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.enumerable {
+            flags |= mozjs::jsapi::JSPROP_ENUMERATE as u32;
+        }
+        if !self.configurable {
+            flags |= mozjs::jsapi::JSPROP_PERMANENT as u32;
+        }
+        flags
+    }
+}
+
+/// a property with an optional getter and setter, a property without a setter is read only
+struct Accessor<G, S> {
+    getter: Option<G>,
+    setter: Option<S>,
+    attributes: PropertyAttributes,
+}
+
+/// the options for adding an event listener, in script these can be passed as the third argument
+/// of addEventListener e.g. obj.addEventListener('saved', listener, {once: true});
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EventListenerOptions {
+    /// remove the listener after it was called once
+    pub once: bool,
+}
+
+enum EventListenerFn {
+    Script(EsPersistentRooted),
+    Rust(i32, EventListener),
+}
+
+struct EventListenerEntry {
+    listener: EventListenerFn,
+    once: bool,
+}
+
+type EventListeners = Vec<Rc<EventListenerEntry>>;
 
 /// create a class def in the runtime which constructs and calls methods in a rust proxy
 pub struct Proxy {
     pub namespace: Vec<&'static str>,
     pub class_name: &'static str,
+    // unique per build so a constructor of a replaced class can be told apart from the current one
+    id: usize,
+    extends: Option<String>,
+    singleton: bool,
     constructor: Option<Constructor>,
     finalizer: Option<Box<dyn Fn(i32) -> ()>>,
-    properties: HashMap<&'static str, (Getter, Setter)>,
+    properties: HashMap<&'static str, Accessor<Getter, Setter>>,
 
     // todo add cx as second arg to methods
     methods: HashMap<&'static str, Method>,
     native_methods: HashMap<&'static str, JSNative>,
     events: HashSet<&'static str>,
-    event_listeners: RefCell<HashMap<i32, HashMap<&'static str, Vec<EsPersistentRooted>>>>,
-    static_properties: HashMap<&'static str, (StaticGetter, StaticSetter)>,
+    event_listeners: RefCell<HashMap<i32, HashMap<&'static str, EventListeners>>>,
+    iterator: Option<IteratorFactory>,
+    index_length: Option<IndexLength>,
+    index_getter: Option<IndexGetter>,
+    index_setter: Option<IndexSetter>,
+    dynamic_properties: Option<DynamicProperties>,
+    static_properties: HashMap<&'static str, Accessor<StaticGetter, StaticSetter>>,
     static_methods: HashMap<&'static str, StaticMethod>,
     static_native_methods: HashMap<&'static str, JSNative>,
     static_events: HashSet<&'static str>,
-    static_event_listeners: RefCell<HashMap<&'static str, Vec<EsPersistentRooted>>>,
+    static_event_listeners: RefCell<HashMap<&'static str, EventListeners>>,
+    // the constants are defined when the class is built, only their names are kept for describe()
+    constant_names: Vec<&'static str>,
+    static_constant_names: Vec<&'static str>,
+    doc: Option<String>,
+    member_docs: HashMap<String, String>,
 }
 
 /// the builder struct for Proxy
 pub struct ProxyBuilder {
     pub namespace: Vec<&'static str>,
     pub class_name: &'static str,
+    extends: Option<String>,
     constructor: Option<Constructor>,
     finalizer: Option<Box<dyn Fn(i32) -> ()>>,
-    properties: HashMap<&'static str, (Getter, Setter)>,
+    properties: HashMap<&'static str, Accessor<Getter, Setter>>,
     methods: HashMap<&'static str, Method>,
     native_methods: HashMap<&'static str, JSNative>,
     events: HashSet<&'static str>,
-    static_properties: HashMap<&'static str, (StaticGetter, StaticSetter)>,
+    constants: HashMap<&'static str, ConstantValue>,
+    iterator: Option<IteratorFactory>,
+    index_length: Option<IndexLength>,
+    index_getter: Option<IndexGetter>,
+    index_setter: Option<IndexSetter>,
+    dynamic_properties: Option<DynamicProperties>,
+    static_properties: HashMap<&'static str, Accessor<StaticGetter, StaticSetter>>,
     static_methods: HashMap<&'static str, StaticMethod>,
     static_native_methods: HashMap<&'static str, JSNative>,
     static_events: HashSet<&'static str>,
+    static_constants: HashMap<&'static str, ConstantValue>,
+    doc: Option<String>,
+    member_docs: HashMap<String, String>,
 }
 
 thread_local! {
     static PROXY_INSTANCE_IDS: RefCell<HashMap<usize, i32>> = RefCell::new(HashMap::new());
-    static PROXY_INSTANCE_CLASSNAMES: RefCell<HashMap<i32, String>> = RefCell::new(HashMap::new());
+    // the proxy of every instance by the address of the instance, instances keep their proxy
+    // so they can still be finalized when the class is unregistered or replaced
+    static PROXY_INSTANCE_PROXIES: RefCell<HashMap<usize, Arc<Proxy>>> = RefCell::new(HashMap::new());
+    static NEXT_PROXY_ID: RefCell<usize> = RefCell::new(0);
     static PROXIES: RefCell<HashMap<String, Arc<Proxy>>> = RefCell::new(HashMap::new());
+    static NEXT_LISTENER_ID: RefCell<i32> = RefCell::new(0);
+    // true while the resolve hook defines a dynamic property so the addProperty hook ignores it
+    static DEFINING_DYNAMIC_PROP: RefCell<bool> = RefCell::new(false);
+    // true while the enumerate hook removes stale dynamic properties so the delProperty hook does
+    // not pass them to the delete hook and the enumerate hook is not reentered
+    static REMOVING_DYNAMIC_PROPS: RefCell<bool> = RefCell::new(false);
+    static PROXY_ITERATORS: RefCell<AutoIdMap<Rc<RefCell<IteratorNext>>>> = RefCell::new(AutoIdMap::new());
+    static PROXY_ITERATOR_IDS: RefCell<HashMap<usize, usize>> = RefCell::new(HashMap::new());
 }
 
 /// find a ref to a proxy, use full canonical name as key, needs to run in the workerthread of the EsRuntimeWrapper
@@ -166,7 +293,8 @@ thread_local! {
 /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
 ///     sm_rt.do_with_jsapi(|_rt, cx, global| {
 ///         // create an example proxy
-///         let _proxy = ProxyBuilder::new(vec!["my", "biz"], "MyClass").build(cx, global);
+///         let _proxy = ProxyBuilder::new(vec!["my", "biz"], "MyClass").build(cx, global)
+///             .ok().expect("could not build proxy");
 ///         // get that proxy later
 ///         let my_biz_MyClass_proxy_opt = get_proxy("my.biz.MyClass");
 ///         assert!(my_biz_MyClass_proxy_opt.is_some());
@@ -181,11 +309,391 @@ pub fn get_proxy(canonical_name: &str) -> Option<Arc<Proxy>> {
     })
 }
 
+/// unregister a proxy class, the class is removed from its namespace and can no longer be constructed
+///
+/// existing instances keep working and are still finalized when they are garbage collected,
+/// a class can not be unregistered while other registered classes extend it
+/// # Example
+/// ```no_run
+/// use es_runtime::jsapi_utils::reflection::{get_proxy, unregister_proxy, ProxyBuilder};
+/// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+///
+/// let rt = EsRuntimeBuilder::new().build();
+/// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+///     sm_rt.do_with_jsapi(|_rt, cx, global| {
+///         let _proxy = ProxyBuilder::new(vec!["my", "biz"], "MyPlugin").build(cx, global)
+///             .ok().expect("could not build proxy");
+///         unregister_proxy(cx, global, "my.biz.MyPlugin").ok().expect("could not unregister");
+///         assert!(get_proxy("my.biz.MyPlugin").is_none());
+///     });
+/// });
+/// ```
+pub fn unregister_proxy(
+    cx: *mut JSContext,
+    scope: HandleObject,
+    canonical_name: &str,
+) -> Result<(), String> {
+    if get_proxy(canonical_name).is_none() {
+        return Err(format!("no proxy registered as {}", canonical_name));
+    }
+    check_not_extended(canonical_name)?;
+    remove_proxy(cx, scope, canonical_name)
+}
+
+/// a class which is extended by other registered classes can not be unregistered or replaced
+/// because the subclasses would keep inheriting from the removed constructor and prototype
+fn check_not_extended(canonical_name: &str) -> Result<(), String> {
+    let sub_classes: Vec<String> = PROXIES.with(|proxies_rc| {
+        let proxies = &*proxies_rc.borrow();
+        proxies
+            .iter()
+            .filter(|(_name, proxy)| proxy.extends.as_deref() == Some(canonical_name))
+            .map(|(name, _proxy)| name.clone())
+            .collect()
+    });
+    if sub_classes.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is extended by {}",
+            canonical_name,
+            sub_classes.join(", ")
+        ))
+    }
+}
+
+/// remove a proxy from PROXIES and its constructor or singleton object from its namespace
+fn remove_proxy(
+    cx: *mut JSContext,
+    scope: HandleObject,
+    canonical_name: &str,
+) -> Result<(), String> {
+    let proxy_opt = PROXIES.with(|proxies_rc| {
+        let proxies = &mut *proxies_rc.borrow_mut();
+        proxies.remove(canonical_name)
+    });
+    if let Some(proxy) = proxy_opt {
+        let pkg_obj = crate::jsapi_utils::objects::get_or_define_namespace(
+            cx,
+            scope,
+            proxy.namespace.clone(),
+        );
+        rooted!(in (cx) let pkg_root = pkg_obj);
+        match crate::jsapi_utils::objects::delete_es_obj_prop(
+            cx,
+            pkg_root.handle(),
+            proxy.class_name,
+        ) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!(
+                "could not remove {} from its namespace",
+                canonical_name
+            )),
+            Err(err) => Err(err.message),
+        }
+    } else {
+        Ok(())
+    }
+}
+
+/// a member of a proxy class, see Proxy::describe
+#[derive(Clone, Debug)]
+pub struct MemberDescription {
+    pub name: String,
+    pub doc: Option<String>,
+}
+
+/// a property of a proxy class, see Proxy::describe
+#[derive(Clone, Debug)]
+pub struct PropertyDescription {
+    pub name: String,
+    pub doc: Option<String>,
+    pub readable: bool,
+    pub writable: bool,
+    pub enumerable: bool,
+}
+
+/// the members of a proxy class, see Proxy::describe
+#[derive(Clone, Debug)]
+pub struct ProxyDescription {
+    pub namespace: Vec<String>,
+    pub class_name: String,
+    pub doc: Option<String>,
+    pub extends: Option<String>,
+    pub singleton: bool,
+    pub constructable: bool,
+    pub methods: Vec<MemberDescription>,
+    pub properties: Vec<PropertyDescription>,
+    pub constants: Vec<MemberDescription>,
+    pub events: Vec<MemberDescription>,
+    pub static_methods: Vec<MemberDescription>,
+    pub static_properties: Vec<PropertyDescription>,
+    pub static_constants: Vec<MemberDescription>,
+    pub static_events: Vec<MemberDescription>,
+    pub iterable: bool,
+    pub indexed: bool,
+    pub index_writable: bool,
+    pub dynamic_properties: bool,
+}
+
+impl ProxyDescription {
+    /// get the canonical name of the proxy class, this includes the namespace
+    /// e.g. "my.biz.MyApp"
+    pub fn get_canonical_name(&self) -> String {
+        format!("{}.{}", self.namespace.join("."), self.class_name)
+    }
+
+    /// create a TypeScript declaration of the class (or of the const for a singleton)
+    /// the declaration is not wrapped in its namespace, see generate_typescript_declarations
+    pub fn to_typescript(&self, indent: &str) -> String {
+        let mut out = String::new();
+        let inner = format!("{}    ", indent);
+        let keyword = if self.namespace.is_empty() {
+            "declare"
+        } else {
+            "export"
+        };
+        ts_doc(&mut out, indent, &self.doc);
+
+        if self.singleton {
+            out.push_str(&format!(
+                "{}{} const {}: {{\n",
+                indent, keyword, self.class_name
+            ));
+            self.static_members_to_typescript(&mut out, &inner, "");
+            out.push_str(&format!("{}}};\n", indent));
+            return out;
+        }
+
+        let extends = match &self.extends {
+            Some(parent_name) => format!(" extends {}", parent_name.trim_start_matches('.')),
+            None => "".to_string(),
+        };
+        out.push_str(&format!(
+            "{}{} class {}{} {{\n",
+            indent, keyword, self.class_name, extends
+        ));
+        if self.constructable {
+            out.push_str(&format!("{}constructor(...args: any[]);\n", inner));
+        } else {
+            out.push_str(&format!("{}private constructor();\n", inner));
+        }
+        for constant in &self.constants {
+            ts_doc(&mut out, &inner, &constant.doc);
+            out.push_str(&format!(
+                "{}readonly {}: any;\n",
+                inner,
+                ts_name(&constant.name)
+            ));
+        }
+        for prop in &self.properties {
+            ts_property(&mut out, &inner, "", prop);
+        }
+        for method in &self.methods {
+            ts_doc(&mut out, &inner, &method.doc);
+            out.push_str(&format!(
+                "{}{}(...args: any[]): any;\n",
+                inner,
+                ts_name(&method.name)
+            ));
+        }
+        ts_events(&mut out, &inner, "", &self.events);
+        if self.iterable {
+            out.push_str(&format!("{}[Symbol.iterator](): Iterator<any>;\n", inner));
+        }
+        if self.indexed {
+            let readonly = if self.index_writable { "" } else { "readonly " };
+            out.push_str(&format!("{}{}[index: number]: any;\n", inner, readonly));
+        }
+        if self.dynamic_properties {
+            out.push_str(&format!("{}[key: string]: any;\n", inner));
+        }
+        self.static_members_to_typescript(&mut out, &inner, "static ");
+        out.push_str(&format!("{}}}\n", indent));
+        out
+    }
+
+    fn static_members_to_typescript(&self, out: &mut String, indent: &str, prefix: &str) {
+        for constant in &self.static_constants {
+            ts_doc(out, indent, &constant.doc);
+            out.push_str(&format!(
+                "{}{}readonly {}: any;\n",
+                indent,
+                prefix,
+                ts_name(&constant.name)
+            ));
+        }
+        for prop in &self.static_properties {
+            ts_property(out, indent, prefix, prop);
+        }
+        for method in &self.static_methods {
+            ts_doc(out, indent, &method.doc);
+            out.push_str(&format!(
+                "{}{}{}(...args: any[]): any;\n",
+                indent,
+                prefix,
+                ts_name(&method.name)
+            ));
+        }
+        ts_events(out, indent, prefix, &self.static_events);
+    }
+}
+
+fn ts_doc(out: &mut String, indent: &str, doc: &Option<String>) {
+    if let Some(doc) = doc {
+        out.push_str(&format!("{}/**\n", indent));
+        for line in doc.lines() {
+            out.push_str(&format!("{} * {}\n", indent, line).replace(" * \n", " *\n"));
+        }
+        out.push_str(&format!("{} */\n", indent));
+    }
+}
+
+/// quote a member name if it is not a valid identifier
+fn ts_name(name: &str) -> String {
+    let is_ident = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_ident {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+fn ts_property(out: &mut String, indent: &str, prefix: &str, prop: &PropertyDescription) {
+    ts_doc(out, indent, &prop.doc);
+    // TypeScript has no write only properties so those are declared as normal properties
+    let readonly = if prop.readable && !prop.writable {
+        "readonly "
+    } else {
+        ""
+    };
+    out.push_str(&format!(
+        "{}{}{}{}: any;\n",
+        indent,
+        prefix,
+        readonly,
+        ts_name(&prop.name)
+    ));
+}
+
+fn ts_events(out: &mut String, indent: &str, prefix: &str, events: &[MemberDescription]) {
+    if events.is_empty() {
+        return;
+    }
+    let types: Vec<String> = events.iter().map(|evt| format!("{:?}", evt.name)).collect();
+    let types = types.join(" | ");
+    for evt in events {
+        if let Some(doc) = &evt.doc {
+            ts_doc(out, indent, &Some(format!("{}: {}", evt.name, doc)));
+        }
+    }
+    out.push_str(&format!(
+        "{}{}addEventListener(type: {}, listener: (evt: any) => any): void;\n",
+        indent, prefix, types
+    ));
+    out.push_str(&format!(
+        "{}{}removeEventListener(type: {}, listener: (evt: any) => any): void;\n",
+        indent, prefix, types
+    ));
+    out.push_str(&format!(
+        "{}{}dispatchEvent(type: {}, evt?: any): boolean;\n",
+        indent, prefix, types
+    ));
+}
+
+/// describe all registered proxy classes, sorted by their canonical name
+pub fn describe_proxies() -> Vec<ProxyDescription> {
+    let mut ret: Vec<ProxyDescription> = PROXIES.with(|proxies_rc| {
+        let proxies = &*proxies_rc.borrow();
+        proxies.values().map(|proxy| proxy.describe()).collect()
+    });
+    ret.sort_by_key(|desc| desc.get_canonical_name());
+    ret
+}
+
+/// generate a TypeScript declaration file (.d.ts) for all registered proxy classes and the
+/// passed global functions, the classes are declared in their namespaces
+pub fn generate_typescript_declarations(mut global_functions: Vec<&str>) -> String {
+    let mut out = String::new();
+
+    global_functions.sort();
+    for func_name in global_functions {
+        out.push_str(&format!(
+            "declare function {}(...args: any[]): any;\n",
+            func_name
+        ));
+    }
+
+    let mut namespaces: Vec<(String, Vec<ProxyDescription>)> = vec![];
+    for desc in describe_proxies() {
+        let ns = desc.namespace.join(".");
+        match namespaces.iter_mut().find(|(name, _descs)| name.eq(&ns)) {
+            Some((_name, descs)) => descs.push(desc),
+            None => namespaces.push((ns, vec![desc])),
+        }
+    }
+    namespaces.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (ns, descs) in namespaces {
+        out.push('\n');
+        if ns.is_empty() {
+            for desc in descs {
+                out.push_str(&desc.to_typescript(""));
+            }
+        } else {
+            out.push_str(&format!("declare namespace {} {{\n", ns));
+            for desc in descs {
+                out.push_str(&desc.to_typescript("    "));
+            }
+            out.push_str("}\n");
+        }
+    }
+
+    out
+}
+
+/// get the Proxy of a constructor, a singleton object or an instance of a proxy class
+pub fn get_proxy_for_class_or_instance(
+    cx: *mut JSContext,
+    obj: *mut JSObject,
+) -> Option<Arc<Proxy>> {
+    if is_proxy_instance(obj) {
+        get_proxy_for(cx, obj)
+    } else {
+        get_static_proxy_for(cx, obj)
+    }
+}
+
 impl Proxy {
-    fn new(cx: *mut JSContext, scope: HandleObject, builder: &mut ProxyBuilder) -> Arc<Self> {
+    fn from_builder(builder: &mut ProxyBuilder, singleton: bool) -> Result<Self, String> {
+        // check the parent before the builder is drained so a failed build does not touch anything
+        let parent_opt = match &builder.extends {
+            Some(parent_name) => match get_proxy(parent_name.as_str()) {
+                Some(parent) => Some(parent),
+                None => {
+                    return Err(format!(
+                        "proxy {} extends {} which has not been built",
+                        builder.class_name, parent_name
+                    ));
+                }
+            },
+            None => None,
+        };
+
         let mut ret = Proxy {
             namespace: builder.namespace.clone(),
             class_name: builder.class_name,
+            id: NEXT_PROXY_ID.with(|next_id_rc| {
+                let next_id = &mut *next_id_rc.borrow_mut();
+                *next_id += 1;
+                *next_id
+            }),
+            extends: builder.extends.clone(),
+            singleton,
             constructor: unsafe { replace(&mut builder.constructor, None) },
             finalizer: unsafe { replace(&mut builder.finalizer, None) },
             properties: HashMap::new(),
@@ -193,11 +701,20 @@ impl Proxy {
             native_methods: HashMap::new(),
             events: HashSet::new(),
             event_listeners: RefCell::new(HashMap::new()),
+            iterator: builder.iterator.take(),
+            index_length: builder.index_length.take(),
+            index_getter: builder.index_getter.take(),
+            index_setter: builder.index_setter.take(),
+            dynamic_properties: builder.dynamic_properties.take(),
             static_properties: HashMap::new(),
             static_methods: HashMap::new(),
             static_native_methods: HashMap::new(),
             static_events: HashSet::new(),
             static_event_listeners: RefCell::new(HashMap::new()),
+            constant_names: builder.constants.keys().cloned().collect(),
+            static_constant_names: builder.static_constants.keys().cloned().collect(),
+            doc: builder.doc.take(),
+            member_docs: builder.member_docs.drain().collect(),
         };
 
         builder.properties.drain().all(|e| {
@@ -240,7 +757,37 @@ impl Proxy {
             true
         });
 
-        // todo if no constructor: create an object instead of a function
+        if let Some(parent) = parent_opt {
+            // the events of the parent may also be dispatched on instances of the subclass
+            for evt_type in &parent.events {
+                ret.events.insert(evt_type);
+            }
+            for evt_type in &parent.static_events {
+                ret.static_events.insert(evt_type);
+            }
+        }
+
+        Ok(ret)
+    }
+
+    fn new(
+        cx: *mut JSContext,
+        scope: HandleObject,
+        builder: &mut ProxyBuilder,
+    ) -> Result<Arc<Self>, String> {
+        let ret = Proxy::from_builder(builder, false)?;
+
+        // get the parent class objects before anything is defined so a failure leaves no half built class
+        rooted!(in (cx) let mut parent_func_root = ptr::null_mut::<JSObject>());
+        rooted!(in (cx) let mut parent_proto_root = ptr::null_mut::<JSObject>());
+        ret.get_parent_class_objects(
+            cx,
+            scope,
+            parent_func_root.handle_mut(),
+            parent_proto_root.handle_mut(),
+        )?;
+
+        ret.replace_registered(cx, scope)?;
 
         // todo get_or_define with rval
         let pkg_obj =
@@ -255,26 +802,126 @@ impl Proxy {
                 Some(proxy_construct),
             );
 
+        rooted!(in (cx) let func_root = func as *mut JSObject);
+
+        ret.set_class_props(cx, func_root.handle());
+        crate::jsapi_utils::objects::set_es_obj_to_string_tag(
+            cx,
+            func_root.handle(),
+            ret.get_canonical_name().as_str(),
+        )
+        .ok()
+        .expect("could not set toStringTag");
+
+        ret.init_prototype(
+            cx,
+            func_root.handle(),
+            parent_func_root.handle(),
+            parent_proto_root.handle(),
+            &mut builder.constants,
+        );
+        define_constants(cx, func_root.handle(), &mut builder.static_constants);
+        ret.init_static_properties(cx, func_root.handle());
+        ret.init_static_methods(cx, func_root.handle());
+        ret.init_static_events(cx, func_root.handle());
+
+        Ok(ret.register())
+    }
+
+    /// create a plain object instead of a constructor, the object only has the static members
+    fn new_singleton(
+        cx: *mut JSContext,
+        scope: HandleObject,
+        builder: &mut ProxyBuilder,
+    ) -> Result<Arc<Self>, String> {
+        if !(builder.constructor.is_none()
+            && builder.extends.is_none()
+            && builder.properties.is_empty()
+            && builder.methods.is_empty()
+            && builder.native_methods.is_empty()
+            && builder.events.is_empty()
+            && builder.constants.is_empty()
+            && builder.iterator.is_none()
+            && builder.index_getter.is_none()
+            && builder.dynamic_properties.is_none())
+        {
+            return Err(format!(
+                "singleton {} can only have static members",
+                builder.class_name
+            ));
+        }
+
+        let ret = Proxy::from_builder(builder, true)?;
+        ret.replace_registered(cx, scope)?;
+
+        let pkg_obj =
+            crate::jsapi_utils::objects::get_or_define_namespace(cx, scope, ret.namespace.clone());
+        rooted!(in (cx) let pkg_root = pkg_obj);
+
+        rooted!(in (cx) let obj_root = crate::jsapi_utils::objects::new_object(cx));
+
         let cname = ret.get_canonical_name();
+        ret.set_class_props(cx, obj_root.handle());
+        // so the object prints as [object my.biz.MyApp] instead of [object Object]
+        crate::jsapi_utils::objects::set_es_obj_to_string_tag(
+            cx,
+            obj_root.handle(),
+            cname.as_str(),
+        )
+        .ok()
+        .expect("could not set toStringTag");
+
+        define_constants(cx, obj_root.handle(), &mut builder.static_constants);
+        ret.init_static_properties(cx, obj_root.handle());
+        ret.init_static_methods(cx, obj_root.handle());
+        ret.init_static_events(cx, obj_root.handle());
+
+        // not permanent so the singleton can be unregistered
+        rooted!(in (cx) let obj_val_root = ObjectValue(obj_root.get()));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_hidden(
+            cx,
+            pkg_root.handle(),
+            ret.class_name,
+            obj_val_root.handle(),
+        );
+
+        Ok(ret.register())
+    }
+
+    /// set the hidden props which identify the constructor or singleton object of this proxy
+    fn set_class_props(&self, cx: *mut JSContext, obj: HandleObject) {
+        let cname = self.get_canonical_name();
         rooted!(in (cx) let cname_root = crate::jsapi_utils::new_es_value_from_str(cx, cname.as_str()));
         crate::jsapi_utils::objects::set_es_obj_prop_val_permanent(
             cx,
-            unsafe { HandleObject::from_marked_location(&(func as *mut JSObject)) },
+            obj,
             PROXY_PROP_CLASS_NAME,
             cname_root.handle(),
         );
+        rooted!(in (cx) let id_root = mozjs::jsval::Int32Value(self.id as i32));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_permanent(
+            cx,
+            obj,
+            PROXY_PROP_CLASS_ID,
+            id_root.handle(),
+        );
+    }
 
-        ret.init_static_properties(cx, unsafe {
-            mozjs::rust::HandleObject::from_marked_location(&(func as *mut JSObject))
-        });
-        ret.init_static_methods(cx, unsafe {
-            mozjs::rust::HandleObject::from_marked_location(&(func as *mut JSObject))
-        });
-        ret.init_static_events(cx, unsafe {
-            mozjs::rust::HandleObject::from_marked_location(&(func as *mut JSObject))
-        });
+    /// building a class with the canonical name of a registered class replaces that class,
+    /// unless that class is extended by other registered classes
+    fn replace_registered(&self, cx: *mut JSContext, scope: HandleObject) -> Result<(), String> {
+        let cname = self.get_canonical_name();
+        if get_proxy(cname.as_str()).is_some() {
+            trace!("replacing proxy {}", cname);
+            check_not_extended(cname.as_str())
+                .map_err(|err| format!("could not replace {}: {}", cname, err))?;
+            remove_proxy(cx, scope, cname.as_str())?;
+        }
+        Ok(())
+    }
 
-        let ret_arc = Arc::new(ret);
+    fn register(self) -> Arc<Self> {
+        let ret_arc = Arc::new(self);
 
         PROXIES.with(|map_rc: &RefCell<HashMap<String, Arc<Proxy>>>| {
             let map = &mut *map_rc.borrow_mut();
@@ -284,12 +931,130 @@ impl Proxy {
         ret_arc
     }
 
+    /// true if this proxy is a singleton object instead of a class, see ProxyBuilder::build_singleton
+    pub fn is_singleton(&self) -> bool {
+        self.singleton
+    }
+
     /// get the canonical name of the proxy class, this includes the namespace
     /// e.g. "my.biz.MyApp"
     pub fn get_canonical_name(&self) -> String {
         format!("{}.{}", self.namespace.join("."), self.class_name)
     }
 
+    /// get the canonical name of the proxy class this class extends, if any
+    pub fn get_extends(&self) -> Option<&str> {
+        self.extends.as_deref()
+    }
+
+    /// describe the members of the proxy class, inherited members are described by the class they
+    /// are declared in, see ProxyDescription::extends
+    pub fn describe(&self) -> ProxyDescription {
+        let member = |name: &str| MemberDescription {
+            name: name.to_string(),
+            doc: self.member_docs.get(name).cloned(),
+        };
+        let members = |names: Vec<&str>| {
+            let mut ret: Vec<MemberDescription> = names.into_iter().map(member).collect();
+            ret.sort_by(|a, b| a.name.cmp(&b.name));
+            ret
+        };
+        let property =
+            |name: &str, readable: bool, writable: bool, enumerable: bool| PropertyDescription {
+                name: name.to_string(),
+                doc: self.member_docs.get(name).cloned(),
+                readable,
+                writable,
+                enumerable,
+            };
+
+        let mut properties: Vec<PropertyDescription> = self
+            .properties
+            .iter()
+            .map(|(name, prop)| {
+                property(
+                    *name,
+                    prop.getter.is_some(),
+                    prop.setter.is_some(),
+                    prop.attributes.enumerable,
+                )
+            })
+            .collect();
+        properties.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut static_properties: Vec<PropertyDescription> = self
+            .static_properties
+            .iter()
+            .map(|(name, prop)| {
+                property(
+                    *name,
+                    prop.getter.is_some(),
+                    prop.setter.is_some(),
+                    prop.attributes.enumerable,
+                )
+            })
+            .collect();
+        static_properties.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let constructable = self.constructor.is_some()
+            || self
+                .extends
+                .as_ref()
+                .and_then(|parent_name| get_proxy(parent_name))
+                .and_then(|parent| find_proxy_in_chain(parent, |p| p.constructor.is_some()))
+                .is_some();
+
+        ProxyDescription {
+            namespace: self.namespace.iter().map(|ns| ns.to_string()).collect(),
+            class_name: self.class_name.to_string(),
+            doc: self.doc.clone(),
+            extends: self.extends.clone(),
+            singleton: self.singleton,
+            constructable,
+            methods: members(
+                self.methods
+                    .keys()
+                    .chain(self.native_methods.keys())
+                    .cloned()
+                    .collect(),
+            ),
+            properties,
+            constants: members(self.constant_names.clone()),
+            events: members(self.events.iter().cloned().collect()),
+            static_methods: members(
+                self.static_methods
+                    .keys()
+                    .chain(self.static_native_methods.keys())
+                    .cloned()
+                    .collect(),
+            ),
+            static_properties,
+            static_constants: members(self.static_constant_names.clone()),
+            static_events: members(self.static_events.iter().cloned().collect()),
+            iterable: self.iterator.is_some(),
+            indexed: self.index_getter.is_some(),
+            index_writable: self.index_setter.is_some(),
+            dynamic_properties: self.dynamic_properties.is_some(),
+        }
+    }
+
+    /// get the constructor function of the proxy class
+    pub fn get_constructor(
+        &self,
+        cx: *mut JSContext,
+        scope: HandleObject,
+        return_handle: MutableHandleValue,
+    ) -> Result<(), EsErrorInfo> {
+        let ns_obj =
+            crate::jsapi_utils::objects::get_or_define_namespace(cx, scope, self.namespace.clone());
+        rooted!(in (cx) let ns_root = ns_obj);
+        crate::jsapi_utils::objects::get_es_obj_prop_val(
+            cx,
+            ns_root.handle(),
+            self.class_name,
+            return_handle,
+        )
+    }
+
     /// instantiate a new instance of the proxy class
     pub fn new_instance(
         &self,
@@ -298,12 +1063,8 @@ impl Proxy {
         args: HandleValueArray,
         return_handle: MutableHandleObject,
     ) -> Result<(), EsErrorInfo> {
-        // to be used for EsValueFacade::new_proxy()
-
-        let namespace = vec!["a", "b", "Client"];
-        let constructor_obj =
-            crate::jsapi_utils::objects::get_or_define_namespace(cx, scope, namespace);
-        rooted!(in (cx) let constructor_root = ObjectValue(constructor_obj));
+        rooted!(in (cx) let mut constructor_root = UndefinedValue());
+        self.get_constructor(cx, scope, constructor_root.handle_mut())?;
         crate::jsapi_utils::objects::new_from_constructor(
             cx,
             constructor_root.handle(),
@@ -320,7 +1081,21 @@ impl Proxy {
         cx: *mut JSContext,
         event_obj: mozjs::jsapi::HandleValue,
     ) {
-        dispatch_event_for_proxy(cx, self, obj_id, event_name, event_obj);
+        dispatch_event_for_proxy(cx, self, obj_id, event_name, event_obj, &mut |_cx, _res| {});
+    }
+
+    /// dispatch an event for a specific instance of the proxy class and get the results of the listeners
+    /// on_result is called with the return value of every listener
+    /// returns true if a listener called preventDefault() on the event object or returned false
+    pub fn dispatch_event_sync(
+        &self,
+        obj_id: i32,
+        event_name: &str,
+        cx: *mut JSContext,
+        event_obj: mozjs::jsapi::HandleValue,
+        on_result: &mut dyn FnMut(*mut JSContext, HandleValue),
+    ) -> bool {
+        dispatch_event_for_proxy(cx, self, obj_id, event_name, event_obj, on_result)
     }
 
     /// dispatch a static event for the proxy class
@@ -330,54 +1105,279 @@ impl Proxy {
         cx: *mut JSContext,
         event_obj: mozjs::jsapi::HandleValue,
     ) {
-        dispatch_static_event_for_proxy(cx, self, event_name, event_obj);
+        dispatch_static_event_for_proxy(cx, self, event_name, event_obj, &mut |_cx, _res| {});
     }
 
-    fn init_static_properties(&self, cx: *mut JSContext, func: HandleObject) {
-        // this is actually how static_props should work, not instance props.. they should be resolved from the proxy_op
-        for prop_name in self.static_properties.keys() {
-            // https://doc.servo.org/mozjs/jsapi/fn.JS_DefineProperty1.html
-            // mozjs::jsapi::JS_DefineProperty1
-            // todo move this to jsapi_utils::object
-            let n = format!("{}\0", prop_name);
-
-            let ok = unsafe {
-                mozjs::jsapi::JS_DefineProperty1(
-                    cx,
-                    func.into(),
-                    n.as_ptr() as *const libc::c_char,
-                    Some(proxy_static_getter),
-                    Some(proxy_static_setter),
-                    (mozjs::jsapi::JSPROP_PERMANENT
-                        & mozjs::jsapi::JSPROP_GETTER
-                        & mozjs::jsapi::JSPROP_SETTER) as u32,
-                )
-            };
-            assert!(ok);
+    /// dispatch a static event for the proxy class and get the results of the listeners
+    /// see dispatch_event_sync
+    pub fn dispatch_static_event_sync(
+        &self,
+        event_name: &str,
+        cx: *mut JSContext,
+        event_obj: mozjs::jsapi::HandleValue,
+        on_result: &mut dyn FnMut(*mut JSContext, HandleValue),
+    ) -> bool {
+        dispatch_static_event_for_proxy(cx, self, event_name, event_obj, on_result)
+    }
+
+    /// add a rust listener for an event of a specific instance of the proxy class
+    /// returns an id which can be used to remove the listener
+    pub fn add_event_listener(
+        &self,
+        obj_id: i32,
+        event_name: &str,
+        options: EventListenerOptions,
+        listener: EventListener,
+    ) -> Result<i32, String> {
+        let evt_type = self.events.get(event_name).ok_or_else(|| {
+            format!(
+                "event {} is not defined for {}",
+                event_name, self.class_name
+            )
+        })?;
+        let listener_id = next_listener_id();
+        let pel = &mut *self.event_listeners.borrow_mut();
+        pel.entry(obj_id)
+            .or_insert_with(HashMap::new)
+            .entry(*evt_type)
+            .or_insert_with(Vec::new)
+            .push(Rc::new(EventListenerEntry {
+                listener: EventListenerFn::Rust(listener_id, listener),
+                once: options.once,
+            }));
+        Ok(listener_id)
+    }
+
+    /// remove a rust listener which was added with add_event_listener
+    pub fn remove_event_listener(&self, obj_id: i32, event_name: &str, listener_id: i32) {
+        let pel = &mut *self.event_listeners.borrow_mut();
+        if let Some(listener_vec) = pel
+            .get_mut(&obj_id)
+            .and_then(|obj_map| obj_map.get_mut(event_name))
+        {
+            listener_vec.retain(|entry| !entry.is_rust_listener(listener_id));
         }
     }
 
-    fn init_static_methods(&self, cx: *mut JSContext, func: HandleObject) {
-        trace!("init static methods for {}", self.class_name);
-        for method_name in self.static_methods.keys() {
-            trace!("init static method {} for {}", method_name, self.class_name);
-            crate::jsapi_utils::functions::define_native_function(
+    /// add a rust listener for a static event of the proxy class
+    /// returns an id which can be used to remove the listener
+    pub fn add_static_event_listener(
+        &self,
+        event_name: &str,
+        options: EventListenerOptions,
+        listener: EventListener,
+    ) -> Result<i32, String> {
+        let evt_type = self.static_events.get(event_name).ok_or_else(|| {
+            format!(
+                "static event {} is not defined for {}",
+                event_name, self.class_name
+            )
+        })?;
+        let listener_id = next_listener_id();
+        let obj_map = &mut *self.static_event_listeners.borrow_mut();
+        obj_map
+            .entry(*evt_type)
+            .or_insert_with(Vec::new)
+            .push(Rc::new(EventListenerEntry {
+                listener: EventListenerFn::Rust(listener_id, listener),
+                once: options.once,
+            }));
+        Ok(listener_id)
+    }
+
+    /// remove a rust listener which was added with add_static_event_listener
+    pub fn remove_static_event_listener(&self, event_name: &str, listener_id: i32) {
+        let obj_map = &mut *self.static_event_listeners.borrow_mut();
+        if let Some(listener_vec) = obj_map.get_mut(event_name) {
+            listener_vec.retain(|entry| !entry.is_rust_listener(listener_id));
+        }
+    }
+
+    /// get the constructor and prototype of the class this class extends, these stay null if the
+    /// class does not extend another proxy class
+    fn get_parent_class_objects(
+        &self,
+        cx: *mut JSContext,
+        scope: HandleObject,
+        mut parent_func: MutableHandleObject,
+        mut parent_proto: MutableHandleObject,
+    ) -> Result<(), String> {
+        if let Some(parent_name) = &self.extends {
+            let parent = get_proxy(parent_name.as_str()).ok_or_else(|| {
+                format!(
+                    "proxy {} extends {} which has not been built",
+                    self.class_name, parent_name
+                )
+            })?;
+
+            rooted!(in (cx) let mut parent_func_root = UndefinedValue());
+            parent
+                .get_constructor(cx, scope, parent_func_root.handle_mut())
+                .map_err(|err| {
+                    format!(
+                        "could not get constructor of parent class {}: {}",
+                        parent_name, err.message
+                    )
+                })?;
+            if !parent_func_root.is_object() {
+                return Err(format!(
+                    "could not get constructor of parent class {}",
+                    parent_name
+                ));
+            }
+            parent_func.set(parent_func_root.to_object());
+
+            rooted!(in (cx) let mut parent_proto_root = UndefinedValue());
+            crate::jsapi_utils::objects::get_es_obj_prop_val(
                 cx,
-                func,
-                method_name,
-                Some(proxy_static_method),
-            );
+                parent_func.handle(),
+                "prototype",
+                parent_proto_root.handle_mut(),
+            )
+            .map_err(|err| {
+                format!(
+                    "could not get prototype of parent class {}: {}",
+                    parent_name, err.message
+                )
+            })?;
+            if !parent_proto_root.is_object() {
+                return Err(format!(
+                    "could not get prototype of parent class {}",
+                    parent_name
+                ));
+            }
+            parent_proto.set(parent_proto_root.to_object());
         }
-        for native_method_name in self.static_native_methods.keys() {
-            trace!(
-                "init static method {} for {}",
-                native_method_name,
-                self.class_name
+        Ok(())
+    }
+
+    /// create the prototype object of the class, if the class extends another proxy class the
+    /// prototype and the constructor inherit from those of the parent class
+    fn init_prototype(
+        &self,
+        cx: *mut JSContext,
+        func: HandleObject,
+        parent_func: HandleObject,
+        parent_proto: HandleObject,
+        constants: &mut HashMap<&'static str, ConstantValue>,
+    ) {
+        rooted!(in (cx) let mut proto_root = ptr::null_mut::<JSObject>());
+
+        if !parent_proto.get().is_null() {
+            proto_root.set(
+                crate::jsapi_utils::objects::new_object_from_prototype(cx, parent_proto)
+                    .ok()
+                    .expect("could not create prototype"),
             );
-            let method: JSNative = self
-                .static_native_methods
-                .get(native_method_name)
-                .cloned()
+
+            // so static members of the parent class can be used on the subclass
+            crate::jsapi_utils::objects::set_prototype(cx, func, parent_func)
+                .ok()
+                .expect("could not set prototype of constructor");
+        } else {
+            proto_root.set(crate::jsapi_utils::objects::new_object(cx));
+        }
+
+        rooted!(in (cx) let proto_val_root = ObjectValue(proto_root.get()));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_permanent(
+            cx,
+            func,
+            "prototype",
+            proto_val_root.handle(),
+        );
+
+        rooted!(in (cx) let func_val_root = ObjectValue(func.get()));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_hidden(
+            cx,
+            proto_root.handle(),
+            "constructor",
+            func_val_root.handle(),
+        );
+
+        rooted!(in (cx) let cname_root = crate::jsapi_utils::new_es_value_from_str(cx, self.get_canonical_name().as_str()));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_hidden(
+            cx,
+            proto_root.handle(),
+            PROXY_PROP_PROTOTYPE_OF,
+            cname_root.handle(),
+        );
+
+        // constants live on the prototype so they are shared by all instances
+        define_constants(cx, proto_root.handle(), constants);
+
+        // so instances print as [object my.biz.MyClass] instead of [object Object]
+        crate::jsapi_utils::objects::set_es_obj_to_string_tag(
+            cx,
+            proto_root.handle(),
+            self.get_canonical_name().as_str(),
+        )
+        .ok()
+        .expect("could not set toStringTag");
+
+        if self.iterator.is_some() {
+            let iterator_func = crate::jsapi_utils::functions::new_native_function(
+                cx,
+                "[Symbol.iterator]",
+                Some(proxy_instance_iterator),
+            );
+            rooted!(in (cx) let iterator_func_root = ObjectValue(iterator_func as *mut JSObject));
+            crate::jsapi_utils::objects::set_es_obj_well_known_symbol_prop_val(
+                cx,
+                proto_root.handle(),
+                mozjs::jsapi::SymbolCode::iterator,
+                iterator_func_root.handle(),
+            )
+            .ok()
+            .expect("could not define Symbol.iterator");
+        }
+    }
+
+    fn init_static_properties(&self, cx: *mut JSContext, func: HandleObject) {
+        for (prop_name, prop) in &self.static_properties {
+            let getter: JSNative = if prop.getter.is_some() {
+                Some(proxy_static_getter)
+            } else {
+                None
+            };
+            let setter: JSNative = if prop.setter.is_some() {
+                Some(proxy_static_setter)
+            } else {
+                None
+            };
+            crate::jsapi_utils::objects::define_native_getter_setter(
+                cx,
+                func,
+                prop_name,
+                getter,
+                setter,
+                prop.attributes.to_flags(),
+            )
+            .ok()
+            .expect("could not define static property");
+        }
+    }
+
+    fn init_static_methods(&self, cx: *mut JSContext, func: HandleObject) {
+        trace!("init static methods for {}", self.class_name);
+        for method_name in self.static_methods.keys() {
+            trace!("init static method {} for {}", method_name, self.class_name);
+            crate::jsapi_utils::functions::define_native_function(
+                cx,
+                func,
+                method_name,
+                Some(proxy_static_method),
+            );
+        }
+        for native_method_name in self.static_native_methods.keys() {
+            trace!(
+                "init static method {} for {}",
+                native_method_name,
+                self.class_name
+            );
+            let method: JSNative = self
+                .static_native_methods
+                .get(native_method_name)
+                .cloned()
                 .unwrap();
             crate::jsapi_utils::functions::define_native_function(
                 cx,
@@ -421,7 +1421,7 @@ impl ProxyBuilder {
     /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
     ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
     ///         let _proxy = ProxyBuilder::new(vec!["com", "mybiz"], "MyClass")
-    ///         .build(cx, global);
+    ///         .build(cx, global).ok().expect("could not build proxy");
     ///     })
     /// });
     /// ```
@@ -429,19 +1429,43 @@ impl ProxyBuilder {
         ProxyBuilder {
             namespace,
             class_name,
+            extends: None,
             constructor: None,
             finalizer: None,
             properties: HashMap::new(),
             methods: HashMap::new(),
             native_methods: HashMap::new(),
             events: HashSet::new(),
+            constants: HashMap::new(),
+            iterator: None,
+            index_length: None,
+            index_getter: None,
+            index_setter: None,
+            dynamic_properties: None,
             static_properties: HashMap::new(),
             static_methods: HashMap::new(),
             static_native_methods: HashMap::new(),
             static_events: HashSet::new(),
+            static_constants: HashMap::new(),
+            doc: None,
+            member_docs: HashMap::new(),
         }
     }
 
+    /// set the documentation of the proxy class, this is included in Proxy::describe() and in
+    /// the TypeScript declarations
+    pub fn doc(&mut self, doc: &str) -> &mut Self {
+        self.doc = Some(doc.to_string());
+        self
+    }
+
+    /// set the documentation of a method, property, constant or event of the proxy class
+    /// static members and instance members with the same name share their documentation
+    pub fn member_doc(&mut self, name: &str, doc: &str) -> &mut Self {
+        self.member_docs.insert(name.to_string(), doc.to_string());
+        self
+    }
+
     /// function to call when the Proxy is constructed
     /// please not that if you do not add a constructor you can only use the static methods, getters, setters and events
     /// # Example
@@ -456,7 +1480,7 @@ impl ProxyBuilder {
     ///         .constructor(|cx, args| {
     ///             Ok(1)
     ///         })
-    ///         .build(cx, global);
+    ///         .build(cx, global).ok().expect("could not build proxy");
     ///     })
     /// });
     /// rt.eval_sync("let i = new com.mybiz.MyClass();",
@@ -487,8 +1511,79 @@ impl ProxyBuilder {
         G: Fn(*mut JSContext, i32, MutableHandleValue) -> Result<(), String> + 'static,
         S: Fn(*mut JSContext, i32, HandleValue) -> Result<(), String> + 'static,
     {
-        self.properties
-            .insert(name, (Box::new(getter), Box::new(setter)));
+        self.accessor(
+            name,
+            Some(Box::new(getter)),
+            Some(Box::new(setter)),
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a property which only has a getter, setting the property fails in strict mode
+    pub fn readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn(*mut JSContext, i32, MutableHandleValue) -> Result<(), String> + 'static,
+    {
+        self.accessor(
+            name,
+            Some(Box::new(getter)),
+            None,
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a property with an optional getter and setter and specific attributes
+    /// a property without a getter is write only, a property without a setter is read only
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::jsapi_utils::reflection::{ProxyBuilder, PropertyAttributes};
+    /// use mozjs::jsapi::JSContext;
+    /// use mozjs::jsval::Int32Value;
+    /// use mozjs::rust::MutableHandleValue;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+    ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
+    ///         let _proxy = ProxyBuilder::new(vec!["com", "mybiz"], "MyClass")
+    ///         .constructor(|_cx, _args| Ok(1))
+    ///         // a property which is not listed by Object.keys()
+    ///         .accessor("secret", Some(Box::new(|_cx: *mut JSContext, _obj_id: i32, mut rval: MutableHandleValue| {
+    ///             rval.set(Int32Value(42));
+    ///             Ok(())
+    ///         })), None, PropertyAttributes{enumerable: false, configurable: false})
+    ///         .build(cx, global).ok().expect("could not build proxy");
+    ///     })
+    /// });
+    /// rt.eval_sync("let i = new com.mybiz.MyClass(); i.secret;",
+    ///     "test_jsapi_proxy_accessor.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    pub fn accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<Getter>,
+        setter: Option<Setter>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
+        self.properties.insert(
+            name,
+            Accessor {
+                getter,
+                setter,
+                attributes,
+            },
+        );
+        self
+    }
+
+    /// add a constant to the prototype of the class, the constant is read only and not enumerable
+    /// value is called once to create the value when the class is built
+    pub fn constant<V>(&mut self, name: &'static str, value: V) -> &mut Self
+    where
+        V: FnOnce(*mut JSContext, MutableHandleValue) + 'static,
+    {
+        self.constants.insert(name, Box::new(value));
         self
     }
 
@@ -498,8 +1593,52 @@ impl ProxyBuilder {
         G: Fn(*mut JSContext, MutableHandleValue) -> Result<(), String> + 'static,
         S: Fn(*mut JSContext, HandleValue) -> Result<(), String> + 'static,
     {
-        self.static_properties
-            .insert(name, (Box::new(getter), Box::new(setter)));
+        self.static_accessor(
+            name,
+            Some(Box::new(getter)),
+            Some(Box::new(setter)),
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a static property which only has a getter
+    pub fn static_readonly_property<G>(&mut self, name: &'static str, getter: G) -> &mut Self
+    where
+        G: Fn(*mut JSContext, MutableHandleValue) -> Result<(), String> + 'static,
+    {
+        self.static_accessor(
+            name,
+            Some(Box::new(getter)),
+            None,
+            PropertyAttributes::default(),
+        )
+    }
+
+    /// add a static property with an optional getter and setter and specific attributes, see accessor
+    pub fn static_accessor(
+        &mut self,
+        name: &'static str,
+        getter: Option<StaticGetter>,
+        setter: Option<StaticSetter>,
+        attributes: PropertyAttributes,
+    ) -> &mut Self {
+        self.static_properties.insert(
+            name,
+            Accessor {
+                getter,
+                setter,
+                attributes,
+            },
+        );
+        self
+    }
+
+    /// add a constant to the class itself, e.g. MyClass.MAX_SIZE
+    pub fn static_constant<V>(&mut self, name: &'static str, value: V) -> &mut Self
+    where
+        V: FnOnce(*mut JSContext, MutableHandleValue) + 'static,
+    {
+        self.static_constants.insert(name, Box::new(value));
         self
     }
 
@@ -521,7 +1660,7 @@ impl ProxyBuilder {
     ///             println!("do something for obj: {}", obj_id);
     ///             Ok(())
     ///         })
-    ///         .build(cx, global);
+    ///         .build(cx, global).ok().expect("could not build proxy");
     ///     })
     /// });
     /// rt.eval_sync("let i = new com.mybiz.MyClass(); i.doSomething();",
@@ -558,11 +1697,218 @@ impl ProxyBuilder {
         self
     }
 
+    /// make this class extend another proxy class, the parent class needs to be built before this class
+    ///
+    /// instances of this class inherit the methods, properties and events of the parent class,
+    /// if this class has no constructor the constructor of the parent class is used
+    /// please note that the methods of the parent class are called with the obj_id of the instance of this class
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::jsapi_utils::reflection::ProxyBuilder;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+    ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
+    ///         let _animal = ProxyBuilder::new(vec!["com", "mybiz"], "Animal")
+    ///         .constructor(|_cx, _args| Ok(1))
+    ///         .method("eat", |_cx, _obj_id, _args, _rval| Ok(()))
+    ///         .build(cx, global).ok().expect("could not build proxy");
+    ///         let _dog = ProxyBuilder::new(vec!["com", "mybiz"], "Dog")
+    ///         .extends("com.mybiz.Animal")
+    ///         .method("bark", |_cx, _obj_id, _args, _rval| Ok(()))
+    ///         .build(cx, global).ok().expect("could not build proxy");
+    ///     })
+    /// });
+    /// rt.eval_sync("let dog = new com.mybiz.Dog(); dog.eat(); dog.bark(); dog instanceof com.mybiz.Animal;",
+    ///     "test_jsapi_proxy_extends.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    pub fn extends(&mut self, canonical_name: &str) -> &mut Self {
+        self.extends = Some(canonical_name.to_string());
+        self
+    }
+
+    /// make instances of the proxy class iterable, e.g. with for..of or [...obj]
+    ///
+    /// the closure is called with the obj_id of the instance for every iteration and returns
+    /// a next function, the next function sets rval to the next value and returns Ok(false) when done
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::jsapi_utils::reflection::ProxyBuilder;
+    /// use mozjs::jsapi::JSContext;
+    /// use mozjs::jsval::Int32Value;
+    /// use mozjs::rust::MutableHandleValue;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+    ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
+    ///         let _proxy = ProxyBuilder::new(vec!["com", "mybiz"], "Range")
+    ///         .constructor(|_cx, _args| Ok(1))
+    ///         .iterator(|_cx, _obj_id| {
+    ///             let mut current = 0;
+    ///             Ok(Box::new(move |_cx: *mut JSContext, mut rval: MutableHandleValue| {
+    ///                 current += 1;
+    ///                 rval.set(Int32Value(current));
+    ///                 Ok(current <= 3)
+    ///             }))
+    ///         })
+    ///         .build(cx, global).ok().expect("could not build proxy");
+    ///     })
+    /// });
+    /// rt.eval_sync("for (let i of new com.mybiz.Range()) {console.log(i);}",
+    ///     "test_jsapi_proxy_iterator.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    pub fn iterator<I>(&mut self, iterator: I) -> &mut Self
+    where
+        I: Fn(*mut JSContext, i32) -> Result<IteratorNext, String> + 'static,
+    {
+        self.iterator = Some(Box::new(iterator));
+        self
+    }
+
+    /// make instances of the proxy class indexable like an array, e.g. obj[0]
+    ///
+    /// this also adds a read only length property, only indices lower than the length are
+    /// resolved on an instance, please note that an index is not removed from an instance when
+    /// the length decreases so the getter should handle indices which are out of bounds
+    pub fn indexed<L, G>(&mut self, length: L, getter: G) -> &mut Self
+    where
+        L: Fn(*mut JSContext, i32) -> Result<u32, String> + 'static,
+        G: Fn(*mut JSContext, i32, u32, MutableHandleValue) -> Result<(), String> + 'static,
+    {
+        let length_rc: IndexLength = Rc::new(length);
+        let length_getter = length_rc.clone();
+        self.accessor(
+            "length",
+            Some(Box::new(
+                move |cx: *mut JSContext, obj_id: i32, mut rval: MutableHandleValue| {
+                    let len = length_getter(cx, obj_id)?;
+                    rval.set(mozjs::jsval::UInt32Value(len));
+                    Ok(())
+                },
+            )),
+            None,
+            PropertyAttributes {
+                enumerable: false,
+                configurable: false,
+            },
+        );
+        self.index_length = Some(length_rc);
+        self.index_getter = Some(Box::new(getter));
+        self
+    }
+
+    /// make the indices of an indexed proxy class writable, e.g. obj[0] = 1;
+    pub fn indexed_setter<S>(&mut self, setter: S) -> &mut Self
+    where
+        S: Fn(*mut JSContext, i32, u32, HandleValue) -> Result<(), String> + 'static,
+    {
+        self.index_setter = Some(Box::new(setter));
+        self
+    }
+
+    /// give the proxy class an open ended set of properties, e.g. for a config tree or a row object
+    ///
+    /// properties which are not declared as a property or method of the class are passed to these
+    /// hooks with their name, has is called when script reads a property or uses the in operator for
+    /// the first time, after which the property is defined as an accessor on the instance (so has is
+    /// not called again for that name), new properties which are assigned from script are passed to
+    /// the setter, delete is called for the delete operator and keys is used by Object.keys() and
+    /// for..in, accessors of names which keys no longer returns are removed from the instance then
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::jsapi_utils::reflection::ProxyBuilder;
+    /// use mozjs::jsval::Int32Value;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+    ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
+    ///         let _proxy = ProxyBuilder::new(vec!["com", "mybiz"], "Row")
+    ///         .constructor(|_cx, _args| Ok(1))
+    ///         .dynamic_properties(
+    ///             |_cx, _obj_id, name, mut rval| {
+    ///                 rval.set(Int32Value(name.len() as i32));
+    ///                 Ok(())
+    ///             },
+    ///             |_cx, _obj_id, _name, _val| Err("row is read only".to_string()),
+    ///             |_cx, _obj_id, name| Ok(name.starts_with("col")),
+    ///             |_cx, _obj_id, _name| Ok(false),
+    ///             |_cx, _obj_id| Ok(vec!["col1".to_string(), "col2".to_string()]),
+    ///         )
+    ///         .build(cx, global).ok().expect("could not build proxy");
+    ///     })
+    /// });
+    /// rt.eval_sync("let row = new com.mybiz.Row(); console.log(row.col1 + ' ' + Object.keys(row));",
+    ///     "test_jsapi_proxy_dynamic_properties.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    pub fn dynamic_properties<G, S, H, D, K>(
+        &mut self,
+        getter: G,
+        setter: S,
+        has: H,
+        delete: D,
+        keys: K,
+    ) -> &mut Self
+    where
+        G: Fn(*mut JSContext, i32, &str, MutableHandleValue) -> Result<(), String> + 'static,
+        S: Fn(*mut JSContext, i32, &str, HandleValue) -> Result<(), String> + 'static,
+        H: Fn(*mut JSContext, i32, &str) -> Result<bool, String> + 'static,
+        D: Fn(*mut JSContext, i32, &str) -> Result<bool, String> + 'static,
+        K: Fn(*mut JSContext, i32) -> Result<Vec<String>, String> + 'static,
+    {
+        self.dynamic_properties = Some(DynamicProperties {
+            getter: Box::new(getter),
+            setter: Box::new(setter),
+            has: Box::new(has),
+            delete: Box::new(delete),
+            keys: Box::new(keys),
+        });
+        self
+    }
+
     /// create the proxy class, please not that this can only be used once on a builder
-    pub fn build(&mut self, cx: *mut JSContext, scope: HandleObject) -> Arc<Proxy> {
+    /// this fails if the class extends a class which has not been built
+    pub fn build(&mut self, cx: *mut JSContext, scope: HandleObject) -> Result<Arc<Proxy>, String> {
         Proxy::new(cx, scope, self)
     }
 
+    /// create a singleton object instead of a class, this is a plain object in the namespace which
+    /// can not be constructed, use the static_* methods of the builder to add its members
+    /// # Example
+    /// ```no_run
+    /// use es_runtime::esruntimebuilder::EsRuntimeBuilder;
+    /// use es_runtime::jsapi_utils::reflection::ProxyBuilder;
+    /// use mozjs::jsval::Int32Value;
+    ///
+    /// let rt = EsRuntimeBuilder::new().build();
+    /// rt.do_in_es_runtime_thread_sync(|sm_rt| {
+    ///     sm_rt.do_with_jsapi(|_rt, cx, global|{
+    ///         let _proxy = ProxyBuilder::new(vec!["com", "mybiz"], "MyApp")
+    ///         .static_method("getVersion", |_cx, _args, mut rval| {
+    ///             rval.set(Int32Value(2));
+    ///             Ok(())
+    ///         })
+    ///         .static_event("started")
+    ///         .build_singleton(cx, global).ok().expect("could not build proxy");
+    ///     })
+    /// });
+    /// rt.eval_sync("console.log('%s version %s', com.mybiz.MyApp, com.mybiz.MyApp.getVersion());",
+    ///     "test_jsapi_proxy_singleton.es")
+    ///     .ok().expect("script failed");
+    /// ```
+    pub fn build_singleton(
+        &mut self,
+        cx: *mut JSContext,
+        scope: HandleObject,
+    ) -> Result<Arc<Proxy>, String> {
+        Proxy::new_singleton(cx, scope, self)
+    }
+
     /// define an event_type for this proxy class
     pub fn event(&mut self, evt_type: &'static str) -> &mut Self {
         self.events.insert(evt_type);
@@ -632,7 +1978,7 @@ mod tests {
                             Ok(())
                         })
                         .event("saved")
-                        .build(cx, global);
+                        .build(cx, global).ok().expect("could not build proxy");
                     let esvf = sm_rt
                         .eval(
                             "// create a new instance of your Proxy\n\
@@ -695,7 +2041,7 @@ mod tests {
                             Ok(())
                         })
                         .static_event("saved")
-                        .build(cx, global);
+                        .build(cx, global).ok().expect("could not build proxy");
                     let esvf = sm_rt
                         .eval(
                             "// you can set props that are not proxied \n\
@@ -724,168 +2070,920 @@ mod tests {
 }
 
 static ES_PROXY_CLASS_CLASS_OPS: JSClassOps = JSClassOps {
+    addProperty: Some(proxy_instance_add_property),
+    delProperty: Some(proxy_instance_del_property),
+    enumerate: Some(proxy_instance_enumerate),
+    newEnumerate: None,
+    resolve: Some(proxy_instance_resolve),
+    mayResolve: None,
+    finalize: Some(proxy_instance_finalize),
+    call: None,
+    hasInstance: None,
+    construct: None,
+    trace: None,
+};
+
+static ES_PROXY_ITERATOR_CLASS_OPS: JSClassOps = JSClassOps {
     addProperty: None,
     delProperty: None,
     enumerate: None,
     newEnumerate: None,
-    resolve: Some(proxy_instance_resolve),
+    resolve: None,
     mayResolve: None,
-    finalize: Some(proxy_instance_finalize),
+    finalize: Some(proxy_iterator_finalize),
     call: None,
     hasInstance: None,
     construct: None,
     trace: None,
 };
 
-static ES_PROXY_CLASS: JSClass = JSClass {
-    name: b"EsProxy\0" as *const u8 as *const libc::c_char,
-    flags: JSCLASS_FOREGROUND_FINALIZE,
-    cOps: &ES_PROXY_CLASS_CLASS_OPS as *const JSClassOps,
-    spec: ptr::null(),
-    ext: ptr::null(),
-    oOps: ptr::null(),
-};
+static ES_PROXY_ITERATOR_CLASS: JSClass = JSClass {
+    name: b"EsProxyIterator\0" as *const u8 as *const libc::c_char,
+    flags: JSCLASS_FOREGROUND_FINALIZE,
+    cOps: &ES_PROXY_ITERATOR_CLASS_OPS as *const JSClassOps,
+    spec: ptr::null(),
+    ext: ptr::null(),
+    oOps: ptr::null(),
+};
+
+static ES_PROXY_CLASS: JSClass = JSClass {
+    name: b"EsProxy\0" as *const u8 as *const libc::c_char,
+    flags: JSCLASS_FOREGROUND_FINALIZE,
+    cOps: &ES_PROXY_CLASS_CLASS_OPS as *const JSClassOps,
+    spec: ptr::null(),
+    ext: ptr::null(),
+    oOps: ptr::null(),
+};
+
+/// resolvea property, this means if we know how to handle a prop we define that prop ob the instance obj
+unsafe extern "C" fn proxy_instance_resolve(
+    cx: *mut JSContext,
+    obj: mozjs::jsapi::HandleObject,
+    key: mozjs::jsapi::HandleId,
+    resolved: *mut bool,
+) -> bool {
+    trace!("reflection::resolve");
+
+    if mozjs::glue::RUST_JSID_IS_INT(key) {
+        return proxy_instance_resolve_index(cx, obj, mozjs::glue::RUST_JSID_TO_INT(key), resolved);
+    }
+    if !mozjs::glue::RUST_JSID_IS_STRING(key) {
+        // symbols are not resolved, Symbol.iterator is defined on the prototype
+        return true;
+    }
+
+    let prop_name = es_jsid_to_string(cx, key);
+
+    trace!("reflection::resolve {}", prop_name);
+
+    let rhandle = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+    if let Some(proxy) = get_proxy_for(cx, obj.get()) {
+        let class_name = proxy.get_canonical_name();
+        {
+            trace!("check proxy {} for {}", class_name, prop_name);
+
+            if is_overridden_in_script(cx, rhandle, prop_name.as_str()) {
+                trace!("{} is overridden by a script subclass", prop_name);
+            } else if prop_name.as_str().eq("addEventListener") {
+                trace!("define addEventListener");
+
+                let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+                crate::jsapi_utils::functions::define_native_function(
+                    cx,
+                    robj,
+                    "addEventListener",
+                    Some(proxy_instance_add_event_listener),
+                );
+
+                *resolved = true;
+                trace!("resolved addEventListener {}", prop_name);
+            } else if prop_name.as_str().eq("removeEventListener") {
+                trace!("define removeEventListener");
+
+                let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+                crate::jsapi_utils::functions::define_native_function(
+                    cx,
+                    robj,
+                    "removeEventListener",
+                    Some(proxy_instance_remove_event_listener),
+                );
+
+                *resolved = true;
+                trace!("resolved removeEventListener {}", prop_name);
+            } else if prop_name.as_str().eq("dispatchEvent") {
+                trace!("define dispatchEvent");
+
+                let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+                crate::jsapi_utils::functions::define_native_function(
+                    cx,
+                    robj,
+                    "dispatchEvent",
+                    Some(proxy_instance_dispatch_event),
+                );
+
+                *resolved = true;
+                trace!("resolved dispatchEvent {}", prop_name);
+            } else if let Some(prop_proxy) = find_proxy_in_chain(proxy.clone(), |p| {
+                p.properties.contains_key(prop_name.as_str())
+            }) {
+                trace!(
+                    "define prop for proxy {} for name {}",
+                    class_name,
+                    prop_name
+                );
+
+                let prop = &prop_proxy.properties[prop_name.as_str()];
+                let getter: JSNative = if prop.getter.is_some() {
+                    Some(proxy_instance_getter)
+                } else {
+                    None
+                };
+                let setter: JSNative = if prop.setter.is_some() {
+                    Some(proxy_instance_setter)
+                } else {
+                    None
+                };
+
+                if crate::jsapi_utils::objects::define_native_getter_setter(
+                    cx,
+                    rhandle,
+                    prop_name.as_str(),
+                    getter,
+                    setter,
+                    prop.attributes.to_flags(),
+                )
+                .is_err()
+                {
+                    panic!("could not define prop");
+                }
+
+                *resolved = true;
+
+                trace!("resolved prop {}", prop_name);
+            } else if find_proxy_in_chain(proxy.clone(), |p| {
+                p.methods.contains_key(prop_name.as_str())
+            })
+            .is_some()
+            {
+                trace!(
+                    "define method for proxy {} for name {}",
+                    class_name,
+                    prop_name
+                );
+
+                let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+                crate::jsapi_utils::functions::define_native_function(
+                    cx,
+                    robj,
+                    prop_name.as_str(),
+                    Some(proxy_instance_method),
+                );
+
+                *resolved = true;
+                trace!("resolved method {}", prop_name);
+            } else if let Some(method_proxy) = find_proxy_in_chain(proxy.clone(), |p| {
+                p.native_methods.contains_key(prop_name.as_str())
+            }) {
+                trace!(
+                    "define native method for proxy {} for name {}",
+                    class_name,
+                    prop_name
+                );
+
+                let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+
+                let method: JSNative = method_proxy
+                    .native_methods
+                    .get(prop_name.as_str())
+                    .cloned()
+                    .unwrap();
+
+                crate::jsapi_utils::functions::define_native_function(
+                    cx,
+                    robj,
+                    prop_name.as_str(),
+                    method,
+                );
+
+                *resolved = true;
+                trace!("resolved native method {}", prop_name);
+            } else if !prop_name.starts_with(PROXY_PROP_PREFIX) {
+                if let Some(dyn_proxy) =
+                    find_proxy_in_chain(proxy, |p| p.dynamic_properties.is_some())
+                {
+                    let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+                    let obj_id = get_obj_id_for(cx, obj.get());
+                    match (dynamic_properties.has)(cx, obj_id, prop_name.as_str()) {
+                        Ok(true) => {
+                            trace!("define dynamic prop {}", prop_name);
+                            if let Err(err) = define_dynamic_prop(cx, rhandle, prop_name.as_str()) {
+                                let s = format!(
+                                    "could not define dynamic prop {}\ncaused by: {}\0",
+                                    prop_name,
+                                    err.err_msg()
+                                );
+                                JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                                return false;
+                            }
+                            *resolved = true;
+                        }
+                        Ok(false) => {}
+                        Err(js_err) => {
+                            let s = format!("has {} failed\ncaused by: {}\0", prop_name, js_err);
+                            JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// define an accessor for a dynamic property on an instance, it can be deleted from script
+fn define_dynamic_prop(
+    cx: *mut JSContext,
+    obj: HandleObject,
+    name: &str,
+) -> Result<(), EsErrorInfo> {
+    DEFINING_DYNAMIC_PROP.with(|defining_rc| *defining_rc.borrow_mut() = true);
+    let res = crate::jsapi_utils::objects::define_native_getter_setter(
+        cx,
+        obj,
+        name,
+        Some(proxy_instance_dynamic_getter),
+        Some(proxy_instance_dynamic_setter),
+        PropertyAttributes {
+            enumerable: true,
+            configurable: true,
+        }
+        .to_flags(),
+    );
+    DEFINING_DYNAMIC_PROP.with(|defining_rc| *defining_rc.borrow_mut() = false);
+    res
+}
+
+/// get the dynamic properties hooks for a property of an instance, this returns None if the
+/// property is declared by the proxy class or if the proxy class has no dynamic properties
+fn get_dynamic_proxy_for(
+    cx: *mut JSContext,
+    obj: *mut JSObject,
+    prop_name: &str,
+) -> Option<Arc<Proxy>> {
+    if prop_name.starts_with(PROXY_PROP_PREFIX) {
+        return None;
+    }
+    let proxy = get_proxy_for(cx, obj)?;
+    let is_declared = prop_name.eq("addEventListener")
+        || prop_name.eq("removeEventListener")
+        || prop_name.eq("dispatchEvent")
+        || find_proxy_in_chain(proxy.clone(), |p| {
+            p.properties.contains_key(prop_name)
+                || p.methods.contains_key(prop_name)
+                || p.native_methods.contains_key(prop_name)
+        })
+        .is_some();
+    if is_declared {
+        None
+    } else {
+        find_proxy_in_chain(proxy, |p| p.dynamic_properties.is_some())
+    }
+}
+
+/// called when script assigns a new property to an instance, for a proxy class with dynamic properties
+/// the value is passed to the setter and the property is redefined as a dynamic property
+unsafe extern "C" fn proxy_instance_add_property(
+    cx: *mut JSContext,
+    obj: mozjs::jsapi::HandleObject,
+    id: mozjs::jsapi::HandleId,
+    v: mozjs::jsapi::HandleValue,
+) -> bool {
+    if !mozjs::glue::RUST_JSID_IS_STRING(id)
+        || DEFINING_DYNAMIC_PROP.with(|defining_rc| *defining_rc.borrow())
+    {
+        return true;
+    }
+
+    let prop_name = es_jsid_to_string(cx, id);
+    if let Some(dyn_proxy) = get_dynamic_proxy_for(cx, obj.get(), prop_name.as_str()) {
+        trace!("reflection::add_property {}", prop_name);
+
+        let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+        let obj_id = get_obj_id_for(cx, obj.get());
+        let val = HandleValue::from_marked_location(&v.get());
+        if let Err(js_err) = (dynamic_properties.setter)(cx, obj_id, prop_name.as_str(), val) {
+            // returning false removes the property again
+            let s = format!("setter {} failed\ncaused by: {}\0", prop_name, js_err);
+            JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+            return false;
+        }
+
+        let rhandle = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+        if define_dynamic_prop(cx, rhandle, prop_name.as_str()).is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+unsafe extern "C" fn proxy_instance_del_property(
+    cx: *mut JSContext,
+    obj: mozjs::jsapi::HandleObject,
+    id: mozjs::jsapi::HandleId,
+    result: *mut mozjs::jsapi::ObjectOpResult,
+) -> bool {
+    if mozjs::glue::RUST_JSID_IS_STRING(id)
+        && !REMOVING_DYNAMIC_PROPS.with(|removing_rc| *removing_rc.borrow())
+    {
+        let prop_name = es_jsid_to_string(cx, id);
+        if let Some(dyn_proxy) = get_dynamic_proxy_for(cx, obj.get(), prop_name.as_str()) {
+            trace!("reflection::del_property {}", prop_name);
+
+            let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+            let obj_id = get_obj_id_for(cx, obj.get());
+            match (dynamic_properties.delete)(cx, obj_id, prop_name.as_str()) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return (*result).failCantDelete();
+                }
+                Err(js_err) => {
+                    let s = format!("delete {} failed\ncaused by: {}\0", prop_name, js_err);
+                    JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                    return false;
+                }
+            }
+        }
+    }
+
+    (*result).succeed()
+}
+
+/// get the name of a dynamic property from the name of its getter or setter
+fn get_accessor_name(cx: *mut JSContext, args: &CallArgs) -> Option<String> {
+    let callee: *mut JSObject = args.callee();
+    let name_res = crate::jsapi_utils::objects::get_es_obj_prop_val_as_string(
+        cx,
+        unsafe { HandleObject::from_marked_location(&callee) },
+        "name",
+    );
+    name_res.ok().map(|name| name[4..].to_string())
+}
+
+unsafe extern "C" fn proxy_instance_dynamic_getter(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    trace!("reflection::dynamic_getter");
+
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+
+    if thisv.is_object() {
+        if let Some(prop_name) = get_accessor_name(cx, &args) {
+            if let Some(dyn_proxy) =
+                get_dynamic_proxy_for(cx, thisv.to_object(), prop_name.as_str())
+            {
+                let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+                let obj_id = get_obj_id_for(cx, thisv.to_object());
+                rooted!(in (cx) let mut rval = UndefinedValue());
+                match (dynamic_properties.getter)(cx, obj_id, prop_name.as_str(), rval.handle_mut())
+                {
+                    Ok(()) => {
+                        args.rval().set(rval.get());
+                    }
+                    Err(js_err) => {
+                        let s = format!("getter {} failed\ncaused by: {}\0", prop_name, js_err);
+                        JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+unsafe extern "C" fn proxy_instance_dynamic_setter(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    trace!("reflection::dynamic_setter");
+
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+
+    if thisv.is_object() {
+        if let Some(prop_name) = get_accessor_name(cx, &args) {
+            if let Some(dyn_proxy) =
+                get_dynamic_proxy_for(cx, thisv.to_object(), prop_name.as_str())
+            {
+                let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+                let obj_id = get_obj_id_for(cx, thisv.to_object());
+                let val = HandleValue::from_marked_location(&args.index(0).get());
+                if let Err(js_err) =
+                    (dynamic_properties.setter)(cx, obj_id, prop_name.as_str(), val)
+                {
+                    let s = format!("setter {} failed\ncaused by: {}\0", prop_name, js_err);
+                    JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// resolve an index of an indexed proxy class, only indices lower than the length are resolved
+unsafe fn proxy_instance_resolve_index(
+    cx: *mut JSContext,
+    obj: mozjs::jsapi::HandleObject,
+    index: i32,
+    resolved: *mut bool,
+) -> bool {
+    trace!("reflection::resolve_index {}", index);
+
+    if let Some(proxy) = get_proxy_for(cx, obj.get()) {
+        if let Some(index_proxy) = find_proxy_in_chain(proxy, |p| p.index_getter.is_some()) {
+            let obj_id = get_obj_id_for(cx, obj.get());
+            let length = index_proxy.index_length.as_ref().unwrap();
+            match length(cx, obj_id) {
+                Ok(len) => {
+                    if index >= 0 && (index as u32) < len {
+                        let setter: JSNative = if index_proxy.index_setter.is_some() {
+                            Some(proxy_instance_index_setter)
+                        } else {
+                            None
+                        };
+                        let rhandle = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+                        if let Err(err) = crate::jsapi_utils::objects::define_native_getter_setter(
+                            cx,
+                            rhandle,
+                            format!("{}", index).as_str(),
+                            Some(proxy_instance_index_getter),
+                            setter,
+                            PropertyAttributes {
+                                enumerable: true,
+                                configurable: true,
+                            }
+                            .to_flags(),
+                        ) {
+                            let s = format!(
+                                "could not define index {}\ncaused by: {}\0",
+                                index,
+                                err.err_msg()
+                            );
+                            JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                            return false;
+                        }
+                        *resolved = true;
+                    }
+                }
+                Err(js_err) => {
+                    let s = format!("length failed\ncaused by: {}\0", js_err);
+                    JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                    return false;
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// get the index from the name of an index getter or setter, the name is "get [index]" or "set [index]"
+fn get_accessor_index(cx: *mut JSContext, args: &CallArgs) -> Option<u32> {
+    get_accessor_name(cx, args).and_then(|name| name.parse::<u32>().ok())
+}
+
+unsafe extern "C" fn proxy_instance_index_getter(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    trace!("reflection::index_getter");
+
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+
+    if thisv.is_object() {
+        if let Some(proxy) = get_proxy_for(cx, thisv.to_object()) {
+            if let Some(index_proxy) = find_proxy_in_chain(proxy, |p| p.index_getter.is_some()) {
+                if let Some(index) = get_accessor_index(cx, &args) {
+                    let obj_id = get_obj_id_for(cx, thisv.to_object());
+                    let getter = index_proxy.index_getter.as_ref().unwrap();
+                    rooted!(in (cx) let mut rval = UndefinedValue());
+                    match getter(cx, obj_id, index, rval.handle_mut()) {
+                        Ok(()) => {
+                            args.rval().set(rval.get());
+                        }
+                        Err(js_err) => {
+                            let s = format!("getter {} failed\ncaused by: {}\0", index, js_err);
+                            JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+unsafe extern "C" fn proxy_instance_index_setter(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    trace!("reflection::index_setter");
+
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+
+    if thisv.is_object() {
+        if let Some(proxy) = get_proxy_for(cx, thisv.to_object()) {
+            if let Some(index_proxy) = find_proxy_in_chain(proxy, |p| p.index_setter.is_some()) {
+                if let Some(index) = get_accessor_index(cx, &args) {
+                    let obj_id = get_obj_id_for(cx, thisv.to_object());
+                    let setter = index_proxy.index_setter.as_ref().unwrap();
+                    let val = HandleValue::from_marked_location(&args.index(0).get());
+                    if let Err(js_err) = setter(cx, obj_id, index, val) {
+                        let s = format!("setter {} failed\ncaused by: {}\0", index, js_err);
+                        JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Symbol.iterator of an iterable proxy class, this creates an iterator object with a next method
+unsafe extern "C" fn proxy_instance_iterator(
+    cx: *mut JSContext,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    trace!("reflection::iterator");
+
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+
+    if thisv.is_object() {
+        if let Some(proxy) = get_proxy_for(cx, thisv.to_object()) {
+            if let Some(iterator_proxy) = find_proxy_in_chain(proxy, |p| p.iterator.is_some()) {
+                let obj_id = get_obj_id_for(cx, thisv.to_object());
+                let iterator = iterator_proxy.iterator.as_ref().unwrap();
+                match iterator(cx, obj_id) {
+                    Ok(next) => {
+                        let iterator_id = PROXY_ITERATORS.with(|iterators_rc| {
+                            let iterators = &mut *iterators_rc.borrow_mut();
+                            iterators.insert(Rc::new(RefCell::new(next)))
+                        });
+
+                        let ret: *mut JSObject =
+                            mozjs::jsapi::JS_NewObject(cx, &ES_PROXY_ITERATOR_CLASS);
+                        rooted!(in (cx) let ret_root = ret);
+                        rooted!(in (cx) let iterator_id_root = mozjs::jsval::Int32Value(iterator_id as i32));
+                        crate::jsapi_utils::objects::set_es_obj_prop_val_permanent(
+                            cx,
+                            ret_root.handle(),
+                            PROXY_PROP_ITERATOR_ID,
+                            iterator_id_root.handle(),
+                        );
+                        crate::jsapi_utils::functions::define_native_function(
+                            cx,
+                            ret_root.handle(),
+                            "next",
+                            Some(proxy_iterator_next),
+                        );
+
+                        PROXY_ITERATOR_IDS.with(|piid_rc| {
+                            let piid = &mut *piid_rc.borrow_mut();
+                            piid.insert(ret as usize, iterator_id);
+                        });
+
+                        args.rval().set(ObjectValue(ret));
+                    }
+                    Err(js_err) => {
+                        let s = format!("iterator failed\ncaused by: {}\0", js_err);
+                        JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
 
-/// resolvea property, this means if we know how to handle a prop we define that prop ob the instance obj
-unsafe extern "C" fn proxy_instance_resolve(
+unsafe extern "C" fn proxy_iterator_next(
     cx: *mut JSContext,
-    obj: mozjs::jsapi::HandleObject,
-    key: mozjs::jsapi::HandleId,
-    resolved: *mut bool,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
 ) -> bool {
-    trace!("reflection::resolve");
+    trace!("reflection::iterator_next");
 
-    let prop_name = es_jsid_to_string(cx, key);
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
 
-    trace!("reflection::resolve {}", prop_name);
+    if !thisv.is_object() {
+        JS_ReportErrorASCII(
+            cx,
+            b"next called on an invalid iterator\0".as_ptr() as *const libc::c_char,
+        );
+        return false;
+    }
 
-    let rhandle = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-    let class_name_res = crate::jsapi_utils::objects::get_es_obj_prop_val_as_string(
+    rooted!(in (cx) let this_root = thisv.to_object());
+    let iterator_id = crate::jsapi_utils::objects::get_es_obj_prop_val_as_i32(
         cx,
-        rhandle,
-        PROXY_PROP_CLASS_NAME,
+        this_root.handle(),
+        PROXY_PROP_ITERATOR_ID,
+    ) as usize;
+
+    // clone the Rc so the map is not borrowed while the iterator runs
+    let next_opt = PROXY_ITERATORS.with(|iterators_rc| {
+        let iterators = &*iterators_rc.borrow();
+        iterators.get(&iterator_id).cloned()
+    });
+
+    rooted!(in (cx) let mut value_root = UndefinedValue());
+    let done = match next_opt {
+        Some(next_rc) => {
+            let next_res = match next_rc.try_borrow_mut() {
+                Ok(mut next) => next(cx, value_root.handle_mut()),
+                Err(_e) => Err("iterator is already running".to_string()),
+            };
+            match next_res {
+                Ok(has_value) => !has_value,
+                Err(js_err) => {
+                    let s = format!("next failed\ncaused by: {}\0", js_err);
+                    JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                    return false;
+                }
+            }
+        }
+        // the iterator was already done
+        None => true,
+    };
+
+    if done {
+        value_root.set(UndefinedValue());
+        remove_iterator(iterator_id);
+    }
+
+    rooted!(in (cx) let res_root = crate::jsapi_utils::objects::new_object(cx));
+    rooted!(in (cx) let done_root = BooleanValue(done));
+    crate::jsapi_utils::objects::set_es_obj_prop_val(
+        cx,
+        res_root.handle(),
+        "value",
+        value_root.handle(),
     );
-    if let Ok(class_name) = class_name_res {
-        PROXIES.with(|proxies_rc| {
-            let proxies = &*proxies_rc.borrow();
-            if let Some(proxy) = proxies.get(class_name.as_str()) {
-                trace!("check proxy {} for {}", class_name, prop_name);
+    crate::jsapi_utils::objects::set_es_obj_prop_val(
+        cx,
+        res_root.handle(),
+        "done",
+        done_root.handle(),
+    );
+    args.rval().set(ObjectValue(res_root.get()));
 
-                if prop_name.as_str().eq("addEventListener") {
-                    trace!("define addEventListener");
+    true
+}
 
-                    let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-                    crate::jsapi_utils::functions::define_native_function(
-                        cx,
-                        robj,
-                        "addEventListener",
-                        Some(proxy_instance_add_event_listener),
-                    );
-
-                    *resolved = true;
-                    trace!("resolved addEventListener {}", prop_name);
-                } else if prop_name.as_str().eq("removeEventListener") {
-                    trace!("define removeEventListener");
-
-                    let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-                    crate::jsapi_utils::functions::define_native_function(
-                        cx,
-                        robj,
-                        "removeEventListener",
-                        Some(proxy_instance_remove_event_listener),
-                    );
-
-                    *resolved = true;
-                    trace!("resolved removeEventListener {}", prop_name);
-                } else if prop_name.as_str().eq("dispatchEvent") {
-                    trace!("define dispatchEvent");
-
-                    let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-                    crate::jsapi_utils::functions::define_native_function(
-                        cx,
-                        robj,
-                        "dispatchEvent",
-                        Some(proxy_instance_dispatch_event),
-                    );
-
-                    *resolved = true;
-                    trace!("resolved dispatchEvent {}", prop_name);
-                } else if proxy.properties.contains_key(prop_name.as_str()) {
-                    trace!(
-                        "define prop for proxy {} for name {}",
-                        class_name,
-                        prop_name
-                    );
-
-                    let n = format!("{}\0", prop_name);
-
-                    // todo move this to jsapi_utils (objects::define_native_getter_setter)
-                    let ok = mozjs::jsapi::JS_DefineProperty1(
-                        cx,
-                        obj,
-                        n.as_ptr() as *const libc::c_char,
-                        Some(proxy_instance_getter),
-                        Some(proxy_instance_setter),
-                        (mozjs::jsapi::JSPROP_PERMANENT
-                            & mozjs::jsapi::JSPROP_GETTER
-                            & mozjs::jsapi::JSPROP_SETTER) as u32,
-                    );
-                    if !ok {
-                        panic!("could not define prop");
-                    }
+fn remove_iterator(iterator_id: usize) {
+    PROXY_ITERATORS.with(|iterators_rc| {
+        let iterators = &mut *iterators_rc.borrow_mut();
+        if iterators.contains_key(&iterator_id) {
+            iterators.remove(&iterator_id);
+        }
+    });
+}
 
-                    *resolved = true;
+/// drops the rust iterator of an iterator object which was not iterated until done
+unsafe extern "C" fn proxy_iterator_finalize(_fop: *mut JSFreeOp, object: *mut JSObject) {
+    trace!("reflection::iterator_finalize");
 
-                    trace!("resolved prop {}", prop_name);
-                } else if proxy.methods.contains_key(prop_name.as_str()) {
-                    trace!(
-                        "define method for proxy {} for name {}",
-                        class_name,
-                        prop_name
-                    );
+    let id_opt = PROXY_ITERATOR_IDS.with(|piid_rc| {
+        let piid = &mut *piid_rc.borrow_mut();
+        piid.remove(&(object as usize))
+    });
+    if let Some(iterator_id) = id_opt {
+        remove_iterator(iterator_id);
+    }
+}
 
-                    let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-                    crate::jsapi_utils::functions::define_native_function(
-                        cx,
-                        robj,
-                        prop_name.as_str(),
-                        Some(proxy_instance_method),
-                    );
-
-                    *resolved = true;
-                    trace!("resolved method {}", prop_name);
-                } else if proxy.native_methods.contains_key(prop_name.as_str()) {
-                    trace!(
-                        "define native method for proxy {} for name {}",
-                        class_name,
-                        prop_name
-                    );
-
-                    let robj = mozjs::rust::HandleObject::from_marked_location(&obj.get());
-
-                    let method: JSNative = proxy
-                        .native_methods
-                        .get(prop_name.as_str())
-                        .cloned()
-                        .unwrap();
+/// resolve the properties of the proxy class on an instance before its own properties are enumerated
+/// so Object.keys() and JSON.stringify() see the enumerable properties, methods are never enumerable
+/// for a proxy class with dynamic properties the keys hook determines which dynamic properties are resolved
+unsafe extern "C" fn proxy_instance_enumerate(
+    cx: *mut JSContext,
+    obj: mozjs::jsapi::HandleObject,
+) -> bool {
+    trace!("reflection::enumerate");
 
-                    crate::jsapi_utils::functions::define_native_function(
-                        cx,
-                        robj,
-                        prop_name.as_str(),
-                        method,
-                    );
+    if REMOVING_DYNAMIC_PROPS.with(|removing_rc| *removing_rc.borrow()) {
+        return true;
+    }
+
+    let rhandle = mozjs::rust::HandleObject::from_marked_location(&obj.get());
+
+    if let Some(index_proxy) = get_proxy_for(cx, obj.get())
+        .and_then(|proxy| find_proxy_in_chain(proxy, |p| p.index_getter.is_some()))
+    {
+        let obj_id = get_obj_id_for(cx, obj.get());
+        let length = index_proxy.index_length.as_ref().unwrap();
+        let len = match length(cx, obj_id) {
+            Ok(len) => len,
+            Err(js_err) => {
+                let s = format!("length failed\ncaused by: {}\0", js_err);
+                JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                return false;
+            }
+        };
+        for index in 0..len {
+            if crate::jsapi_utils::objects::has_own_es_obj_prop(
+                cx,
+                rhandle,
+                format!("{}", index).as_str(),
+            )
+            .is_err()
+            {
+                return false;
+            }
+        }
+    }
+
+    let mut current = get_proxy_for(cx, obj.get());
+    while let Some(proxy) = current {
+        for prop_name in proxy.properties.keys() {
+            // checking for an own prop calls the resolve hook
+            if crate::jsapi_utils::objects::has_own_es_obj_prop(cx, rhandle, prop_name).is_err() {
+                return false;
+            }
+        }
+        current = proxy
+            .extends
+            .as_ref()
+            .and_then(|parent_name| get_proxy(parent_name));
+    }
 
-                    *resolved = true;
-                    trace!("resolved native method {}", prop_name);
+    if let Some(dyn_proxy) = get_proxy_for(cx, obj.get())
+        .and_then(|proxy| find_proxy_in_chain(proxy, |p| p.dynamic_properties.is_some()))
+    {
+        let dynamic_properties = dyn_proxy.dynamic_properties.as_ref().unwrap();
+        let obj_id = get_obj_id_for(cx, obj.get());
+        match (dynamic_properties.keys)(cx, obj_id) {
+            Ok(keys) => {
+                for key in &keys {
+                    if crate::jsapi_utils::objects::has_own_es_obj_prop(cx, rhandle, key.as_str())
+                        .is_err()
+                    {
+                        return false;
+                    }
+                }
+                REMOVING_DYNAMIC_PROPS.with(|removing_rc| *removing_rc.borrow_mut() = true);
+                let remove_ok = remove_stale_dynamic_props(cx, rhandle, &keys);
+                REMOVING_DYNAMIC_PROPS.with(|removing_rc| *removing_rc.borrow_mut() = false);
+                if !remove_ok {
+                    return false;
                 }
             }
-        });
+            Err(js_err) => {
+                let s = format!("keys failed\ncaused by: {}\0", js_err);
+                JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// dynamic properties are defined on an instance when they are first resolved, remove those which
+/// are no longer returned by the keys hook so they are not enumerated or found by the in operator
+unsafe fn remove_stale_dynamic_props(
+    cx: *mut JSContext,
+    obj: HandleObject,
+    keys: &[String],
+) -> bool {
+    let mut ids = mozjs::rust::IdVector::new(cx);
+    if !mozjs::rust::jsapi_wrapped::GetPropertyKeys(
+        cx,
+        obj,
+        mozjs::jsapi::JSITER_OWNONLY,
+        ids.handle_mut(),
+    ) {
+        return false;
+    }
+
+    let mut stale = vec![];
+    for x in 0..ids.len() {
+        rooted!(in (cx) let id = ids[x]);
+        if !mozjs::glue::RUST_JSID_IS_STRING(id.handle().into()) {
+            continue;
+        }
+        let prop_name = es_jsid_to_string(cx, id.handle().into());
+        if !keys.contains(&prop_name)
+            && get_dynamic_proxy_for(cx, obj.get(), prop_name.as_str()).is_some()
+        {
+            stale.push(prop_name);
+        }
     }
 
+    for prop_name in stale {
+        trace!("remove stale dynamic prop {}", prop_name);
+        if crate::jsapi_utils::objects::delete_es_obj_prop(cx, obj, prop_name.as_str()).is_err() {
+            return false;
+        }
+    }
     true
 }
 
+/// define the constants of a proxy class on an object
+fn define_constants(
+    cx: *mut JSContext,
+    obj: HandleObject,
+    constants: &mut HashMap<&'static str, ConstantValue>,
+) {
+    for (name, value) in constants.drain() {
+        rooted!(in (cx) let mut val_root = UndefinedValue());
+        value(cx, val_root.handle_mut());
+        crate::jsapi_utils::objects::set_es_obj_prop_val_permanent(
+            cx,
+            obj,
+            name,
+            val_root.handle(),
+        );
+    }
+}
+
+/// find the proxy class in the chain of extended classes which matches a predicate, starting with proxy itself
+fn find_proxy_in_chain<P>(proxy: Arc<Proxy>, predicate: P) -> Option<Arc<Proxy>>
+where
+    P: Fn(&Proxy) -> bool,
+{
+    let mut current = Some(proxy);
+    while let Some(p) = current {
+        if predicate(&p) {
+            return Some(p);
+        }
+        current = p
+            .extends
+            .as_ref()
+            .and_then(|parent_name| get_proxy(parent_name));
+    }
+    None
+}
+
+/// check if a script subclass of a proxy class defines a member with a specific name
+/// this walks the prototype chain of an instance until the prototype of a proxy class is reached
+fn is_overridden_in_script(cx: *mut JSContext, obj: HandleObject, prop_name: &str) -> bool {
+    rooted!(in (cx) let mut proto_root = ptr::null_mut::<JSObject>());
+    if crate::jsapi_utils::objects::get_prototype(cx, obj, proto_root.handle_mut()).is_err() {
+        return false;
+    }
+    while !proto_root.is_null() {
+        if crate::jsapi_utils::objects::has_own_es_obj_prop(
+            cx,
+            proto_root.handle(),
+            PROXY_PROP_PROTOTYPE_OF,
+        )
+        .unwrap_or(true)
+        {
+            return false;
+        }
+        if crate::jsapi_utils::objects::has_own_es_obj_prop(cx, proto_root.handle(), prop_name)
+            .unwrap_or(false)
+        {
+            return true;
+        }
+        rooted!(in (cx) let mut next_proto_root = ptr::null_mut::<JSObject>());
+        if crate::jsapi_utils::objects::get_prototype(
+            cx,
+            proto_root.handle(),
+            next_proto_root.handle_mut(),
+        )
+        .is_err()
+        {
+            return false;
+        }
+        proto_root.set(next_proto_root.get());
+    }
+    false
+}
+
 unsafe extern "C" fn proxy_instance_getter(
     cx: *mut JSContext,
     argc: u32,
@@ -932,9 +3030,12 @@ unsafe extern "C" fn proxy_instance_getter(
 
                 let p_name = &prop_name[4..];
 
-                if let Some(prop) = proxy.properties.get(p_name) {
+                if let Some(prop_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.properties.contains_key(p_name))
+                {
+                    let getter = prop_proxy.properties[p_name].getter.as_ref().unwrap();
                     rooted!(in (cx) let mut rval = UndefinedValue());
-                    let js_val_res = prop.0(cx, obj_id, rval.handle_mut());
+                    let js_val_res = getter(cx, obj_id, rval.handle_mut());
                     trace!("got val for getter");
                     match js_val_res {
                         Ok(()) => {
@@ -984,9 +3085,15 @@ unsafe extern "C" fn proxy_static_getter(
 
                 let p_name = &prop_name[4..];
 
-                if let Some(prop) = proxy.static_properties.get(p_name) {
+                if let Some(prop_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.static_properties.contains_key(p_name))
+                {
+                    let getter = prop_proxy.static_properties[p_name]
+                        .getter
+                        .as_ref()
+                        .unwrap();
                     rooted!(in (cx) let mut rval = UndefinedValue());
-                    let js_val_res = prop.0(cx, rval.handle_mut());
+                    let js_val_res = getter(cx, rval.handle_mut());
                     trace!("got val for static_getter");
                     match js_val_res {
                         Ok(()) => {
@@ -1012,8 +3119,21 @@ pub fn get_obj_id_for(cx: *mut JSContext, obj: *mut JSObject) -> i32 {
     crate::jsapi_utils::objects::get_es_obj_prop_val_as_i32(cx, obj_handle, PROXY_PROP_OBJ_ID)
 }
 
+/// check if an object is an instance of a proxy class (and not e.g. the constructor of the class)
+pub fn is_proxy_instance(obj: *mut JSObject) -> bool {
+    unsafe { mozjs::jsapi::JS_GetClass(obj) == &ES_PROXY_CLASS as *const JSClass }
+}
+
 /// Get the Proxy of which an object is an instance
+/// for an instance this is the proxy which created it, even if the class was replaced since
 pub fn get_proxy_for(cx: *mut JSContext, obj: *mut JSObject) -> Option<Arc<Proxy>> {
+    if is_proxy_instance(obj) {
+        return PROXY_INSTANCE_PROXIES.with(|pip_rc| {
+            let pip = &*pip_rc.borrow();
+            pip.get(&(obj as usize)).cloned()
+        });
+    }
+
     let obj_handle = unsafe { mozjs::rust::HandleObject::from_marked_location(&obj) };
     let cn_res = crate::jsapi_utils::objects::get_es_obj_prop_val_as_string(
         cx,
@@ -1030,6 +3150,8 @@ pub fn get_proxy_for(cx: *mut JSContext, obj: *mut JSObject) -> Option<Arc<Proxy
     None
 }
 
+/// get the registered Proxy of a constructor or singleton object, this returns None if the
+/// class was unregistered or replaced
 fn get_static_proxy_for(cx: *mut JSContext, obj: *mut JSObject) -> Option<Arc<Proxy>> {
     let obj_handle = unsafe { mozjs::rust::HandleObject::from_marked_location(&obj) };
     let cn_res = crate::jsapi_utils::objects::get_es_obj_prop_val_as_string(
@@ -1038,10 +3160,15 @@ fn get_static_proxy_for(cx: *mut JSContext, obj: *mut JSObject) -> Option<Arc<Pr
         PROXY_PROP_CLASS_NAME,
     );
     if let Ok(class_name) = cn_res {
-        return PROXIES.with(|proxies_rc| {
-            let proxies = &*proxies_rc.borrow();
-            proxies.get(class_name.as_str()).cloned()
-        });
+        let proxy = get_proxy(class_name.as_str())?;
+        let id = crate::jsapi_utils::objects::get_es_obj_prop_val_as_i32(
+            cx,
+            obj_handle,
+            PROXY_PROP_CLASS_ID,
+        );
+        if proxy.id as i32 == id {
+            return Some(proxy);
+        }
     }
 
     None
@@ -1083,11 +3210,14 @@ unsafe extern "C" fn proxy_instance_setter(
                 // strip "set " from propname
                 let p_name = &prop_name[4..];
 
-                if let Some(prop) = proxy.properties.get(p_name) {
+                if let Some(prop_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.properties.contains_key(p_name))
+                {
+                    let setter = prop_proxy.properties[p_name].setter.as_ref().unwrap();
                     let val = HandleValue::from_marked_location(&args.index(0).get());
 
                     trace!("reflection::setter setting val");
-                    let js_val_res = prop.1(cx, obj_id, val);
+                    let js_val_res = setter(cx, obj_id, val);
                     if let Err(js_err) = js_val_res {
                         let s = format!("setter {} failed\ncaused by: {}\0", p_name, js_err);
                         JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
@@ -1128,11 +3258,17 @@ unsafe extern "C" fn proxy_static_setter(
                 // strip "set " from propname
                 let p_name = &prop_name[4..];
 
-                if let Some(prop) = proxy.static_properties.get(p_name) {
+                if let Some(prop_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.static_properties.contains_key(p_name))
+                {
+                    let setter = prop_proxy.static_properties[p_name]
+                        .setter
+                        .as_ref()
+                        .unwrap();
                     let val = HandleValue::from_marked_location(&args.index(0).get());
 
                     trace!("reflection::static_setter setting val");
-                    let js_val_res = prop.1(cx, val);
+                    let js_val_res = setter(cx, val);
                     if let Err(js_err) = js_val_res {
                         let s = format!("setter {} failed\ncaused by: {}\0", p_name, js_err);
                         JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
@@ -1165,6 +3301,8 @@ unsafe extern "C" fn proxy_static_add_event_listener(
             .ok()
             .unwrap();
 
+        let options = get_event_listener_options(cx, &args);
+
         let thisv: mozjs::jsapi::Value = *args.thisv();
 
         if let Some(proxy) = get_static_proxy_for(cx, thisv.to_object()) {
@@ -1179,7 +3317,10 @@ unsafe extern "C" fn proxy_static_add_event_listener(
                 }
 
                 let listener_vec = obj_map.get_mut(type_str).unwrap();
-                listener_vec.push(listener_epr);
+                listener_vec.push(Rc::new(EventListenerEntry {
+                    listener: EventListenerFn::Script(listener_epr),
+                    once: options.once,
+                }));
             } else {
                 trace!(
                     "add_static_event_listener -> static event not defined: {}",
@@ -1222,13 +3363,12 @@ unsafe extern "C" fn proxy_static_remove_event_listener(
 
                 if obj_map.contains_key(type_str) {
                     let listener_vec = obj_map.get_mut(type_str).unwrap();
-                    for x in 0..listener_vec.len() {
-                        let epr = listener_vec.get(x).unwrap();
-                        if epr.get() == listener_obj {
-                            trace!("remove static event listener for {}", type_str);
-                            listener_vec.remove(x);
-                            break;
-                        }
+                    if let Some(x) = listener_vec
+                        .iter()
+                        .position(|entry| entry.is_script_listener(listener_obj))
+                    {
+                        trace!("remove static event listener for {}", type_str);
+                        listener_vec.remove(x);
                     }
                 }
             }
@@ -1259,7 +3399,15 @@ unsafe extern "C" fn proxy_static_dispatch_event(
             if proxy.static_events.contains(&type_str.as_str()) {
                 let type_str = &&(*(*proxy.static_events.get(type_str.as_str()).unwrap()));
 
-                dispatch_static_event_for_proxy(cx, proxy.borrow(), type_str, evt_obj_handle_val);
+                let default_prevented = dispatch_static_event_for_proxy(
+                    cx,
+                    proxy.borrow(),
+                    type_str,
+                    evt_obj_handle_val,
+                    &mut |_cx, _res| {},
+                );
+                args.rval().set(BooleanValue(!default_prevented));
+                return true;
             }
         }
     }
@@ -1285,6 +3433,8 @@ unsafe extern "C" fn proxy_instance_add_event_listener(
             .ok()
             .unwrap();
 
+        let options = get_event_listener_options(cx, &args);
+
         let thisv: mozjs::jsapi::Value = *args.thisv();
 
         let obj_id = get_obj_id_for(cx, thisv.to_object());
@@ -1303,7 +3453,10 @@ unsafe extern "C" fn proxy_instance_add_event_listener(
                 }
 
                 let listener_vec = obj_map.get_mut(type_str).unwrap();
-                listener_vec.push(listener_epr);
+                listener_vec.push(Rc::new(EventListenerEntry {
+                    listener: EventListenerFn::Script(listener_epr),
+                    once: options.once,
+                }));
             } else {
                 trace!("add_event_listener -> event not defined: {}", type_str);
             }
@@ -1346,13 +3499,12 @@ unsafe extern "C" fn proxy_instance_remove_event_listener(
 
                     if obj_map.contains_key(type_str) {
                         let listener_vec = obj_map.get_mut(type_str).unwrap();
-                        for x in 0..listener_vec.len() {
-                            let epr = listener_vec.get(x).unwrap();
-                            if epr.get() == listener_obj {
-                                trace!("remove event listener for {}", type_str);
-                                listener_vec.remove(x);
-                                break;
-                            }
+                        if let Some(x) = listener_vec
+                            .iter()
+                            .position(|entry| entry.is_script_listener(listener_obj))
+                        {
+                            trace!("remove event listener for {}", type_str);
+                            listener_vec.remove(x);
                         }
                     }
                 }
@@ -1386,36 +3538,142 @@ unsafe extern "C" fn proxy_instance_dispatch_event(
             if proxy.events.contains(&type_str.as_str()) {
                 let type_str = &&(*(*proxy.events.get(type_str.as_str()).unwrap()));
 
-                dispatch_event_for_proxy(cx, proxy.borrow(), obj_id, type_str, evt_obj_handle_val);
+                let default_prevented = dispatch_event_for_proxy(
+                    cx,
+                    proxy.borrow(),
+                    obj_id,
+                    type_str,
+                    evt_obj_handle_val,
+                    &mut |_cx, _res| {},
+                );
+                args.rval().set(BooleanValue(!default_prevented));
+                return true;
             }
         }
     }
     true
 }
 
-// proxy can call this from Proxy::dispatch_event with esvf.to_es_val()
-fn dispatch_event_for_proxy(
+impl EventListenerEntry {
+    fn is_script_listener(&self, listener_obj: *mut JSObject) -> bool {
+        match &self.listener {
+            EventListenerFn::Script(epr) => epr.get() == listener_obj,
+            EventListenerFn::Rust(_, _) => false,
+        }
+    }
+
+    fn is_rust_listener(&self, listener_id: i32) -> bool {
+        match &self.listener {
+            EventListenerFn::Script(_) => false,
+            EventListenerFn::Rust(id, _) => *id == listener_id,
+        }
+    }
+}
+
+fn next_listener_id() -> i32 {
+    NEXT_LISTENER_ID.with(|rc| {
+        let id = &mut *rc.borrow_mut();
+        *id += 1;
+        *id
+    })
+}
+
+/// get the options from the third argument of addEventListener
+fn get_event_listener_options(cx: *mut JSContext, args: &CallArgs) -> EventListenerOptions {
+    let mut options = EventListenerOptions::default();
+    if args.argc_ >= 3 {
+        let options_val: mozjs::jsapi::Value = *args.index(2);
+        if options_val.is_object() {
+            rooted!(in (cx) let options_root = options_val.to_object());
+            rooted!(in (cx) let mut once_root = UndefinedValue());
+            let get_res = crate::jsapi_utils::objects::get_es_obj_prop_val(
+                cx,
+                options_root.handle(),
+                "once",
+                once_root.handle_mut(),
+            );
+            options.once = get_res.is_ok() && once_root.is_boolean() && once_root.to_boolean();
+        }
+    }
+    options
+}
+
+/// get the listeners to call for a dispatch, once listeners are removed from the vec before they are called
+/// the listeners are cloned so listeners may add or remove listeners while the event is dispatched
+fn listeners_for_dispatch(listener_vec: &mut EventListeners) -> EventListeners {
+    let ret = listener_vec.clone();
+    listener_vec.retain(|entry| !entry.once);
+    ret
+}
+
+/// add preventDefault() and defaultPrevented to an event object
+fn prepare_event_obj(cx: *mut JSContext, evt_obj: HandleObject) {
+    rooted!(in (cx) let false_root = BooleanValue(false));
+    crate::jsapi_utils::objects::set_es_obj_prop_val_hidden(
+        cx,
+        evt_obj,
+        "defaultPrevented",
+        false_root.handle(),
+    );
+    crate::jsapi_utils::functions::define_native_function(
+        cx,
+        evt_obj,
+        "preventDefault",
+        Some(event_prevent_default),
+    );
+}
+
+unsafe extern "C" fn event_prevent_default(
     cx: *mut JSContext,
-    proxy: &Proxy,
-    obj_id: i32,
-    evt_type: &str,
+    argc: u32,
+    vp: *mut mozjs::jsapi::Value,
+) -> bool {
+    let args = CallArgs::from_vp(vp, argc);
+    let thisv: mozjs::jsapi::Value = *args.thisv();
+    if thisv.is_object() {
+        rooted!(in (cx) let this_root = thisv.to_object());
+        rooted!(in (cx) let true_root = BooleanValue(true));
+        crate::jsapi_utils::objects::set_es_obj_prop_val_hidden(
+            cx,
+            this_root.handle(),
+            "defaultPrevented",
+            true_root.handle(),
+        );
+    }
+    args.rval().set(UndefinedValue());
+    true
+}
+
+/// call the listeners for an event
+/// returns true if a listener called preventDefault() on the event object or returned false
+fn call_event_listeners(
+    cx: *mut JSContext,
+    listeners: EventListeners,
     evt_obj: mozjs::jsapi::HandleValue,
-) {
-    let pel = &*proxy.event_listeners.borrow();
-    if let Some(obj_map) = pel.get(&obj_id) {
-        if let Some(listener_vec) = obj_map.get(evt_type) {
-            rooted!(in (cx) let mut ret_val = UndefinedValue());
-            // todo this_obj should be the proxy obj..
-            rooted!(in (cx) let this_obj = NullValue().to_object_or_null());
-            // since evt_obj is already rooted here we don;t need the auto_root macro, we can just use call_method_value()
-
-            for listener_epr in listener_vec {
-                let mut args_vec = vec![];
-                args_vec.push(*evt_obj);
-                let func_obj = listener_epr.get();
-                // todo why do we only have a call_method by val and not by HandleObject?
-                // the whole rooting func here could be avoided
-                rooted!(in (cx) let function_val = ObjectValue(func_obj));
+    on_result: &mut dyn FnMut(*mut JSContext, HandleValue),
+) -> bool {
+    if listeners.is_empty() {
+        return false;
+    }
+
+    let evt_val: mozjs::jsapi::Value = *evt_obj;
+    rooted!(in (cx) let evt_obj_root = evt_val.to_object_or_null());
+    if evt_val.is_object() {
+        prepare_event_obj(cx, evt_obj_root.handle());
+    }
+
+    let mut default_prevented = false;
+    rooted!(in (cx) let mut ret_val = UndefinedValue());
+    // todo this_obj should be the proxy obj..
+    rooted!(in (cx) let this_obj = NullValue().to_object_or_null());
+
+    for entry in listeners {
+        ret_val.set(UndefinedValue());
+        let res = match &entry.listener {
+            EventListenerFn::Script(listener_epr) => {
+                // since evt_obj is already rooted here we don;t need the auto_root macro, we can just use call_method_value()
+                let args_vec = vec![evt_val];
+                rooted!(in (cx) let function_val = ObjectValue(listener_epr.get()));
                 crate::jsapi_utils::functions::call_method_value(
                     cx,
                     this_obj.handle(),
@@ -1423,45 +3681,85 @@ fn dispatch_event_for_proxy(
                     args_vec,
                     ret_val.handle_mut(),
                 )
-                .ok()
-                .unwrap();
             }
+            EventListenerFn::Rust(_id, listener) => listener(
+                cx,
+                unsafe { HandleValue::from_raw(evt_obj) },
+                ret_val.handle_mut(),
+            )
+            .map_err(|message| EsErrorInfo {
+                message,
+                filename: "".to_string(),
+                lineno: 0,
+                column: 0,
+            }),
+        };
+        match res {
+            Ok(()) => {
+                if ret_val.is_boolean() && !ret_val.to_boolean() {
+                    default_prevented = true;
+                }
+                on_result(cx, ret_val.handle());
+            }
+            Err(err) => {
+                crate::spidermonkeyruntimewrapper::report_uncaught_error(err);
+            }
+        }
+    }
+
+    if evt_val.is_object() {
+        rooted!(in (cx) let mut prevented_root = UndefinedValue());
+        let get_res = crate::jsapi_utils::objects::get_es_obj_prop_val(
+            cx,
+            evt_obj_root.handle(),
+            "defaultPrevented",
+            prevented_root.handle_mut(),
+        );
+        if get_res.is_ok() && prevented_root.is_boolean() && prevented_root.to_boolean() {
+            default_prevented = true;
         }
     }
+
+    default_prevented
 }
 
-fn dispatch_static_event_for_proxy(
+// proxy can call this from Proxy::dispatch_event with esvf.to_es_val()
+fn dispatch_event_for_proxy(
     cx: *mut JSContext,
     proxy: &Proxy,
+    obj_id: i32,
     evt_type: &str,
     evt_obj: mozjs::jsapi::HandleValue,
-) {
-    let obj_map = &*proxy.static_event_listeners.borrow();
-
-    if let Some(listener_vec) = obj_map.get(evt_type) {
-        rooted!(in (cx) let mut ret_val = UndefinedValue());
-        // todo this_obj should be the proxy obj..
-        rooted!(in (cx) let this_obj = NullValue().to_object_or_null());
-        // since evt_obj is already rooted here we don;t need the auto_root macro, we can just use call_method_value()
+    on_result: &mut dyn FnMut(*mut JSContext, HandleValue),
+) -> bool {
+    let listeners = {
+        let pel = &mut *proxy.event_listeners.borrow_mut();
+        match pel
+            .get_mut(&obj_id)
+            .and_then(|obj_map| obj_map.get_mut(evt_type))
+        {
+            Some(listener_vec) => listeners_for_dispatch(listener_vec),
+            None => vec![],
+        }
+    };
+    call_event_listeners(cx, listeners, evt_obj, on_result)
+}
 
-        for listener_epr in listener_vec {
-            let mut args_vec = vec![];
-            args_vec.push(*evt_obj);
-            let func_obj = listener_epr.get();
-            // todo why do we only have a call_method by val and not by HandleObject?
-            // the whole rooting func here could be avoided
-            rooted!(in (cx) let function_val = ObjectValue(func_obj));
-            crate::jsapi_utils::functions::call_method_value(
-                cx,
-                this_obj.handle(),
-                function_val.handle(),
-                args_vec,
-                ret_val.handle_mut(),
-            )
-            .ok()
-            .unwrap();
+fn dispatch_static_event_for_proxy(
+    cx: *mut JSContext,
+    proxy: &Proxy,
+    evt_type: &str,
+    evt_obj: mozjs::jsapi::HandleValue,
+    on_result: &mut dyn FnMut(*mut JSContext, HandleValue),
+) -> bool {
+    let listeners = {
+        let obj_map = &mut *proxy.static_event_listeners.borrow_mut();
+        match obj_map.get_mut(evt_type) {
+            Some(listener_vec) => listeners_for_dispatch(listener_vec),
+            None => vec![],
         }
-    }
+    };
+    call_event_listeners(cx, listeners, evt_obj, on_result)
 }
 
 unsafe extern "C" fn proxy_instance_method(
@@ -1495,7 +3793,10 @@ unsafe extern "C" fn proxy_instance_method(
 
                 let p_name = prop_name.as_str();
 
-                if let Some(prop) = proxy.methods.get(p_name) {
+                if let Some(method_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.methods.contains_key(p_name))
+                {
+                    let prop = &method_proxy.methods[p_name];
                     trace!("got method for method");
 
                     let mut args_vec = vec![];
@@ -1548,7 +3849,10 @@ unsafe extern "C" fn proxy_static_method(
 
                 let p_name = prop_name.as_str();
 
-                if let Some(prop) = proxy.static_methods.get(p_name) {
+                if let Some(method_proxy) =
+                    find_proxy_in_chain(proxy.clone(), |p| p.static_methods.contains_key(p_name))
+                {
+                    let prop = &method_proxy.static_methods[p_name];
                     trace!("got method for static_method");
 
                     let mut args_vec = vec![];
@@ -1587,17 +3891,20 @@ unsafe extern "C" fn proxy_instance_finalize(_fop: *mut JSFreeOp, object: *mut J
     });
 
     if let Some(id) = id_opt {
-        let cn = PROXY_INSTANCE_CLASSNAMES
-            .with(|piid_rc| {
-                let piid = &mut *piid_rc.borrow_mut();
-                piid.remove(&id)
-            })
-            .unwrap();
+        let proxy_opt = PROXY_INSTANCE_PROXIES.with(|pip_rc| {
+            let pip = &mut *pip_rc.borrow_mut();
+            pip.remove(&ptr_usize)
+        });
 
-        trace!("finalize id {} of type {}", id, cn);
-        if let Some(proxy) = get_proxy(cn.as_str()) {
-            if let Some(finalizer) = &proxy.finalizer {
-                finalizer(id);
+        trace!("finalize id {}", id);
+        if let Some(proxy) = proxy_opt {
+            // the id was created by the constructor of this class or of the class it extends
+            if let Some(constructor_proxy) =
+                find_proxy_in_chain(proxy.clone(), |p| p.constructor.is_some())
+            {
+                if let Some(finalizer) = &constructor_proxy.finalizer {
+                    finalizer(id);
+                }
             }
 
             // clear event listeners
@@ -1609,6 +3916,11 @@ unsafe extern "C" fn proxy_instance_finalize(_fop: *mut JSFreeOp, object: *mut J
 
 const PROXY_PROP_CLASS_NAME: &str = "__proxy_class_name__";
 const PROXY_PROP_OBJ_ID: &str = "__proxy_obj_id__";
+const PROXY_PROP_CLASS_ID: &str = "__proxy_class_id__";
+const PROXY_PROP_PROTOTYPE_OF: &str = "__proxy_prototype_of__";
+const PROXY_PROP_ITERATOR_ID: &str = "__proxy_iterator_id__";
+// the prefix of the hidden props of instances, these are never passed to dynamic properties
+const PROXY_PROP_PREFIX: &str = "__proxy_";
 
 unsafe extern "C" fn proxy_construct(
     cx: *mut JSContext,
@@ -1630,9 +3942,12 @@ unsafe extern "C" fn proxy_construct(
     .unwrap();
     trace!("reflection::construct cn={}", class_name);
 
-    if let Some(proxy) = get_proxy(class_name.as_str()) {
+    if let Some(proxy) = get_static_proxy_for(cx, constructor_root.get()) {
         trace!("constructing proxy {}", class_name);
-        if let Some(constructor) = &proxy.constructor {
+        if let Some(constructor_proxy) =
+            find_proxy_in_chain(proxy.clone(), |p| p.constructor.is_some())
+        {
+            let constructor = constructor_proxy.constructor.as_ref().unwrap();
             trace!("constructing proxy constructor {}", class_name);
 
             let mut args_vec = vec![];
@@ -1644,7 +3959,34 @@ unsafe extern "C" fn proxy_construct(
 
             if obj_id_res.is_ok() {
                 let obj_id = obj_id_res.ok().unwrap();
-                let ret: *mut JSObject = mozjs::jsapi::JS_NewObject(cx, &ES_PROXY_CLASS);
+
+                // when constructed from a script subclass (super()) new.target is the subclass
+                rooted!(in (cx) let mut proto_root = ptr::null_mut::<JSObject>());
+                if args.is_constructing() {
+                    rooted!(in (cx) let new_target_root = args.new_target().to_object());
+                    rooted!(in (cx) let mut proto_val_root = UndefinedValue());
+                    if crate::jsapi_utils::objects::get_es_obj_prop_val(
+                        cx,
+                        new_target_root.handle(),
+                        "prototype",
+                        proto_val_root.handle_mut(),
+                    )
+                    .is_ok()
+                        && proto_val_root.is_object()
+                    {
+                        proto_root.set(proto_val_root.to_object());
+                    }
+                }
+
+                let ret: *mut JSObject = if proto_root.is_null() {
+                    mozjs::jsapi::JS_NewObject(cx, &ES_PROXY_CLASS)
+                } else {
+                    mozjs::jsapi::JS_NewObjectWithGivenProto(
+                        cx,
+                        &ES_PROXY_CLASS,
+                        proto_root.handle().into(),
+                    )
+                };
 
                 rooted!(in (cx) let ret_root = ret);
                 rooted!(in (cx) let pname_root = crate::jsapi_utils::new_es_value_from_str(cx, &class_name));
@@ -1667,9 +4009,9 @@ unsafe extern "C" fn proxy_construct(
                     piid.insert(ret as usize, obj_id);
                 });
 
-                PROXY_INSTANCE_CLASSNAMES.with(|piid_rc| {
-                    let piid = &mut *piid_rc.borrow_mut();
-                    piid.insert(obj_id, class_name.clone());
+                PROXY_INSTANCE_PROXIES.with(|pip_rc| {
+                    let pip = &mut *pip_rc.borrow_mut();
+                    pip.insert(ret as usize, proxy.clone());
                 });
 
                 args.rval().set(ObjectValue(ret));
@@ -1683,7 +4025,8 @@ unsafe extern "C" fn proxy_construct(
         }
     }
 
-    JS_ReportErrorASCII(cx, b"no such class found\0".as_ptr() as *const libc::c_char);
+    let s = format!("class {} is not registered\0", class_name);
+    JS_ReportErrorASCII(cx, s.as_ptr() as *const libc::c_char);
 
     false
 }
@@ -103,12 +103,19 @@ extern crate log;
 #[macro_use]
 extern crate lazy_static;
 
+// so the code generated by es_runtime_derive (which uses ::es_runtime paths) also works in this crate
+extern crate self as es_runtime;
+
 mod debugmutex;
 mod enginehandleproducer;
 mod es_sys_scripts;
 #[macro_use]
 
 pub mod utils;
+pub mod esclass;
+pub mod esconversions;
+pub mod esfuture;
+pub mod esobjectref;
 pub mod esreflection;
 pub mod esruntime;
 pub mod esruntimebuilder;
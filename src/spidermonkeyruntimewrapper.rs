@@ -1,3 +1,5 @@
+use crate::esobjectref::EsObjectRef;
+use crate::esruntime::{PromiseRejectionInfo, PromiseRejectionState};
 use crate::esruntimeinner::EsRuntimeInner;
 use crate::esvaluefacade::EsValueFacade;
 use crate::jsapi_utils;
@@ -5,7 +7,7 @@ use crate::jsapi_utils::rooting::EsPersistentRooted;
 use crate::jsapi_utils::EsErrorInfo;
 use crate::utils::AutoIdMap;
 
-use log::{debug, trace};
+use log::{debug, error, trace};
 use lru::LruCache;
 
 use mozjs::glue::{CreateJobQueue, JobQueueTraps};
@@ -21,8 +23,10 @@ use mozjs::jsapi::JS_NewArrayObject;
 use mozjs::jsapi::JS_NewGlobalObject;
 use mozjs::jsapi::JS_ReportErrorASCII;
 use mozjs::jsapi::OnNewGlobalHookOption;
+use mozjs::jsapi::PromiseRejectionHandlingState;
 use mozjs::jsapi::SetJobQueue;
 use mozjs::jsapi::SetModuleResolveHook;
+use mozjs::jsapi::SetPromiseRejectionTrackerCallback;
 use mozjs::jsapi::JS::HandleValueArray;
 use mozjs::jsval::{NullValue, ObjectValue, UndefinedValue};
 use mozjs::panic::wrap_panic;
@@ -34,9 +38,10 @@ use mozjs::rust::SIMPLE_GLOBAL_CLASS;
 
 use std::borrow::Borrow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::os::raw::c_void;
 
+use std::mem::replace;
 use std::ptr;
 use std::rc::Rc;
 use std::str;
@@ -122,6 +127,14 @@ impl SmRuntime {
         })
     }
 
+    /// get the names of the functions added with add_global_function
+    pub(crate) fn get_global_function_names() -> Vec<&'static str> {
+        GLOBAL_OPS.with(|global_ops_rc| {
+            let global_ops = &*global_ops_rc.borrow();
+            global_ops.keys().cloned().collect()
+        })
+    }
+
     /// construct a new SmRuntime, this should only be called from the workerthread of the MicroTaskManager
     /// here we actualy construct a new Runtime
     fn new() -> Self {
@@ -189,6 +202,12 @@ impl SmRuntime {
 
         self.do_with_jsapi(|_rt, cx, _global| unsafe {
             SetJobQueue(cx, job_queue);
+            // this tells JSAPI who to notify about unhandled rejections
+            SetPromiseRejectionTrackerCallback(
+                cx,
+                Some(promise_rejection_tracker),
+                ptr::null_mut(),
+            );
         });
     }
 
@@ -252,6 +271,36 @@ impl SmRuntime {
         })
     }
 
+    /// eval a piece of script and return the resulting object as a live EsObjectRef
+    /// this fails if the script does not evaluate to an object
+    pub fn eval_object_ref(
+        &self,
+        eval_code: &str,
+        file_name: &str,
+    ) -> Result<EsObjectRef, EsErrorInfo> {
+        trace!(
+            "smrt.eval_object_ref {} in thread {}",
+            file_name,
+            thread_id::get()
+        );
+
+        self.do_with_jsapi(|rt, cx, global| {
+            rooted!(in (cx) let mut rval = UndefinedValue());
+            jsapi_utils::eval(rt, global, eval_code, file_name, rval.handle_mut())?;
+
+            if rval.is_object() {
+                Ok(EsObjectRef::new(cx, rval.to_object()))
+            } else {
+                Err(EsErrorInfo {
+                    message: format!("{} did not evaluate to an object", file_name),
+                    filename: file_name.to_string(),
+                    lineno: 0,
+                    column: 0,
+                })
+            }
+        })
+    }
+
     /// eval a piece of script and ignore the result
     pub fn eval_void(&self, eval_code: &str, file_name: &str) -> Result<(), EsErrorInfo> {
         trace!(
@@ -716,7 +765,10 @@ unsafe extern "C" fn enqueue_promise_job(
 
         let cb = PromiseJobCallback::new(cx, job.get());
 
+        ENQUEUED_PROMISE_JOBS.with(|rc| *rc.borrow_mut() += 1);
+
         let task = move || {
+            STARTED_PROMISE_JOBS.with(|rc| *rc.borrow_mut() += 1);
             SM_RT.with(move |rc| {
                 trace!("running a job");
 
@@ -732,9 +784,16 @@ unsafe extern "C" fn enqueue_promise_job(
                     if call_res.is_err() {
                         debug!("job failed");
                         if let Some(err) = jsapi_utils::report_es_ex(cx) {
-                            panic!(
-                                "job failed {}:{}:{} -> {}",
-                                err.filename, err.lineno, err.column, err.message
+                            sm_rt.clone_esrt_inner().report_promise_rejection(
+                                PromiseRejectionInfo {
+                                    state: PromiseRejectionState::JobFailed,
+                                    promise_id: 0,
+                                    reason: EsValueFacade::new_str(err.message),
+                                    stack: format!(
+                                        "{}:{}:{}",
+                                        err.filename, err.lineno, err.column
+                                    ),
+                                },
                             );
                         }
                     }
@@ -755,6 +814,188 @@ unsafe extern "C" fn enqueue_promise_job(
     result
 }
 
+/// report an error which was thrown in an async script context (e.g. a setImmediate callback or an event listener)
+/// the error is dispatched to the script first (onerror and addEventListener('error', ...)) and then
+/// passed to the UncaughtErrorHandler of the EsRuntime
+pub(crate) fn report_uncaught_error(err: EsErrorInfo) {
+    SM_RT.with(|sm_rt_rc| {
+        let sm_rt = &*sm_rt_rc.borrow();
+
+        let error_info = EsValueFacade::new_obj(vec![
+            (
+                "message".to_string(),
+                EsValueFacade::new_str(err.message.clone()),
+            ),
+            (
+                "filename".to_string(),
+                EsValueFacade::new_str(err.filename.clone()),
+            ),
+            ("lineno".to_string(), EsValueFacade::new_i32(err.lineno)),
+            ("colno".to_string(), EsValueFacade::new_i32(err.column)),
+        ]);
+        let dispatch_res = sm_rt.call(vec![], "__dispatch_error_event", vec![error_info]);
+        if let Err(dispatch_err) = dispatch_res {
+            error!("could not dispatch error event: {}", dispatch_err.err_msg());
+        }
+
+        sm_rt.clone_esrt_inner().report_uncaught_error(&err);
+    });
+}
+
+/// the max number of promise ids kept in REPORTED_REJECTIONS, when a promise gets a rejection
+/// handler after more newer rejections were reported it is not reported as Handled anymore
+const MAX_REPORTED_REJECTIONS: usize = 1000;
+
+thread_local! {
+    /// the number of promise jobs which were enqueued
+    static ENQUEUED_PROMISE_JOBS: RefCell<u64> = RefCell::new(0);
+    /// the number of promise jobs which started running, jobs run in the order they were enqueued
+    static STARTED_PROMISE_JOBS: RefCell<u64> = RefCell::new(0);
+    /// rejections which were tracked but not reported yet, the promises are rooted until they are reported
+    static PENDING_REJECTIONS: RefCell<Vec<(u64, PromiseRejectionState, EsPersistentRooted)>> = RefCell::new(vec![]);
+    /// the ids of the promises which were most recently reported as Unhandled, oldest first
+    static REPORTED_REJECTIONS: RefCell<VecDeque<u64>> = RefCell::new(VecDeque::new());
+}
+
+/// this function is called by JSAPI when a promise is rejected without having a rejection handler
+/// or when a rejection handler is added to a promise which was rejected without one earlier
+/// the rejections are queued here and reported by report_pending_rejections when the promise jobs which were
+/// enqueued at that time have run, so a promise which gets a rejection handler before that is never reported
+unsafe extern "C" fn promise_rejection_tracker(
+    cx: *mut JSContext,
+    _muted_errors: bool,
+    promise: mozjs::jsapi::HandleObject,
+    state: PromiseRejectionHandlingState,
+    _data: *mut c_void,
+) {
+    wrap_panic(&mut || {
+        trace!("promise_rejection_tracker called");
+
+        let promise_handle = mozjs::rust::Handle::from_raw(promise);
+        let promise_id = mozjs::rust::jsapi_wrapped::GetPromiseID(promise_handle);
+
+        let state = match state {
+            PromiseRejectionHandlingState::Unhandled => PromiseRejectionState::Unhandled,
+            PromiseRejectionHandlingState::Handled => {
+                let was_pending = PENDING_REJECTIONS.with(|rc| {
+                    let pending = &mut *rc.borrow_mut();
+                    let len = pending.len();
+                    pending.retain(|(id, _, _)| *id != promise_id);
+                    pending.len() != len
+                });
+                if was_pending {
+                    // handled before it was reported, so never report it
+                    return;
+                }
+                let was_reported = REPORTED_REJECTIONS.with(|rc| {
+                    let reported = &mut *rc.borrow_mut();
+                    if let Some(pos) = reported.iter().position(|id| *id == promise_id) {
+                        reported.remove(pos);
+                        true
+                    } else {
+                        false
+                    }
+                });
+                if !was_reported {
+                    return;
+                }
+                PromiseRejectionState::Handled
+            }
+        };
+
+        let schedule = PENDING_REJECTIONS.with(|rc| {
+            let pending = &mut *rc.borrow_mut();
+            pending.push((
+                promise_id,
+                state,
+                EsPersistentRooted::new_from_obj(cx, promise.get()),
+            ));
+            pending.len() == 1
+        });
+        if schedule {
+            SmRuntime::clone_current_esrt_inner_arc()
+                .task_manager
+                .add_task_from_worker(|| report_pending_rejections(None));
+        }
+    });
+}
+
+/// pass the queued rejections to the PromiseRejectionHandler of the EsRuntime
+/// on the first run this takes the number of enqueued promise jobs and waits (by rescheduling itself)
+/// until those jobs have run, jobs which are enqueued later on do not delay the report
+fn report_pending_rejections(jobs_to_run: Option<u64>) {
+    let jobs_to_run = jobs_to_run.unwrap_or_else(|| ENQUEUED_PROMISE_JOBS.with(|rc| *rc.borrow()));
+    let jobs_pending = STARTED_PROMISE_JOBS.with(|rc| *rc.borrow() < jobs_to_run);
+    if jobs_pending {
+        SmRuntime::clone_current_esrt_inner_arc()
+            .task_manager
+            .add_task_from_worker(move || report_pending_rejections(Some(jobs_to_run)));
+        return;
+    }
+
+    let pending = PENDING_REJECTIONS.with(|rc| replace(&mut *rc.borrow_mut(), vec![]));
+    if pending.is_empty() {
+        return;
+    }
+
+    SM_RT.with(|sm_rt_rc| {
+        let sm_rt = &*sm_rt_rc.borrow();
+        let esrt_inner = sm_rt.clone_esrt_inner();
+        sm_rt.do_with_jsapi(|rt, cx, global| {
+            for (promise_id, state, promise_epr) in pending {
+                rooted!(in (cx) let promise_root = promise_epr.get());
+                rooted!(in (cx) let mut reason_root = UndefinedValue());
+                if jsapi_utils::promises::get_promise_result(
+                    promise_root.handle(),
+                    reason_root.handle_mut(),
+                )
+                .is_err()
+                {
+                    continue;
+                }
+
+                let mut stack = "".to_string();
+                if reason_root.is_object() {
+                    rooted!(in (cx) let reason_obj_root = reason_root.to_object());
+                    rooted!(in (cx) let mut stack_root = UndefinedValue());
+                    if jsapi_utils::objects::get_es_obj_prop_val(
+                        cx,
+                        reason_obj_root.handle(),
+                        "stack",
+                        stack_root.handle_mut(),
+                    )
+                    .is_ok()
+                        && stack_root.is_string()
+                    {
+                        if let Ok(stack_str) = jsapi_utils::es_value_to_str(cx, *stack_root) {
+                            stack = stack_str;
+                        }
+                    }
+                }
+
+                if state == PromiseRejectionState::Unhandled {
+                    REPORTED_REJECTIONS.with(|rc| {
+                        let reported = &mut *rc.borrow_mut();
+                        if reported.len() >= MAX_REPORTED_REJECTIONS {
+                            reported.pop_front();
+                        }
+                        reported.push_back(promise_id);
+                    });
+                }
+
+                let reason = EsValueFacade::new_v(rt, cx, global, reason_root.handle());
+
+                esrt_inner.report_promise_rejection(PromiseRejectionInfo {
+                    state,
+                    promise_id,
+                    reason,
+                    stack,
+                });
+            }
+        });
+    });
+}
+
 /// the code below was copied and altered from the servo project
 /// https://github.com/servo/servo
 /// so it falls under this LICENSE https://raw.githubusercontent.com/servo/servo/master/LICENSE